@@ -220,3 +220,52 @@ pub fn cross_file_error_diagnostics(
 
     result
 }
+
+/// Group every diagnostic in a project-wide solc output by file.
+///
+/// Unlike [`cross_file_error_diagnostics`] this keeps all severities and
+/// does not skip any file: each file named in a `sourceLocation` is read
+/// from disk once and its diagnostics are produced by
+/// [`build_output_to_diagnostics`].  Used for `workspace/diagnostic`.
+///
+/// Returns a map of `absolute_path → Vec<Diagnostic>`.  The `project_root`
+/// resolves relative paths.
+pub fn project_diagnostics(
+    solc_output: &Value,
+    project_root: &Path,
+    ignored_error_codes: &[u64],
+) -> HashMap<PathBuf, Vec<Diagnostic>> {
+    let Some(errors) = solc_output.get("errors").and_then(|v| v.as_array()) else {
+        return HashMap::new();
+    };
+
+    let mut result: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+    for err in errors {
+        let Some(source_file) = err
+            .get("sourceLocation")
+            .and_then(|loc| loc.get("file"))
+            .and_then(|f| f.as_str())
+        else {
+            continue;
+        };
+        let source_path = Path::new(source_file);
+        let abs_path = if source_path.is_absolute() {
+            source_path.to_path_buf()
+        } else {
+            project_root.join(source_path)
+        };
+        if result.contains_key(&abs_path) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&abs_path) else {
+            continue;
+        };
+        let diags =
+            build_output_to_diagnostics(solc_output, &abs_path, &content, ignored_error_codes);
+        if !diags.is_empty() {
+            result.insert(abs_path, diags);
+        }
+    }
+
+    result
+}
@@ -640,7 +640,7 @@ pub fn find_node_info<'a>(
 }
 
 /// Resolve an absolute path to a filesystem path by searching `id_to_path_map`.
-fn find_file_path(
+pub(crate) fn find_file_path(
     abs_path: &str,
     id_to_path_map: &HashMap<SolcFileId, String>,
 ) -> Option<std::path::PathBuf> {
@@ -0,0 +1,817 @@
+//! `solidity.changeSignature`: reorder, add or remove the parameters of a
+//! function and update everything that depends on them.
+//!
+//! The new parameter list mixes existing parameters, by their current index,
+//! with new ones, which carry the expression to pass at existing call sites.
+//! One workspace edit rewrites:
+//!
+//! - the declaration and every override / implementation linked through
+//!   [`CachedBuild::base_function_implementation`],
+//! - every call site, positional or with named arguments `f({a: x})`,
+//!   including calls nested in the arguments of another call,
+//! - the `@param` lines of each declaration's NatSpec.
+//!
+//! Call sites come from the builds' reference index; the text is read with
+//! tree-sitter so argument layout (one per line or inline) is preserved.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Deserialize;
+use tower_lsp::lsp_types::{Range, TextEdit, Url};
+use tree_sitter::{Node, Tree};
+
+use crate::goto::{CachedBuild, NodeInfo};
+use crate::references;
+use crate::types::{NodeId, SourceLoc};
+use crate::utils::{byte_offset_to_position, is_valid_solidity_identifier};
+
+/// One entry of the new parameter list.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterSpec {
+    /// Keep the parameter currently at index `from`.
+    Existing { from: usize },
+    /// Add a parameter. `default` is passed at existing call sites and is
+    /// required when there are any.
+    New {
+        #[serde(rename = "type")]
+        type_name: String,
+        name: String,
+        #[serde(default)]
+        default: Option<String>,
+    },
+}
+
+/// A replacement whose text may embed (rewritten) source ranges, so calls
+/// nested in the arguments of another rewritten call compose.
+struct Rewrite {
+    start: usize,
+    end: usize,
+    pieces: Vec<Piece>,
+}
+
+enum Piece {
+    Text(String),
+    Source(usize, usize),
+}
+
+/// A file read and parsed once for all the rewrites in it.
+struct File {
+    source: String,
+    tree: Tree,
+}
+
+/// Edits changing the signature of the function at `byte` in `abs_path`
+/// (its declaration, or a call of it) to `parameters`.
+///
+/// Each build is searched in its own node-ID space and the results are
+/// merged. `read_source` returns the current text of a file by path.
+pub fn change_signature(
+    builds: &[&CachedBuild],
+    abs_path: &str,
+    byte: usize,
+    parameters: &[ParameterSpec],
+    read_source: impl Fn(&str) -> Option<String>,
+) -> Result<HashMap<Url, Vec<TextEdit>>, String> {
+    let mut files: HashMap<String, File> = HashMap::new();
+    let mut rewrites: HashMap<String, Vec<Rewrite>> = HashMap::new();
+    let mut seen: HashSet<(String, usize, usize)> = HashSet::new();
+    let mut found = false;
+
+    for build in builds {
+        let path = build
+            .path_to_abs
+            .get(abs_path)
+            .map(|p| p.as_str())
+            .unwrap_or(abs_path);
+        let Some(target) = target_function(build, path, byte) else {
+            continue;
+        };
+        found = true;
+        for (file, rewrite) in build_rewrites(build, target, parameters, &read_source, &mut files)?
+        {
+            if seen.insert((file.clone(), rewrite.start, rewrite.end)) {
+                rewrites.entry(file).or_default().push(rewrite);
+            }
+        }
+    }
+    if !found {
+        return Err("No function declaration or call at the cursor".to_string());
+    }
+
+    let mut changes = HashMap::new();
+    for (path, mut file_rewrites) in rewrites {
+        let Some(file) = files.get(&path) else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(&path) else {
+            continue;
+        };
+        file_rewrites.sort_by_key(|r| (r.start, std::cmp::Reverse(r.end)));
+        let mut edits = Vec::new();
+        let mut covered = 0;
+        for (i, rewrite) in file_rewrites.iter().enumerate() {
+            if rewrite.start < covered {
+                continue;
+            }
+            covered = rewrite.end;
+            edits.push(TextEdit {
+                range: Range {
+                    start: byte_offset_to_position(&file.source, rewrite.start),
+                    end: byte_offset_to_position(&file.source, rewrite.end),
+                },
+                new_text: render(&file.source, &file_rewrites[i + 1..], rewrite),
+            });
+        }
+        changes.insert(uri, edits);
+    }
+    Ok(changes)
+}
+
+/// The `FunctionDefinition` at `byte`, or the one called there.
+fn target_function(build: &CachedBuild, abs_path: &str, byte: usize) -> Option<NodeId> {
+    let id = references::byte_to_id(&build.nodes, abs_path, byte)?;
+    let info = build.nodes.get(abs_path)?.get(&id)?;
+    let id = match info.node_type.as_deref() {
+        Some("FunctionDefinition") => id,
+        _ => info.referenced_declaration?,
+    };
+    let (_, decl) = find_node(build, id)?;
+    (decl.node_type.as_deref() == Some("FunctionDefinition")).then_some(id)
+}
+
+fn find_node(build: &CachedBuild, id: NodeId) -> Option<(&str, &NodeInfo)> {
+    build
+        .nodes
+        .iter()
+        .find_map(|(path, nodes)| nodes.get(&id).map(|info| (path.as_str(), info)))
+}
+
+/// `target` and every declaration it overrides or is overridden by.
+fn related_functions(build: &CachedBuild, target: NodeId) -> Vec<NodeId> {
+    let mut related = vec![target];
+    let mut queue = VecDeque::from([target]);
+    while let Some(id) = queue.pop_front() {
+        for &other in build
+            .base_function_implementation
+            .get(&id)
+            .into_iter()
+            .flatten()
+        {
+            if !related.contains(&other) {
+                related.push(other);
+                queue.push_back(other);
+            }
+        }
+    }
+    related
+}
+
+/// A declaration of the function being changed.
+struct Declaration {
+    /// Parameter names, `None` for unnamed parameters.
+    names: Vec<Option<String>>,
+    /// Declared in a library, so `x.f(...)` binds `x` to the first parameter.
+    in_library: bool,
+    library_name: String,
+}
+
+fn build_rewrites(
+    build: &CachedBuild,
+    target: NodeId,
+    parameters: &[ParameterSpec],
+    read_source: &impl Fn(&str) -> Option<String>,
+    files: &mut HashMap<String, File>,
+) -> Result<Vec<(String, Rewrite)>, String> {
+    let mut out = Vec::new();
+    let mut declarations: HashMap<NodeId, Declaration> = HashMap::new();
+
+    for id in related_functions(build, target) {
+        let Some((path, info)) = find_node(build, id) else {
+            continue;
+        };
+        let Some(loc) = SourceLoc::parse(&info.src) else {
+            continue;
+        };
+        let file = load(files, path, read_source)?;
+        let Some(function) = node_at(&file.tree, loc.offset, "function_definition") else {
+            continue;
+        };
+        if function.child_by_field_name("name").is_none() {
+            return Err("Only named functions can change signature".to_string());
+        }
+        let params = children(function, "parameter");
+        validate(parameters, params.len())?;
+        let names: Vec<Option<String>> = params
+            .iter()
+            .map(|p| {
+                p.child_by_field_name("name")
+                    .map(|n| file.source[n.byte_range()].to_string())
+            })
+            .collect();
+
+        if let Some((open, close)) = parens(function) {
+            let original = &file.source[open..close];
+            let items = parameters
+                .iter()
+                .map(|spec| match spec {
+                    ParameterSpec::Existing { from } => {
+                        Piece::Text(file.source[params[*from].byte_range()].to_string())
+                    }
+                    ParameterSpec::New {
+                        type_name, name, ..
+                    } => Piece::Text(format!("{type_name} {name}")),
+                })
+                .collect();
+            out.push((
+                path.to_string(),
+                Rewrite {
+                    start: open,
+                    end: close,
+                    pieces: layout(original, items),
+                },
+            ));
+        }
+        if let Some(rewrite) = natspec_rewrite(&file.source, function, &names, parameters) {
+            out.push((path.to_string(), rewrite));
+        }
+
+        let library = enclosing(function, "library_declaration");
+        declarations.insert(
+            id,
+            Declaration {
+                names,
+                in_library: library.is_some(),
+                library_name: library
+                    .and_then(|l| l.child_by_field_name("name"))
+                    .map(|n| file.source[n.byte_range()].to_string())
+                    .unwrap_or_default(),
+            },
+        );
+    }
+
+    for (path, nodes) in &build.nodes {
+        for info in nodes.values() {
+            let Some(decl) = info
+                .referenced_declaration
+                .and_then(|id| declarations.get(&id))
+            else {
+                continue;
+            };
+            if !matches!(
+                info.node_type.as_deref(),
+                Some("Identifier" | "MemberAccess")
+            ) {
+                continue;
+            }
+            let Some(loc) = SourceLoc::parse(&info.src) else {
+                continue;
+            };
+            let file = load(files, path, read_source)?;
+            let Some(call) = call_of(&file.tree, loc.offset, loc.end()) else {
+                // Referenced without being called (`f.selector`, a function
+                // pointer): nothing positional to update.
+                continue;
+            };
+            if let Some(rewrite) = call_rewrite(&file.source, call, decl, parameters)? {
+                out.push((path.to_string(), rewrite));
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn load<'a>(
+    files: &'a mut HashMap<String, File>,
+    path: &str,
+    read_source: &impl Fn(&str) -> Option<String>,
+) -> Result<&'a File, String> {
+    if !files.contains_key(path) {
+        let source = read_source(path).ok_or_else(|| format!("Cannot read {path}"))?;
+        let tree = crate::syntax::parse(&source).ok_or_else(|| format!("Cannot parse {path}"))?;
+        files.insert(path.to_string(), File { source, tree });
+    }
+    Ok(&files[path])
+}
+
+fn validate(parameters: &[ParameterSpec], count: usize) -> Result<(), String> {
+    let mut kept = HashSet::new();
+    let mut names = HashSet::new();
+    for spec in parameters {
+        match spec {
+            ParameterSpec::Existing { from } => {
+                if *from >= count {
+                    return Err(format!(
+                        "Parameter index {from} is out of range: the function has {count} parameter(s)"
+                    ));
+                }
+                if !kept.insert(*from) {
+                    return Err(format!("Parameter {from} is listed twice"));
+                }
+            }
+            ParameterSpec::New {
+                type_name, name, ..
+            } => {
+                if type_name.trim().is_empty() || !is_valid_solidity_identifier(name) {
+                    return Err(format!("Invalid new parameter `{type_name} {name}`"));
+                }
+                if !names.insert(name.as_str()) {
+                    return Err(format!("Parameter `{name}` is listed twice"));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The rewrite of the arguments of `call`, or `None` when its arguments
+/// don't line up with the declaration (the compiler would have rejected it).
+fn call_rewrite(
+    source: &str,
+    call: Node<'_>,
+    decl: &Declaration,
+    parameters: &[ParameterSpec],
+) -> Result<Option<Rewrite>, String> {
+    // `x.f(a)` on a library function declared `f(T self, U a)`.
+    let bound = decl.in_library
+        && call
+            .child_by_field_name("function")
+            .and_then(|f| f.named_child(0))
+            .filter(|f| f.kind() == "member_expression")
+            .and_then(|f| f.child_by_field_name("object"))
+            .is_some_and(|object| source[object.byte_range()] != decl.library_name);
+    let skip = usize::from(bound);
+    if bound && parameters.first() != Some(&ParameterSpec::Existing { from: 0 }) {
+        return Err(
+            "The first parameter of a library function attached with `using for` must stay first"
+                .to_string(),
+        );
+    }
+    let default = |name: &str, default: &Option<String>| {
+        default
+            .clone()
+            .ok_or_else(|| format!("New parameter `{name}` needs a default value for call sites"))
+    };
+
+    let args = children(call, "call_argument");
+    let named = args
+        .first()
+        .and_then(|a| a.named_child(0))
+        .filter(|a| a.kind() == "call_struct_argument");
+    if let Some(first) = named {
+        let argument = first.parent().unwrap_or(first);
+        let entries = children(argument, "call_struct_argument");
+        let mut items = Vec::new();
+        for spec in &parameters[skip..] {
+            match spec {
+                ParameterSpec::Existing { from } => {
+                    let Some(name) = decl.names.get(*from).cloned().flatten() else {
+                        return Ok(None);
+                    };
+                    let Some(entry) = entries.iter().find(|e| {
+                        e.child_by_field_name("name")
+                            .is_some_and(|n| source[n.byte_range()] == name)
+                    }) else {
+                        return Ok(None);
+                    };
+                    items.push(Piece::Source(entry.start_byte(), entry.end_byte()));
+                }
+                ParameterSpec::New {
+                    name, default: d, ..
+                } => {
+                    items.push(Piece::Text(format!("{name}: {}", default(name, d)?)));
+                }
+            }
+        }
+        let open = argument.start_byte() + 1;
+        let close = argument.end_byte() - 1;
+        return Ok(Some(Rewrite {
+            start: open,
+            end: close,
+            pieces: layout(&source[open..close], items),
+        }));
+    }
+
+    if args.len() + skip != decl.names.len() {
+        return Ok(None);
+    }
+    let Some((open, close)) = parens(call) else {
+        return Ok(None);
+    };
+    let mut items = Vec::new();
+    for spec in &parameters[skip..] {
+        match spec {
+            ParameterSpec::Existing { from } => {
+                let arg = args[from - skip];
+                items.push(Piece::Source(arg.start_byte(), arg.end_byte()));
+            }
+            ParameterSpec::New {
+                name, default: d, ..
+            } => {
+                items.push(Piece::Text(default(name, d)?));
+            }
+        }
+    }
+    Ok(Some(Rewrite {
+        start: open,
+        end: close,
+        pieces: layout(&source[open..close], items),
+    }))
+}
+
+/// Reorder, drop and add the `@param` lines of the NatSpec above `function`.
+/// Only touched when the existing `@param` lines are contiguous.
+fn natspec_rewrite(
+    source: &str,
+    function: Node<'_>,
+    names: &[Option<String>],
+    parameters: &[ParameterSpec],
+) -> Option<Rewrite> {
+    let (doc_start, doc_end) = doc_comment(source, function)?;
+    let mut lines = Vec::new();
+    let mut offset = doc_start;
+    for line in source[doc_start..doc_end].split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    // `(name, start, end)` of each `@param` line and its continuation lines.
+    let mut groups: Vec<(&str, usize, usize)> = Vec::new();
+    let mut in_params = false;
+    let mut region_closed = false;
+    for (start, line) in &lines {
+        let content = doc_content(line);
+        if let Some(rest) = content.strip_prefix("@param") {
+            if region_closed || line.contains("/**") || line.contains("*/") {
+                return None;
+            }
+            let name = rest.split_whitespace().next().unwrap_or("");
+            groups.push((name, *start, start + line.len()));
+            in_params = true;
+        } else if in_params
+            && !content.is_empty()
+            && !content.starts_with('@')
+            && !line.contains("*/")
+        {
+            if let Some(group) = groups.last_mut() {
+                group.2 = start + line.len();
+            }
+        } else if in_params {
+            in_params = false;
+            region_closed = true;
+        }
+    }
+    let first = groups.first()?;
+    let last = groups.last()?;
+    let prefix = {
+        let line = &source[first.1..first.2];
+        &line[..line.find("@param")?]
+    };
+
+    let mut pieces = Vec::new();
+    for spec in parameters {
+        match spec {
+            ParameterSpec::Existing { from } => {
+                let Some(name) = names.get(*from).cloned().flatten() else {
+                    continue;
+                };
+                if let Some(group) = groups.iter().find(|g| g.0 == name) {
+                    pieces.push(Piece::Text(source[group.1..group.2].to_string()));
+                }
+            }
+            ParameterSpec::New { name, .. } => {
+                pieces.push(Piece::Text(format!("{prefix}@param {name}\n")));
+            }
+        }
+    }
+    Some(Rewrite {
+        start: first.1,
+        end: last.2,
+        pieces,
+    })
+}
+
+/// Byte range of the doc comments directly above `node`, from the start of
+/// the first comment's line so line prefixes are kept whole.
+fn doc_comment(source: &str, node: Node<'_>) -> Option<(usize, usize)> {
+    let mut start = None;
+    let mut current = node.prev_sibling();
+    while let Some(comment) = current.filter(|c| {
+        c.kind() == "comment" && {
+            let text = &source[c.byte_range()];
+            text.starts_with("///") || text.starts_with("/**")
+        }
+    }) {
+        start = Some(comment.start_byte());
+        current = comment.prev_sibling();
+    }
+    let start = start?;
+    let line_start = source[..start].trim_end_matches([' ', '\t']).len();
+    Some((line_start, node.start_byte()))
+}
+
+/// A doc comment line without its `///`, `/**` or `*` marker.
+fn doc_content(line: &str) -> &str {
+    let line = line.trim();
+    let line = line
+        .strip_prefix("///")
+        .or_else(|| line.strip_prefix("/**"))
+        .or_else(|| line.strip_prefix('*'))
+        .unwrap_or(line);
+    line.trim()
+}
+
+/// Lay `items` out the way `original` (the text between the brackets) lays
+/// out its items: one per line when it spans lines, otherwise inline.
+fn layout(original: &str, items: Vec<Piece>) -> Vec<Piece> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let (open, separator, close) = if original.contains('\n') {
+        let indent: String = original
+            .trim_start_matches(|c| c != '\n')
+            .trim_start_matches('\n')
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let close = &original[original.trim_end().len()..];
+        let close = if close.contains('\n') { close } else { "\n" };
+        (
+            format!("\n{indent}"),
+            format!(",\n{indent}"),
+            close.to_string(),
+        )
+    } else {
+        let pad = if original.starts_with(' ') && !original.trim().is_empty() {
+            " "
+        } else {
+            ""
+        };
+        (pad.to_string(), ", ".to_string(), pad.to_string())
+    };
+    let mut pieces = vec![Piece::Text(open)];
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            pieces.push(Piece::Text(separator.clone()));
+        }
+        pieces.push(item);
+    }
+    pieces.push(Piece::Text(close));
+    pieces
+}
+
+/// The text of `rewrite`, with the rewrites nested in its source pieces
+/// applied. `rest` holds the rewrites starting after `rewrite`, in order.
+fn render(source: &str, rest: &[Rewrite], rewrite: &Rewrite) -> String {
+    let mut out = String::new();
+    for piece in &rewrite.pieces {
+        match piece {
+            Piece::Text(text) => out.push_str(text),
+            Piece::Source(start, end) => {
+                let mut pos = *start;
+                for (i, inner) in rest.iter().enumerate() {
+                    if inner.start >= pos && inner.end <= *end {
+                        out.push_str(&source[pos..inner.start]);
+                        out.push_str(&render(source, &rest[i + 1..], inner));
+                        pos = inner.end;
+                    }
+                }
+                out.push_str(&source[pos..*end]);
+            }
+        }
+    }
+    out
+}
+
+/// Byte range between the `(` and `)` of a call or function header.
+fn parens(node: Node<'_>) -> Option<(usize, usize)> {
+    let mut cursor = node.walk();
+    let mut open = None;
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "(" if open.is_none() => open = Some(child.end_byte()),
+            ")" if open.is_some() => return Some((open?, child.start_byte())),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The call whose callee spans exactly `start..end`.
+fn call_of(tree: &Tree, start: usize, end: usize) -> Option<Node<'_>> {
+    let mut node = tree.root_node().descendant_for_byte_range(start, end)?;
+    while node.start_byte() >= start && node.end_byte() <= end {
+        node = node.parent()?;
+    }
+    let call = if node.kind() == "call_expression" {
+        node
+    } else {
+        return None;
+    };
+    let function = call.child_by_field_name("function")?;
+    (function.start_byte() == start && function.end_byte() == end).then_some(call)
+}
+
+/// The `kind` node starting at `offset`.
+fn node_at<'a>(tree: &'a Tree, offset: usize, kind: &str) -> Option<Node<'a>> {
+    let mut node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+    loop {
+        if node.kind() == kind && node.start_byte() == offset {
+            return Some(node);
+        }
+        node = node.parent()?;
+    }
+}
+
+fn enclosing<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if n.kind() == kind {
+            return Some(n);
+        }
+        current = n.parent();
+    }
+    None
+}
+
+fn children<'a>(node: Node<'a>, kind: &str) -> Vec<Node<'a>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|c| c.kind() == kind)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build_at, node};
+
+    const PATH: &str = "/project/src/A.sol";
+
+    /// `(offset, len)` of the `nth` occurrence of `needle`.
+    fn span(source: &str, needle: &str, nth: usize) -> (usize, usize) {
+        (
+            source.match_indices(needle).nth(nth).unwrap().0,
+            needle.len(),
+        )
+    }
+
+    fn change(
+        source: &str,
+        build: &CachedBuild,
+        byte: usize,
+        parameters: &[ParameterSpec],
+    ) -> Result<String, String> {
+        let changes = change_signature(&[build], PATH, byte, parameters, |_| {
+            Some(source.to_string())
+        })?;
+        let edits = &changes[&Url::from_file_path(PATH).unwrap()];
+        Ok(crate::file_operations::apply_text_edits(source, edits))
+    }
+
+    fn new(type_name: &str, name: &str, default: Option<&str>) -> ParameterSpec {
+        ParameterSpec::New {
+            type_name: type_name.to_string(),
+            name: name.to_string(),
+            default: default.map(str::to_string),
+        }
+    }
+
+    const SOURCE: &str = "interface I {
+    /// @notice Adds.
+    /// @param a first
+    ///        operand
+    /// @param b second
+    function f(uint a, uint b) external returns (uint);
+}
+
+contract C is I {
+    function f(uint a, uint b) external override returns (uint) {
+        return a + b;
+    }
+
+    function g() external returns (uint) {
+        uint x = this.f(this.f(1, 2), 3);
+        return this.f({a: x, b: 5});
+    }
+}
+";
+
+    fn source_build() -> CachedBuild {
+        let (i_f, i_len) = span(
+            SOURCE,
+            "function f(uint a, uint b) external returns (uint);",
+            0,
+        );
+        let c_f = SOURCE
+            .find("function f(uint a, uint b) external override")
+            .unwrap();
+        let c_len = SOURCE[c_f..].find("}").unwrap() + 1;
+        let calls: Vec<(usize, usize)> = (0..3).map(|i| span(SOURCE, "this.f", i)).collect();
+        build_at(
+            PATH,
+            SOURCE,
+            &[
+                node(10, "FunctionDefinition", i_f, i_len),
+                node(20, "FunctionDefinition", c_f, c_len).bases(&[10]),
+                node(30, "MemberAccess", calls[0].0, calls[0].1).references(20),
+                node(31, "MemberAccess", calls[1].0, calls[1].1).references(20),
+                node(32, "MemberAccess", calls[2].0, calls[2].1).references(20),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_change_signature_updates_overrides_calls_and_natspec() {
+        let build = source_build();
+        let byte = SOURCE.find("this.f({").unwrap() + 5;
+        let parameters = [
+            ParameterSpec::Existing { from: 1 },
+            new("address", "to", Some("msg.sender")),
+            ParameterSpec::Existing { from: 0 },
+        ];
+        let out = change(SOURCE, &build, byte, &parameters).unwrap();
+        assert!(out.contains(
+            "    /// @notice Adds.
+    /// @param b second
+    /// @param to
+    /// @param a first
+    ///        operand
+    function f(uint b, address to, uint a) external returns (uint);"
+        ));
+        assert!(out.contains("function f(uint b, address to, uint a) external override"));
+        assert!(out.contains(
+            "uint x = this.f(3, msg.sender, this.f(2, msg.sender, 1));
+        return this.f({b: 5, to: msg.sender, a: x});"
+        ));
+    }
+
+    #[test]
+    fn test_change_signature_requires_default_for_call_sites() {
+        let build = source_build();
+        let byte = SOURCE.find("f(uint a").unwrap();
+        let parameters = [
+            ParameterSpec::Existing { from: 0 },
+            ParameterSpec::Existing { from: 1 },
+            new("uint", "c", None),
+        ];
+        assert_eq!(
+            change(SOURCE, &build, byte, &parameters).unwrap_err(),
+            "New parameter `c` needs a default value for call sites"
+        );
+    }
+
+    #[test]
+    fn test_change_signature_keeps_bound_library_argument() {
+        let source = "library L {
+    function add(
+        uint self,
+        uint x
+    ) internal pure returns (uint) {
+        return self + x;
+    }
+}
+
+contract C {
+    using L for uint;
+
+    function g(uint v) external pure returns (uint) {
+        uint w = v.add(1);
+        return L.add(w, 2);
+    }
+}
+";
+        let add = source.find("function add").unwrap();
+        let add_len = source[add..].find("}").unwrap() + 1;
+        let (bound, bound_len) = span(source, "v.add", 0);
+        let (qualified, qualified_len) = span(source, "L.add", 0);
+        let build = build_at(
+            PATH,
+            source,
+            &[
+                node(10, "FunctionDefinition", add, add_len),
+                node(20, "MemberAccess", bound, bound_len).references(10),
+                node(21, "MemberAccess", qualified, qualified_len).references(10),
+            ],
+        );
+        let parameters = [
+            ParameterSpec::Existing { from: 0 },
+            new("uint", "y", Some("0")),
+            ParameterSpec::Existing { from: 1 },
+        ];
+        let out = change(source, &build, add + 9, &parameters).unwrap();
+        assert!(out.contains(
+            "    function add(\n        uint self,\n        uint y,\n        uint x\n    ) internal"
+        ));
+        assert!(out.contains(
+            "uint w = v.add(0, 1);
+        return L.add(w, 0, 2);"
+        ));
+
+        let swapped = [
+            ParameterSpec::Existing { from: 1 },
+            ParameterSpec::Existing { from: 0 },
+        ];
+        assert!(change(source, &build, add + 9, &swapped).is_err());
+    }
+}
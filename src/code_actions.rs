@@ -11,7 +11,7 @@
 /// ```json
 /// {
 ///   "kind":  "insert" | "replace_token" | "delete_token" |
-///            "delete_node" | "insert_before_node" | "custom",
+///            "delete_node" | "insert_before_node" | "cross_file" | "custom",
 ///   "title": "<human-readable label shown in the editor>",
 ///
 ///   // insert only
@@ -27,16 +27,28 @@
 ///   // insert_before_node only
 ///   "walk_to":      "<tree-sitter node kind to walk up to>",
 ///   "before_child": ["<first matching child kind>", ...],
-///   "text":         "<text to insert>"
+///   "text":         "<text to insert>",
+///
+///   // cross_file only
+///   "fix": "override_list" | "base_virtual"
 /// }
 /// ```
 ///
+/// `"cross_file"` entries are computed from the `CachedBuild` (the
+/// overriding declaration's `baseFunctions`) and may edit files other than
+/// the one carrying the diagnostic; see [`cross_file_edits`].
+///
 /// `"custom"` entries have no extra fields — the handler falls through to the
 /// hand-written match arms in `lsp.rs`.
 use std::collections::HashMap;
 
+use crate::goto::CachedBuild;
+use crate::solc_ast::DeclNode;
 use crate::types::ErrorCode;
+use crate::utils::{byte_offset_to_position, position_to_byte_offset};
 use serde::Deserialize;
+use tower_lsp::lsp_types::{Range, TextEdit, Url};
+use tree_sitter::Node;
 
 // ── JSON types ───────────────────────────────────────────────────────────────
 
@@ -60,6 +72,8 @@ struct RawAction {
     // insert_before_node
     walk_to: Option<String>,
     before_child: Option<Vec<String>>,
+    // cross_file
+    fix: Option<String>,
 }
 
 // ── Public types ─────────────────────────────────────────────────────────────
@@ -117,11 +131,26 @@ pub enum FixKind {
         text: String,
     },
 
+    /// Fix computed from the `CachedBuild` that may span several files.
+    /// The handler turns the result of [`cross_file_edits`] into a
+    /// multi-file `WorkspaceEdit`.
+    CrossFile { fix: CrossFileFix },
+
     /// No generic fix available — the handler falls through to a hand-written
     /// match arm in `lsp.rs`.
     Custom,
 }
 
+/// Build-aware fixes for the override family of errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossFileFix {
+    /// 4327/4520: insert or replace the `override(...)` specifier with the
+    /// contracts that define the overridden base functions.
+    OverrideList,
+    /// 4334: add `virtual` to every overridden base declaration lacking it.
+    BaseVirtual,
+}
+
 #[derive(Debug, Clone)]
 pub enum InsertAnchor {
     FileStart,
@@ -197,6 +226,17 @@ fn parse_action(a: RawAction) -> Option<CodeActionDef> {
             text: a.text?,
         },
 
+        "cross_file" => FixKind::CrossFile {
+            fix: match a.fix.as_deref() {
+                Some("override_list") => CrossFileFix::OverrideList,
+                Some("base_virtual") => CrossFileFix::BaseVirtual,
+                other => {
+                    eprintln!("unknown cross_file fix: {other:?}");
+                    return None;
+                }
+            },
+        },
+
         "custom" => FixKind::Custom,
 
         other => {
@@ -208,6 +248,297 @@ fn parse_action(a: RawAction) -> Option<CodeActionDef> {
     Some(CodeActionDef { title, fix })
 }
 
+// ── Single-file fixes ────────────────────────────────────────────────────────
+
+/// The tree-sitter edit for a single-file `fix` at `diag_range`.
+///
+/// Returns `None` for [`FixKind::CrossFile`] and [`FixKind::Custom`], which
+/// need the build or a hand-written handler, and when the tree-sitter lookup
+/// fails.
+pub fn fix_edit(fix: &FixKind, source: &str, diag_range: Range) -> Option<TextEdit> {
+    use crate::goto::{CodeActionKind, code_action_edit};
+
+    match fix {
+        // InsertAtFileStart is the only anchor for now.
+        FixKind::Insert { text, anchor: _ } => code_action_edit(
+            source,
+            diag_range,
+            CodeActionKind::InsertAtFileStart { text },
+        ),
+        FixKind::ReplaceToken {
+            replacement,
+            walk_to,
+        } => code_action_edit(
+            source,
+            diag_range,
+            CodeActionKind::ReplaceToken {
+                replacement,
+                walk_to: walk_to.as_deref(),
+            },
+        ),
+        FixKind::DeleteToken => code_action_edit(source, diag_range, CodeActionKind::DeleteToken),
+        // Only variable_declaration_statement supported so far.
+        FixKind::DeleteNode { node_kind } if node_kind == "variable_declaration_statement" => {
+            code_action_edit(source, diag_range, CodeActionKind::DeleteLocalVar)
+        }
+        FixKind::DeleteNode { .. } => None,
+        FixKind::DeleteChildNode {
+            walk_to,
+            child_kinds,
+        } => {
+            let ck: Vec<&str> = child_kinds.iter().map(|s| s.as_str()).collect();
+            code_action_edit(
+                source,
+                diag_range,
+                CodeActionKind::DeleteChildNode {
+                    walk_to,
+                    child_kinds: &ck,
+                },
+            )
+        }
+        FixKind::ReplaceChildNode {
+            walk_to,
+            child_kind,
+            replacement,
+        } => code_action_edit(
+            source,
+            diag_range,
+            CodeActionKind::ReplaceChildNode {
+                walk_to,
+                child_kind,
+                replacement,
+            },
+        ),
+        FixKind::InsertBeforeNode {
+            walk_to,
+            before_child,
+            text,
+        } => {
+            let bc: Vec<&str> = before_child.iter().map(|s| s.as_str()).collect();
+            code_action_edit(
+                source,
+                diag_range,
+                CodeActionKind::InsertBeforeNode {
+                    walk_to,
+                    before_child: &bc,
+                    text,
+                },
+            )
+        }
+        FixKind::CrossFile { .. } | FixKind::Custom => None,
+    }
+}
+
+/// Edits of every single-file fix that applies to `diagnostics`, as run by
+/// "fix all" on save.
+///
+/// Every fix in the database is a preferred quick fix. Duplicate edits (two
+/// diagnostics asking for the same insertion) are returned once; overlapping
+/// ones are left to the caller.
+pub fn fix_all_edits(
+    db: &HashMap<ErrorCode, CodeActionDef>,
+    source: &str,
+    diagnostics: &[tower_lsp::lsp_types::Diagnostic],
+) -> Vec<TextEdit> {
+    use tower_lsp::lsp_types::NumberOrString;
+
+    let mut edits: Vec<TextEdit> = Vec::new();
+    for diag in diagnostics {
+        let Some(NumberOrString::String(code)) = &diag.code else {
+            continue;
+        };
+        let Some(def) = code.parse().ok().and_then(|n| db.get(&ErrorCode(n))) else {
+            continue;
+        };
+        if let Some(edit) = fix_edit(&def.fix, source, diag.range)
+            && !edits.contains(&edit)
+        {
+            edits.push(edit);
+        }
+    }
+    edits
+}
+
+// ── Cross-file fixes ─────────────────────────────────────────────────────────
+
+/// Tree-sitter kinds of declarations that can carry `override` / `virtual`.
+const OVERRIDABLE_KINDS: &[&str] = &[
+    "function_definition",
+    "modifier_definition",
+    "state_variable_declaration",
+];
+
+/// Compute the edits for a [`CrossFileFix`], keyed by file.
+///
+/// `source` is the text of `uri`, where the diagnostic points into the
+/// overriding declaration. `read_source` returns the current text of other
+/// files (base declarations), preferring open buffers over disk.
+pub fn cross_file_edits(
+    fix: CrossFileFix,
+    build: &CachedBuild,
+    uri: &Url,
+    source: &str,
+    diag_range: Range,
+    read_source: impl Fn(&std::path::Path) -> Option<String>,
+) -> Option<HashMap<Url, Vec<TextEdit>>> {
+    let tree = crate::syntax::parse(source)?;
+    let byte = position_to_byte_offset(source, diag_range.start);
+    let decl_node = enclosing_overridable(tree.root_node(), byte)?;
+    let name_node = decl_node.child_by_field_name("name")?;
+    let name = &source[name_node.byte_range()];
+
+    let path = uri.to_file_path().ok()?;
+    let path_str = path.to_str()?;
+    let abs_path = build
+        .path_to_abs
+        .get(path_str)
+        .map_or(path_str, |p| p.as_str());
+    let decl = overriding_decl(build, abs_path, name, decl_node.start_byte())?;
+    let base_ids = decl.base_functions()?;
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    match fix {
+        CrossFileFix::OverrideList => {
+            let mut contracts: Vec<&str> = base_ids
+                .iter()
+                .filter_map(|id| {
+                    let scope = build.decl_index.get(&crate::types::NodeId(*id))?.scope()?;
+                    match build.decl_index.get(&crate::types::NodeId(scope))? {
+                        DeclNode::ContractDefinition(c) => Some(c.name.as_str()),
+                        _ => None,
+                    }
+                })
+                .collect();
+            contracts.sort_unstable();
+            contracts.dedup();
+            let specifier = match contracts.as_slice() {
+                [] | [_] => "override".to_string(),
+                many => format!("override({})", many.join(", ")),
+            };
+            let edit = specifier_edit(source, decl_node, "override_specifier", &specifier)?;
+            changes.insert(uri.clone(), vec![edit]);
+        }
+        CrossFileFix::BaseVirtual => {
+            for id in base_ids {
+                let base = build.decl_index.get(&crate::types::NodeId(*id))?;
+                let is_virtual = match base {
+                    DeclNode::FunctionDefinition(f) => f.is_virtual == Some(true),
+                    DeclNode::ModifierDefinition(m) => m.is_virtual == Some(true),
+                    _ => true,
+                };
+                if is_virtual {
+                    continue;
+                }
+                let base_abs = build
+                    .node_id_to_source_path
+                    .get(&crate::types::NodeId(*id))?;
+                let base_path = crate::call_hierarchy::find_file_path(
+                    base_abs.as_str(),
+                    &build.id_to_path_map,
+                )?;
+                let base_source = if base_path == path {
+                    source.to_string()
+                } else {
+                    read_source(&base_path)?
+                };
+                let base_tree = crate::syntax::parse(&base_source)?;
+                let offset = crate::types::SourceLoc::parse(base.src())?.offset;
+                let base_node = enclosing_overridable(base_tree.root_node(), offset)?;
+                let edit = specifier_edit(&base_source, base_node, "virtual", "virtual")?;
+                changes
+                    .entry(Url::from_file_path(&base_path).ok()?)
+                    .or_default()
+                    .push(edit);
+            }
+        }
+    }
+    (!changes.is_empty()).then_some(changes)
+}
+
+/// Innermost function, modifier or state variable declaration containing `byte`.
+fn enclosing_overridable(root: Node<'_>, byte: usize) -> Option<Node<'_>> {
+    let mut node = root.descendant_for_byte_range(byte, byte)?;
+    while !OVERRIDABLE_KINDS.contains(&node.kind()) {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+/// The declaration named `name` in `abs_path` whose `src` starts closest to
+/// `start_byte` (the live buffer may have shifted since the build).
+fn overriding_decl<'a>(
+    build: &'a CachedBuild,
+    abs_path: &str,
+    name: &str,
+    start_byte: usize,
+) -> Option<&'a DeclNode> {
+    build
+        .decl_index
+        .iter()
+        .filter(|(id, decl)| {
+            decl.name() == name
+                && decl.base_functions().is_some_and(|b| !b.is_empty())
+                && build
+                    .node_id_to_source_path
+                    .get(id)
+                    .is_some_and(|p| p.as_str() == abs_path)
+        })
+        .filter_map(|(_, decl)| {
+            let offset = crate::types::SourceLoc::parse(decl.src())?.offset;
+            Some((offset.abs_diff(start_byte), decl))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, decl)| decl)
+}
+
+/// Replace the `child_kind` child of `decl` with `text`, or insert `text`
+/// where a specifier goes: before the return types or body of a function or
+/// modifier, or before the name of a state variable.
+fn specifier_edit(source: &str, decl: Node<'_>, child_kind: &str, text: &str) -> Option<TextEdit> {
+    let mut cursor = decl.walk();
+    let children: Vec<Node> = decl.children(&mut cursor).collect();
+    let edit = |start: usize, end: usize, new_text: String| TextEdit {
+        range: Range {
+            start: byte_offset_to_position(source, start),
+            end: byte_offset_to_position(source, end),
+        },
+        new_text,
+    };
+
+    if let Some(existing) = children.iter().find(|c| c.kind() == child_kind) {
+        return Some(edit(
+            existing.start_byte(),
+            existing.end_byte(),
+            text.to_string(),
+        ));
+    }
+
+    let before = if decl.kind() == "state_variable_declaration" {
+        decl.child_by_field_name("name")
+    } else {
+        children
+            .iter()
+            .find(|c| matches!(c.kind(), "return_type_definition" | "function_body"))
+            .copied()
+    };
+    match before {
+        Some(node) => Some(edit(
+            node.start_byte(),
+            node.start_byte(),
+            format!("{text} "),
+        )),
+        // Bodiless declaration: `function f() external;`
+        None => {
+            let semi = children.iter().rfind(|c| c.kind() == ";")?;
+            Some(edit(
+                semi.start_byte(),
+                semi.start_byte(),
+                format!(" {text}"),
+            ))
+        }
+    }
+}
+
 // ── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -303,6 +634,228 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_override_codes_are_cross_file() {
+        let db = load();
+        for (code, fix) in [
+            (4327u32, CrossFileFix::OverrideList),
+            (4520, CrossFileFix::OverrideList),
+            (4334, CrossFileFix::BaseVirtual),
+        ] {
+            let def = db.get(&code).unwrap();
+            assert!(
+                matches!(def.fix, FixKind::CrossFile { fix: f } if f == fix),
+                "code {code} should be {fix:?}"
+            );
+        }
+    }
+
+    const BASE: &str = "contract A { function f() public virtual {} function g() public {} }\ncontract B { function f() public virtual {} }\n";
+    const DERIVED: &str =
+        "contract C is A, B { function f() public override {} function g() public override {} }\n";
+
+    fn function(
+        id: i64,
+        src: &str,
+        name: &str,
+        scope: i64,
+        virt: bool,
+        bases: &[i64],
+    ) -> serde_json::Value {
+        let params = |id: i64| serde_json::json!({ "id": id, "nodeType": "ParameterList", "src": "0:0:0", "parameters": [] });
+        serde_json::json!({
+            "id": id,
+            "nodeType": "FunctionDefinition",
+            "src": src,
+            "name": name,
+            "kind": "function",
+            "visibility": "public",
+            "stateMutability": "nonpayable",
+            "virtual": virt,
+            "scope": scope,
+            "parameters": params(id + 1),
+            "returnParameters": params(id + 2),
+            "baseFunctions": bases
+        })
+    }
+
+    fn contract(
+        id: i64,
+        src: &str,
+        name: &str,
+        nodes: Vec<serde_json::Value>,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "nodeType": "ContractDefinition",
+            "src": src,
+            "name": name,
+            "contractKind": "contract",
+            "baseContracts": [],
+            "nodes": nodes
+        })
+    }
+
+    /// `Base.sol` declares `A` and `B`; `C.sol` declares `C is A, B`.
+    fn override_build() -> CachedBuild {
+        CachedBuild::new(override_output(), 0, None)
+    }
+
+    fn override_output() -> serde_json::Value {
+        let base = serde_json::json!([
+            contract(
+                10,
+                "0:68:0",
+                "A",
+                vec![
+                    function(11, "13:30:0", "f", 10, true, &[]),
+                    function(14, "44:22:0", "g", 10, false, &[]),
+                ]
+            ),
+            contract(
+                20,
+                "69:45:0",
+                "B",
+                vec![function(21, "82:30:0", "f", 20, true, &[])]
+            ),
+        ]);
+        let derived = serde_json::json!([contract(
+            30,
+            "0:86:1",
+            "C",
+            vec![
+                function(31, "21:31:1", "f", 30, false, &[11, 21]),
+                function(34, "53:31:1", "g", 30, false, &[14]),
+            ]
+        )]);
+        crate::test_support::output_files(vec![
+            ("/tmp/ovr/Base.sol", 115, base),
+            ("/tmp/ovr/C.sol", 87, derived),
+        ])
+    }
+
+    fn at(character: u32) -> Range {
+        let pos = tower_lsp::lsp_types::Position::new(0, character);
+        Range::new(pos, pos)
+    }
+
+    #[test]
+    fn test_cross_file_override_list() {
+        let build = override_build();
+        let uri = Url::from_file_path("/tmp/ovr/C.sol").unwrap();
+        let changes = cross_file_edits(
+            CrossFileFix::OverrideList,
+            &build,
+            &uri,
+            DERIVED,
+            at(41),
+            |_| None,
+        )
+        .unwrap();
+        let edits = &changes[&uri];
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range, Range::new(at(41).start, at(49).start));
+        assert_eq!(edits[0].new_text, "override(A, B)");
+    }
+
+    #[test]
+    fn test_cross_file_base_virtual() {
+        let build = override_build();
+        let uri = Url::from_file_path("/tmp/ovr/C.sol").unwrap();
+        let base_uri = Url::from_file_path("/tmp/ovr/Base.sol").unwrap();
+        let changes = cross_file_edits(
+            CrossFileFix::BaseVirtual,
+            &build,
+            &uri,
+            DERIVED,
+            at(53),
+            |path| (path == std::path::Path::new("/tmp/ovr/Base.sol")).then(|| BASE.to_string()),
+        )
+        .unwrap();
+        assert!(!changes.contains_key(&uri));
+        let edits = &changes[&base_uri];
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range, at(64));
+        assert_eq!(edits[0].new_text, "virtual ");
+
+        // `f` is already virtual in both bases: nothing to do.
+        assert!(
+            cross_file_edits(
+                CrossFileFix::BaseVirtual,
+                &build,
+                &uri,
+                DERIVED,
+                at(21),
+                |_| { Some(BASE.to_string()) }
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_cross_file_override_only_in_buffer() {
+        // `g` was just added to `C`: the cached build predates it, and the
+        // buffer's own build carries the 4334 error the fix is offered for.
+        let mut stale = override_output();
+        stale["sources"]["/tmp/ovr/C.sol"]["ast"]["nodes"][0]["nodes"]
+            .as_array_mut()
+            .unwrap()
+            .pop();
+        let stale = CachedBuild::new(stale, 1, None);
+        let mut output = override_output();
+        output["errors"] = serde_json::json!([{
+            "errorCode": "4334",
+            "severity": "error",
+            "message": "Trying to override non-virtual function.",
+            "sourceLocation": { "file": "/tmp/ovr/C.sol", "start": 53, "end": 84 }
+        }]);
+        let buffer = CachedBuild::from_partial_output(output, "/tmp/ovr/C.sol", 2).unwrap();
+
+        let uri = Url::from_file_path("/tmp/ovr/C.sol").unwrap();
+        let read = |_: &std::path::Path| Some(BASE.to_string());
+        let fix = CrossFileFix::BaseVirtual;
+        assert!(cross_file_edits(fix, &stale, &uri, DERIVED, at(53), read).is_none());
+        let changes = cross_file_edits(fix, &buffer, &uri, DERIVED, at(53), read).unwrap();
+        let base_uri = Url::from_file_path("/tmp/ovr/Base.sol").unwrap();
+        assert_eq!(changes[&base_uri][0].new_text, "virtual ");
+    }
+
+    #[test]
+    fn test_specifier_edit_on_bodiless_function() {
+        let source = "interface I { function f() external; }";
+        let tree = crate::syntax::parse(source).unwrap();
+        let node = enclosing_overridable(tree.root_node(), 24).unwrap();
+        let edit = specifier_edit(source, node, "virtual", "virtual").unwrap();
+        assert_eq!(edit.range, at(35));
+        assert_eq!(edit.new_text, " virtual");
+    }
+
+    #[test]
+    fn test_fix_all_edits_dedupes_and_skips_unknown_codes() {
+        use tower_lsp::lsp_types::{Diagnostic, NumberOrString, Position};
+
+        let db = load();
+        let source = "pragma solidity ^0.8.0;\ncontract C {\n    function f() public {\n        uint256 x = 1;\n    }\n}\n";
+        let diag = |code: &str, start: (u32, u32), end: (u32, u32)| Diagnostic {
+            range: Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1)),
+            code: Some(NumberOrString::String(code.to_string())),
+            ..Default::default()
+        };
+        let diagnostics = vec![
+            diag("1878", (0, 0), (0, 0)),
+            diag("1878", (0, 0), (0, 0)),
+            diag("2072", (3, 8), (3, 17)),
+            diag("unused-import", (0, 0), (0, 6)),
+        ];
+
+        let edits = fix_all_edits(&db, source, &diagnostics);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(
+            crate::file_operations::apply_text_edits(source, &edits),
+            "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.0;\ncontract C {\n    function f() public {\n    }\n}\n"
+        );
+    }
+
     #[test]
     fn test_null_actions_not_in_map() {
         let db = load();
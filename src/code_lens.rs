@@ -0,0 +1,286 @@
+//! Code lens support (`textDocument/codeLens` + `codeLens/resolve`).
+//!
+//! Every function, event, error, modifier, and state variable gets a
+//! "N references" lens. Functions, modifiers, and state variables that
+//! participate in an interface ↔ implementation relationship (an entry in
+//! `CachedBuild::base_function_implementation`) also get an
+//! "N implementations" lens, and declarations with a selector get a
+//! non-clickable selector lens.
+//!
+//! Counting references requires scanning the project build and every lib
+//! sub-cache, so `textDocument/codeLens` only returns the lens ranges plus a
+//! [`CodeLensData`] payload. The counts and commands are filled in lazily by
+//! `codeLens/resolve`, which only runs for lenses the editor actually shows.
+
+use crate::goto::{CachedBuild, bytes_to_pos};
+use crate::solc_ast::DeclNode;
+use crate::types::SourceLoc;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{CodeLens, Command, Location, Position, Range, Url};
+
+/// Client-side command used to open a references/implementations list.
+///
+/// This is VS Code's built-in `editor.action.showReferences`, which takes
+/// `[uri, position, locations]`. Other editors can register a handler under
+/// the same name (e.g. `vim.lsp.commands` in Neovim).
+pub const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+/// What an unresolved lens counts once resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CodeLensKind {
+    References,
+    Implementations,
+}
+
+/// Payload stored in `CodeLens.data` between `codeLens` and `codeLens/resolve`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeLensData {
+    pub uri: Url,
+    pub kind: CodeLensKind,
+    /// Position of the declaration name, used to re-resolve the target.
+    pub position: Position,
+}
+
+/// Collect unresolved code lenses for every lens-worthy declaration in a file.
+///
+/// Declarations are read from the file's `nodes` index; `decl_index` is used
+/// to tell state variables apart from locals and parameters and to read
+/// selectors. Lens ranges are the declaration name ranges in `source_bytes`.
+pub fn code_lenses(build: &CachedBuild, file_uri: &Url, source_bytes: &[u8]) -> Vec<CodeLens> {
+    let Some(path) = file_uri.to_file_path().ok() else {
+        return vec![];
+    };
+    let Some(abs_path) = path.to_str().and_then(|p| build.path_to_abs.get(p)) else {
+        return vec![];
+    };
+    let Some(file_nodes) = build.nodes.get(abs_path) else {
+        return vec![];
+    };
+
+    let mut decls: Vec<(usize, usize, &DeclNode)> = file_nodes
+        .iter()
+        .filter_map(|(id, info)| {
+            let decl = build.decl_index.get(id)?;
+            let lens_worthy = match decl {
+                DeclNode::FunctionDefinition(f) => !f.name.is_empty(),
+                DeclNode::VariableDeclaration(v) => v.state_variable == Some(true),
+                DeclNode::EventDefinition(_)
+                | DeclNode::ErrorDefinition(_)
+                | DeclNode::ModifierDefinition(_) => true,
+                _ => false,
+            };
+            if !lens_worthy {
+                return None;
+            }
+            let loc = SourceLoc::parse(info.name_location.as_deref()?)?;
+            Some((loc.offset, loc.length, decl))
+        })
+        .collect();
+    decls.sort_by_key(|(offset, _, _)| *offset);
+
+    let mut lenses = Vec::new();
+    for (offset, length, decl) in decls {
+        let (Some(start), Some(end)) = (
+            bytes_to_pos(source_bytes, offset),
+            bytes_to_pos(source_bytes, offset + length),
+        ) else {
+            continue;
+        };
+        let range = Range { start, end };
+
+        lenses.push(unresolved_lens(file_uri, range, CodeLensKind::References));
+
+        let has_implementations = !matches!(
+            decl,
+            DeclNode::EventDefinition(_) | DeclNode::ErrorDefinition(_)
+        ) && build
+            .base_function_implementation
+            .contains_key(&crate::types::NodeId(decl.id()));
+        if has_implementations {
+            lenses.push(unresolved_lens(
+                file_uri,
+                range,
+                CodeLensKind::Implementations,
+            ));
+        }
+
+        if let Some(selector) = decl.extract_typed_selector() {
+            lenses.push(CodeLens {
+                range,
+                command: Some(Command {
+                    title: selector.to_prefixed(),
+                    command: String::new(),
+                    arguments: None,
+                }),
+                data: None,
+            });
+        }
+    }
+    lenses
+}
+
+fn unresolved_lens(file_uri: &Url, range: Range, kind: CodeLensKind) -> CodeLens {
+    let data = CodeLensData {
+        uri: file_uri.clone(),
+        kind,
+        position: range.start,
+    };
+    CodeLens {
+        range,
+        command: None,
+        data: serde_json::to_value(data).ok(),
+    }
+}
+
+/// Fill in the title and command of an unresolved lens from the locations
+/// it counts.
+pub fn resolve_code_lens(
+    mut lens: CodeLens,
+    data: &CodeLensData,
+    locations: Vec<Location>,
+) -> CodeLens {
+    let title = lens_title(data.kind, locations.len());
+    lens.command = Some(Command {
+        title,
+        command: SHOW_REFERENCES_COMMAND.to_string(),
+        arguments: Some(vec![
+            serde_json::json!(data.uri),
+            serde_json::json!(data.position),
+            serde_json::json!(locations),
+        ]),
+    });
+    lens
+}
+
+/// Human-readable lens title, e.g. `"1 reference"` or `"3 implementations"`.
+pub fn lens_title(kind: CodeLensKind, count: usize) -> String {
+    let noun = match kind {
+        CodeLensKind::References => "reference",
+        CodeLensKind::Implementations => "implementation",
+    };
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn param_list(id: i64) -> serde_json::Value {
+        json!({ "id": id, "nodeType": "ParameterList", "src": "0:0:0", "parameters": [] })
+    }
+
+    /// A tiny single-file build:
+    ///
+    /// ```solidity
+    /// contract C { uint256 public x; function f() public {} event E(); }
+    /// ```
+    fn build() -> CachedBuild {
+        let nodes = json!([{
+            "id": 2,
+            "nodeType": "ContractDefinition",
+            "src": "0:66:0",
+            "name": "C",
+            "nameLocation": "9:1:0",
+            "contractKind": "contract",
+            "baseContracts": [],
+            "nodes": [
+                {
+                    "id": 3,
+                    "nodeType": "VariableDeclaration",
+                    "src": "13:16:0",
+                    "name": "x",
+                    "nameLocation": "28:1:0",
+                    "stateVariable": true,
+                    "visibility": "public",
+                    "functionSelector": "0c55699c",
+                    "typeDescriptions": { "typeString": "uint256" }
+                },
+                {
+                    "id": 4,
+                    "nodeType": "FunctionDefinition",
+                    "src": "31:22:0",
+                    "name": "f",
+                    "nameLocation": "40:1:0",
+                    "kind": "function",
+                    "stateMutability": "nonpayable",
+                    "visibility": "public",
+                    "functionSelector": "26121ff0",
+                    "parameters": param_list(5),
+                    "returnParameters": param_list(6)
+                },
+                {
+                    "id": 7,
+                    "nodeType": "EventDefinition",
+                    "src": "54:10:0",
+                    "name": "E",
+                    "nameLocation": "60:1:0",
+                    "parameters": param_list(8)
+                }
+            ]
+        }]);
+        CachedBuild::new(
+            crate::test_support::output("/tmp/C.sol", 66, nodes),
+            0,
+            None,
+        )
+    }
+
+    const SOURCE: &str = "contract C { uint256 public x; function f() public {} event E(); }";
+
+    #[test]
+    fn test_lens_title_pluralization() {
+        assert_eq!(lens_title(CodeLensKind::References, 0), "0 references");
+        assert_eq!(lens_title(CodeLensKind::References, 1), "1 reference");
+        assert_eq!(
+            lens_title(CodeLensKind::Implementations, 3),
+            "3 implementations"
+        );
+    }
+
+    #[test]
+    fn test_code_lenses_cover_declarations_in_order() {
+        let uri = Url::from_file_path("/tmp/C.sol").unwrap();
+        let lenses = code_lenses(&build(), &uri, SOURCE.as_bytes());
+
+        let reference_lines: Vec<(u32, u32)> = lenses
+            .iter()
+            .filter(|l| l.data.is_some())
+            .map(|l| (l.range.start.line, l.range.start.character))
+            .collect();
+        assert_eq!(reference_lines, vec![(0, 28), (0, 40), (0, 60)]);
+
+        let selectors: Vec<&str> = lenses
+            .iter()
+            .filter_map(|l| l.command.as_ref().map(|c| c.title.as_str()))
+            .collect();
+        assert_eq!(selectors, vec!["0x0c55699c", "0x26121ff0"]);
+    }
+
+    #[test]
+    fn test_resolve_code_lens_sets_show_references_command() {
+        let uri = Url::from_file_path("/tmp/C.sol").unwrap();
+        let lens = code_lenses(&build(), &uri, SOURCE.as_bytes())
+            .into_iter()
+            .next()
+            .unwrap();
+        let data: CodeLensData = serde_json::from_value(lens.data.clone().unwrap()).unwrap();
+        assert_eq!(data.kind, CodeLensKind::References);
+
+        let loc = Location {
+            uri: uri.clone(),
+            range: Range::default(),
+        };
+        let resolved = resolve_code_lens(lens, &data, vec![loc.clone(), loc]);
+        let command = resolved.command.unwrap();
+        assert_eq!(command.title, "2 references");
+        assert_eq!(command.command, SHOW_REFERENCES_COMMAND);
+        assert_eq!(command.arguments.unwrap().len(), 3);
+    }
+}
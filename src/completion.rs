@@ -1822,28 +1822,7 @@ fn import_statement_already_present(source_text: &str, symbol: &str, import_path
 
 fn build_import_text_edit(source_text: &str, symbol: &str, import_path: &str) -> Option<TextEdit> {
     let import_stmt = format!("import {{{symbol}}} from \"{import_path}\";\n");
-    let lines: Vec<&str> = source_text.lines().collect();
-
-    let last_import_line = lines
-        .iter()
-        .enumerate()
-        .filter(|(_, line)| line.trim_start().starts_with("import "))
-        .map(|(idx, _)| idx)
-        .last();
-
-    let insert_line = if let Some(idx) = last_import_line {
-        idx + 1
-    } else if let Some(idx) = lines
-        .iter()
-        .enumerate()
-        .filter(|(_, line)| line.trim_start().starts_with("pragma "))
-        .map(|(idx, _)| idx)
-        .last()
-    {
-        idx + 1
-    } else {
-        0
-    };
+    let insert_line = import_insert_line(source_text);
 
     Some(TextEdit {
         range: Range {
@@ -1860,6 +1839,107 @@ fn build_import_text_edit(source_text: &str, symbol: &str, import_path: &str) ->
     })
 }
 
+/// Line on which a new import statement should be inserted: right after the
+/// last top-level import (which may span several lines), else after the last
+/// pragma, else at the top of the file.
+fn import_insert_line(source_text: &str) -> usize {
+    let Some(tree) = crate::syntax::parse(source_text) else {
+        return 0;
+    };
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let children: Vec<_> = root.children(&mut cursor).collect();
+    let last_of = |kind: &str| {
+        children
+            .iter()
+            .rfind(|node| node.kind() == kind)
+            .map(|node| node.end_position().row + 1)
+    };
+    last_of("import_directive")
+        .or_else(|| last_of("pragma_directive"))
+        .unwrap_or(0)
+}
+
+/// Import path for `target_file` as written in `current_file`.
+///
+/// Prefers the remapped form from the most specific remapping whose target
+/// contains the file (e.g. `forge-std/Test.sol`), falling back to a path
+/// relative to the importing file (e.g. `./libraries/Pool.sol`).
+pub fn preferred_import_path(
+    current_file: &Path,
+    target_file: &Path,
+    project_root: &Path,
+    remappings: &[String],
+) -> Option<String> {
+    let target_file = project_root.join(target_file);
+    let target_file = target_file.canonicalize().unwrap_or(target_file);
+
+    let remapped = remappings
+        .iter()
+        .filter_map(|r| {
+            let (prefix, target) = r.split_once('=')?;
+            if prefix.is_empty() || target.is_empty() {
+                return None;
+            }
+            let raw = project_root.join(target);
+            let target_abs = raw.canonicalize().unwrap_or(raw);
+            let suffix = target_file.strip_prefix(&target_abs).ok()?;
+            Some((target_abs.components().count(), prefix, suffix))
+        })
+        .max_by_key(|(depth, _, _)| *depth)
+        .map(|(_, prefix, suffix)| {
+            let suffix = suffix.to_string_lossy().replace('\\', "/");
+            if prefix.ends_with('/') {
+                format!("{prefix}{suffix}")
+            } else {
+                format!("{prefix}/{suffix}")
+            }
+        });
+
+    remapped.or_else(|| to_relative_import_path(current_file, &target_file))
+}
+
+/// Import quick fixes for an undeclared identifier `name`.
+///
+/// Returns one `(import_path, edit)` pair per file that declares `name` at
+/// the top level, sorted by import path. Files already imported by
+/// `source_text` and the current file itself are skipped.
+pub fn auto_import_edits(
+    cache: &CompletionCache,
+    name: &str,
+    current_file: &Path,
+    source_text: &str,
+    project_root: &Path,
+    remappings: &[String],
+) -> Vec<(String, TextEdit)> {
+    let Some(symbols) = cache.top_level_importables_by_name.get(name) else {
+        return vec![];
+    };
+
+    let mut out: Vec<(String, TextEdit)> = Vec::new();
+    for symbol in symbols {
+        let declaring = project_root.join(&symbol.declaring_path);
+        if declaring == current_file {
+            continue;
+        }
+        let Some(import_path) =
+            preferred_import_path(current_file, &declaring, project_root, remappings)
+        else {
+            continue;
+        };
+        if import_statement_already_present(source_text, &symbol.name, &import_path)
+            || out.iter().any(|(p, _)| *p == import_path)
+        {
+            continue;
+        }
+        if let Some(edit) = build_import_text_edit(source_text, &symbol.name, &import_path) {
+            out.push((import_path, edit));
+        }
+    }
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
 /// Handle a completion request with optional tail candidates.
 ///
 /// Tail candidates are only appended for non-dot completions and are always
@@ -2507,6 +2587,75 @@ mod tests {
         assert!(edit_text.contains("import {B} from \"./B.sol\";"));
     }
 
+    #[test]
+    fn import_edit_goes_after_multiline_import() {
+        let source = "pragma solidity ^0.8.0;\n\nimport {\n    A,\n    B\n} from \"./AB.sol\";\n\ncontract C {}\n";
+        let edit = super::build_import_text_edit(source, "D", "./D.sol").unwrap();
+        assert_eq!(edit.range.start.line, 6);
+
+        let edit = super::build_import_text_edit("pragma solidity ^0.8.0;\n", "D", "./D.sol");
+        assert_eq!(edit.unwrap().range.start.line, 1);
+    }
+
+    #[test]
+    fn preferred_import_path_uses_most_specific_remapping() {
+        let root = std::path::Path::new("/nonexistent/project");
+        let current = root.join("src/A.sol");
+        let remappings = vec![
+            "lib/=lib/".to_string(),
+            "forge-std/=lib/forge-std/src/".to_string(),
+        ];
+
+        let path = super::preferred_import_path(
+            &current,
+            &root.join("lib/forge-std/src/Test.sol"),
+            root,
+            &remappings,
+        );
+        assert_eq!(path.as_deref(), Some("forge-std/Test.sol"));
+
+        let path = super::preferred_import_path(
+            &current,
+            &root.join("src/utils/B.sol"),
+            root,
+            &remappings,
+        );
+        assert_eq!(path.as_deref(), Some("./utils/B.sol"));
+    }
+
+    #[test]
+    fn auto_import_edits_skip_existing_imports_and_current_file() {
+        let mut cache = empty_cache();
+        let symbol = |path: &str| TopLevelImportable {
+            name: "B".to_string(),
+            declaring_path: path.to_string(),
+            node_type: "ContractDefinition".to_string(),
+            kind: CompletionItemKind::CLASS,
+        };
+        cache.top_level_importables_by_name.insert(
+            SymbolName::new("B"),
+            vec![
+                symbol("/tmp/example/src/B.sol"),
+                symbol("/tmp/example/src/deps/B.sol"),
+                symbol("/tmp/example/src/A.sol"),
+            ],
+        );
+
+        let root = std::path::Path::new("/tmp/example");
+        let current = root.join("src/A.sol");
+        let source =
+            "pragma solidity ^0.8.0;\nimport {B} from \"./deps/B.sol\";\ncontract A is B {}\n";
+        let edits = super::auto_import_edits(&cache, "B", &current, source, root, &[]);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].0, "./B.sol");
+        assert_eq!(edits[0].1.range.start.line, 2);
+        assert_eq!(edits[0].1.new_text, "import {B} from \"./B.sol\";\n");
+
+        assert!(
+            super::auto_import_edits(&cache, "Missing", &current, source, root, &[]).is_empty()
+        );
+    }
+
     #[test]
     fn handle_completion_general_path_keeps_base_items() {
         let mut cache = empty_cache();
@@ -29,6 +29,10 @@ pub struct Settings {
     pub file_operations: FileOperationsSettings,
     #[serde(default)]
     pub project_index: ProjectIndexSettings,
+    #[serde(default)]
+    pub natspec: NatspecSettings,
+    #[serde(default)]
+    pub on_save: OnSaveSettings,
     /// Check GitHub releases for a newer version on startup.
     #[serde(default = "default_true")]
     pub check_for_updates: bool,
@@ -41,11 +45,27 @@ pub struct InlayHintsSettings {
     /// Show parameter-name hints on function/event/struct calls.
     #[serde(default = "default_true")]
     pub parameters: bool,
+    /// Show component type hints in destructuring tuples.
+    #[serde(default = "default_true")]
+    pub types: bool,
+    /// Show storage slot hints on state variables and per-contract slot
+    /// totals after each contract.
+    #[serde(default)]
+    pub storage_slots: bool,
+    /// Show `↑ Base.f` / `↓ N overrides` hints after function and modifier
+    /// headers.
+    #[serde(default = "default_true")]
+    pub inheritance: bool,
 }
 
 impl Default for InlayHintsSettings {
     fn default() -> Self {
-        Self { parameters: true }
+        Self {
+            parameters: true,
+            types: true,
+            storage_slots: false,
+            inheritance: true,
+        }
     }
 }
 
@@ -96,6 +116,9 @@ pub struct FileOperationsSettings {
     /// Auto-update Solidity imports during `workspace/willDeleteFiles`.
     #[serde(default = "default_true")]
     pub update_imports_on_delete: bool,
+    /// Rename `Foo.sol` along with `contract Foo` on `textDocument/rename`.
+    #[serde(default)]
+    pub rename_file_with_contract: bool,
 }
 
 impl Default for FileOperationsSettings {
@@ -104,10 +127,75 @@ impl Default for FileOperationsSettings {
             template_on_create: true,
             update_imports_on_rename: true,
             update_imports_on_delete: true,
+            rename_file_with_contract: false,
         }
     }
 }
 
+/// NatSpec validation settings (powered by `lintspec`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatspecSettings {
+    /// Master toggle for NatSpec diagnostics.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Items that must be documented, using lintspec item names
+    /// (e.g. `["contract", "external-function", "public-function", "event"]`).
+    /// Items not listed are never reported.
+    #[serde(default = "default_natspec_items")]
+    pub items: Vec<String>,
+    /// Tags that are mandatory on the items above: `notice`, `dev`, `param`,
+    /// `return`, `title`, `author`, and `inheritdoc` (public/external
+    /// functions in contracts must use `@inheritdoc`).
+    #[serde(default = "default_natspec_tags")]
+    pub tags: Vec<String>,
+}
+
+impl Default for NatspecSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            items: default_natspec_items(),
+            tags: default_natspec_tags(),
+        }
+    }
+}
+
+fn default_natspec_items() -> Vec<String> {
+    [
+        "external-function",
+        "public-function",
+        "modifier",
+        "event",
+        "error",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+fn default_natspec_tags() -> Vec<String> {
+    ["notice", "param", "return"].map(String::from).to_vec()
+}
+
+/// Save pipeline run by `textDocument/willSaveWaitUntil`.
+///
+/// Each step is off by default; enabled steps run in the order below and
+/// their edits are returned to the editor before the file is written.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnSaveSettings {
+    /// Apply every preferred quick fix from `data/error_codes.json` for the
+    /// file's current diagnostics.
+    #[serde(default)]
+    pub fix_all: bool,
+    /// Organize imports (`source.organizeImports`).
+    #[serde(default)]
+    pub organize_imports: bool,
+    /// Format with `forge fmt`.
+    #[serde(default)]
+    pub format: bool,
+}
+
 /// Project indexing feature settings.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -519,6 +607,64 @@ pub fn find_foundry_toml(start: &Path) -> Option<PathBuf> {
         .map(|p| p.join("foundry.toml"))
 }
 
+/// Directories never searched for nested Foundry projects.
+const PROJECT_SCAN_SKIP_DIRS: &[&str] = &["node_modules", "out", "cache", "target"];
+
+/// How many directory levels below a workspace folder are searched for
+/// nested `foundry.toml` files.
+const PROJECT_SCAN_MAX_DEPTH: usize = 4;
+
+/// Find every Foundry project a workspace folder belongs to or contains.
+///
+/// Returns the `foundry.toml` paths of the folder's own project (the nearest
+/// `foundry.toml` at or above `folder`) plus every nested project below it,
+/// e.g. `contracts/core/foundry.toml` in a monorepo.  Hidden directories,
+/// build output, `node_modules`, and each project's `libs` are not searched,
+/// so vendored dependencies are not treated as workspace projects.
+pub fn discover_foundry_projects(folder: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    if let Some(toml_path) = find_foundry_toml(folder) {
+        found.push(toml_path);
+    }
+    scan_foundry_projects(folder, 0, &mut found);
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn scan_foundry_projects(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if depth > PROJECT_SCAN_MAX_DEPTH {
+        return;
+    }
+    let toml_path = dir.join("foundry.toml");
+    let libs = if toml_path.is_file() {
+        let libs = load_foundry_config_from_toml(&toml_path).libs;
+        found.push(toml_path);
+        libs
+    } else {
+        Vec::new()
+    };
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.')
+            || PROJECT_SCAN_SKIP_DIRS.contains(&name.as_ref())
+            || libs.iter().any(|lib| dir.join(lib) == path)
+        {
+            continue;
+        }
+        scan_foundry_projects(&path, depth + 1, found);
+    }
+}
+
 /// Load the lint configuration from the nearest `foundry.toml` relative to
 /// `file_path`. Returns `LintConfig::default()` when no config is found or
 /// the relevant sections are absent.
@@ -740,6 +886,43 @@ src = "src"
         assert_eq!(found, Some(toml_path));
     }
 
+    #[test]
+    fn test_discover_foundry_projects_finds_nested_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for project in [
+            "contracts/core",
+            "contracts/periphery",
+            "contracts/core/lib/dep",
+        ] {
+            fs::create_dir_all(root.join(project)).unwrap();
+            fs::write(root.join(project).join("foundry.toml"), "[profile.default]").unwrap();
+        }
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(
+            root.join("node_modules/pkg/foundry.toml"),
+            "[profile.default]",
+        )
+        .unwrap();
+
+        let found = discover_foundry_projects(root);
+        assert_eq!(
+            found,
+            vec![
+                root.join("contracts/core/foundry.toml"),
+                root.join("contracts/periphery/foundry.toml"),
+            ]
+        );
+
+        // A folder inside a project resolves to that project.
+        let inside = root.join("contracts/core/src");
+        fs::create_dir_all(&inside).unwrap();
+        assert_eq!(
+            discover_foundry_projects(&inside),
+            vec![root.join("contracts/core/foundry.toml")]
+        );
+    }
+
     #[test]
     fn test_load_lint_config_walks_ancestors() {
         let dir = tempfile::tempdir().unwrap();
@@ -987,23 +1170,62 @@ src = "contracts"
         let value = serde_json::json!({});
         let s = parse_settings(&value);
         assert!(s.inlay_hints.parameters);
+        assert!(s.inlay_hints.types);
+        assert!(!s.inlay_hints.storage_slots);
+        assert!(s.inlay_hints.inheritance);
         assert!(s.lint.enabled);
         assert!(s.file_operations.template_on_create);
         assert!(s.file_operations.update_imports_on_rename);
         assert!(s.file_operations.update_imports_on_delete);
+        assert!(!s.file_operations.rename_file_with_contract);
         assert!(s.project_index.full_project_scan);
         assert_eq!(s.project_index.cache_mode, ProjectIndexCacheMode::V2);
         assert!(!s.project_index.incremental_edit_reindex);
         assert!(s.lint.severity.is_empty());
         assert!(s.lint.only.is_empty());
         assert!(s.lint.exclude.is_empty());
+        assert!(!s.natspec.enabled);
+        assert_eq!(s.natspec.tags, vec!["notice", "param", "return"]);
+        assert!(!s.on_save.organize_imports);
+        assert!(!s.on_save.fix_all);
+        assert!(!s.on_save.format);
+    }
+
+    #[test]
+    fn test_parse_settings_on_save() {
+        let value = serde_json::json!({
+            "solidity-language-server": {
+                "onSave": { "format": true, "organizeImports": true }
+            }
+        });
+        let s = parse_settings(&value);
+        assert!(s.on_save.format);
+        assert!(s.on_save.organize_imports);
+        assert!(!s.on_save.fix_all);
+    }
+
+    #[test]
+    fn test_parse_settings_natspec() {
+        let value = serde_json::json!({
+            "solidity-language-server": {
+                "natspec": {
+                    "enabled": true,
+                    "items": ["contract", "external-function"],
+                    "tags": ["notice", "inheritdoc"]
+                }
+            }
+        });
+        let s = parse_settings(&value);
+        assert!(s.natspec.enabled);
+        assert_eq!(s.natspec.items, vec!["contract", "external-function"]);
+        assert_eq!(s.natspec.tags, vec!["notice", "inheritdoc"]);
     }
 
     #[test]
     fn test_parse_settings_wrapped() {
         let value = serde_json::json!({
             "solidity-language-server": {
-                "inlayHints": { "parameters": false },
+                "inlayHints": { "parameters": false, "storageSlots": true, "inheritance": false },
                 "lint": {
                     "enabled": true,
                     "severity": ["high", "med"],
@@ -1013,7 +1235,8 @@ src = "contracts"
                 "fileOperations": {
                     "templateOnCreate": false,
                     "updateImportsOnRename": false,
-                    "updateImportsOnDelete": false
+                    "updateImportsOnDelete": false,
+                    "renameFileWithContract": true
                 },
                 "projectIndex": {
                     "fullProjectScan": true,
@@ -1024,10 +1247,13 @@ src = "contracts"
         });
         let s = parse_settings(&value);
         assert!(!s.inlay_hints.parameters);
+        assert!(s.inlay_hints.storage_slots);
+        assert!(!s.inlay_hints.inheritance);
         assert!(s.lint.enabled);
         assert!(!s.file_operations.template_on_create);
         assert!(!s.file_operations.update_imports_on_rename);
         assert!(!s.file_operations.update_imports_on_delete);
+        assert!(s.file_operations.rename_file_with_contract);
         assert!(s.project_index.full_project_scan);
         assert_eq!(s.project_index.cache_mode, ProjectIndexCacheMode::V2);
         assert!(s.project_index.incremental_edit_reindex);
@@ -1047,7 +1273,8 @@ src = "contracts"
             "fileOperations": {
                 "templateOnCreate": false,
                 "updateImportsOnRename": false,
-                "updateImportsOnDelete": false
+                "updateImportsOnDelete": false,
+                "renameFileWithContract": true
             },
             "projectIndex": {
                 "fullProjectScan": true,
@@ -1061,6 +1288,7 @@ src = "contracts"
         assert!(!s.file_operations.template_on_create);
         assert!(!s.file_operations.update_imports_on_rename);
         assert!(!s.file_operations.update_imports_on_delete);
+        assert!(s.file_operations.rename_file_with_contract);
         assert!(s.project_index.full_project_scan);
         assert_eq!(s.project_index.cache_mode, ProjectIndexCacheMode::V2);
         assert!(s.project_index.incremental_edit_reindex);
@@ -1081,6 +1309,7 @@ src = "contracts"
         assert!(s.file_operations.template_on_create);
         assert!(s.file_operations.update_imports_on_rename);
         assert!(s.file_operations.update_imports_on_delete);
+        assert!(!s.file_operations.rename_file_with_contract);
         assert!(s.project_index.full_project_scan);
         assert_eq!(s.project_index.cache_mode, ProjectIndexCacheMode::V2);
         assert!(!s.project_index.incremental_edit_reindex);
@@ -1100,6 +1329,7 @@ src = "contracts"
         assert!(s.file_operations.template_on_create);
         assert!(s.file_operations.update_imports_on_rename);
         assert!(s.file_operations.update_imports_on_delete);
+        assert!(!s.file_operations.rename_file_with_contract);
         assert!(s.project_index.full_project_scan);
         assert_eq!(s.project_index.cache_mode, ProjectIndexCacheMode::V2);
         assert!(!s.project_index.incremental_edit_reindex);
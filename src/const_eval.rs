@@ -0,0 +1,524 @@
+//! Compile-time evaluation of constant expressions for hover.
+//!
+//! Evaluates the typed initializer of a `constant` (arithmetic, shifts,
+//! bitwise operators, type conversions, `type(T).min/max`, `keccak256` of a
+//! string literal, number literals with unit suffixes) and follows references
+//! to other constants through `decl_index`. Anything else evaluates to `None`.
+
+use crate::solc_ast::{
+    BinaryOperation, DeclNode, Expression, FunctionCall, FunctionCallKind, Literal, LiteralKind,
+    MemberAccess, Mutability, UnaryOperation,
+};
+use crate::types::NodeId;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::collections::HashMap;
+
+/// How deep constants may reference other constants.
+const MAX_DEPTH: usize = 32;
+
+/// Largest intermediate value, in bits, before evaluation gives up.
+const MAX_BITS: u64 = 4096;
+
+/// Evaluate a constant expression.
+pub fn evaluate(expr: &Expression, decl_index: &HashMap<NodeId, DeclNode>) -> Option<BigInt> {
+    eval(expr, decl_index, 0)
+}
+
+/// Evaluate the initializer of the constant declared as `id`.
+pub fn constant_value(id: NodeId, decl_index: &HashMap<NodeId, DeclNode>) -> Option<BigInt> {
+    constant(id.0, decl_index, 0)
+}
+
+/// The value of a number literal such as `0x1e`, `1_000`, `2.5e3` or
+/// `1.5 ether`, with `unit` the optional subdenomination. `None` if the
+/// literal is not an integer.
+pub fn number_literal(text: &str, unit: Option<&str>) -> Option<BigInt> {
+    let text = text.replace('_', "");
+    let scale: u64 = match unit.unwrap_or("wei") {
+        "wei" | "seconds" => 1,
+        "gwei" => 1_000_000_000,
+        "szabo" => 1_000_000_000_000,
+        "finney" => 1_000_000_000_000_000,
+        "ether" => 1_000_000_000_000_000_000,
+        "minutes" => 60,
+        "hours" => 3_600,
+        "days" => 86_400,
+        "weeks" => 604_800,
+        "years" => 31_536_000,
+        _ => return None,
+    };
+
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return Some(BigInt::parse_bytes(hex.as_bytes(), 16)? * scale);
+    }
+
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (text.as_str(), 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{frac}");
+    let value = BigInt::parse_bytes(digits.as_bytes(), 10)? * scale;
+    let exponent = exponent - frac.len() as i64;
+    if exponent.unsigned_abs() > MAX_BITS {
+        return None;
+    }
+    let power = BigInt::from(10u8).pow(exponent.unsigned_abs() as u32);
+    if exponent >= 0 {
+        Some(value * power)
+    } else if (&value % &power).is_zero() {
+        Some(value / power)
+    } else {
+        None
+    }
+}
+
+/// Render `value` for hover: decimal and hex, plus ether/gwei amounts for
+/// integer types in a plausible currency range. Hex for `bytesN` keeps its
+/// leading zero bytes.
+pub fn format_value(value: &BigInt, type_string: Option<&str>) -> String {
+    let width = type_string
+        .and_then(|t| t.strip_prefix("bytes"))
+        .and_then(|n| n.parse::<usize>().ok())
+        .map_or(0, |n| n * 2);
+    let hex = if value.is_negative() {
+        format!("-0x{:0width$x}", value.magnitude())
+    } else {
+        format!("0x{:0width$x}", value.magnitude())
+    };
+    let mut parts = vec![format!("`{value}`"), format!("`{hex}`")];
+
+    let integer = type_string.is_none_or(|t| {
+        t.starts_with("uint") || t.starts_with("int") || t.starts_with("rational_const")
+    });
+    let gwei = BigInt::from(1_000_000_000u64);
+    let max = BigInt::from(10u8).pow(36);
+    if integer && *value >= gwei && *value < max {
+        parts.push(format!("`{} ether`", decimal(value, 18)));
+        parts.push(format!("`{} gwei`", decimal(value, 9)));
+    }
+    parts.join(" · ")
+}
+
+/// `value / 10^scale` as an exact decimal without trailing zeros.
+fn decimal(value: &BigInt, scale: u32) -> String {
+    let divisor = BigInt::from(10u8).pow(scale);
+    let int = value / &divisor;
+    let frac = (value % &divisor).to_string();
+    if frac == "0" {
+        return int.to_string();
+    }
+    let frac = format!("{frac:0>width$}", width = scale as usize);
+    format!("{int}.{}", frac.trim_end_matches('0'))
+}
+
+fn eval(expr: &Expression, decls: &HashMap<NodeId, DeclNode>, depth: usize) -> Option<BigInt> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let value = match expr {
+        Expression::Literal(lit) => literal(lit)?,
+        Expression::Identifier(ident) => constant(ident.referenced_declaration?, decls, depth)?,
+        Expression::MemberAccess(access) => member_access(access, decls, depth)?,
+        Expression::TupleExpression(tuple)
+            if tuple.components.len() == 1 && tuple.is_inline_array != Some(true) =>
+        {
+            eval(tuple.components[0].as_ref()?, decls, depth)?
+        }
+        Expression::UnaryOperation(op) => unary(op, decls, depth)?,
+        Expression::BinaryOperation(op) => binary(op, decls, depth)?,
+        Expression::FunctionCall(call) => function_call(call, decls, depth)?,
+        _ => return None,
+    };
+    (value.bits() <= MAX_BITS).then_some(value)
+}
+
+fn constant(id: i64, decls: &HashMap<NodeId, DeclNode>, depth: usize) -> Option<BigInt> {
+    match decls.get(&NodeId(id))? {
+        DeclNode::VariableDeclaration(var) if var.mutability == Some(Mutability::Constant) => {
+            eval(var.value.as_ref()?, decls, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+fn literal(lit: &Literal) -> Option<BigInt> {
+    match lit.kind {
+        LiteralKind::Number => {
+            number_literal(lit.value.as_deref()?, lit.subdenomination.as_deref())
+        }
+        _ => None,
+    }
+}
+
+/// `type(T).min` / `type(T).max`, or a qualified constant such as `Lib.FEE`.
+fn member_access(
+    access: &MemberAccess,
+    decls: &HashMap<NodeId, DeclNode>,
+    depth: usize,
+) -> Option<BigInt> {
+    if let Expression::FunctionCall(call) = access.expression.as_ref()
+        && let Expression::Identifier(callee) = call.expression.as_ref()
+        && callee.name == "type"
+    {
+        let ty = call
+            .type_descriptions
+            .type_string
+            .as_deref()?
+            .strip_prefix("type(")?
+            .strip_suffix(')')?;
+        let (signed, bits) = integer_type(ty)?;
+        let (min, max) = if signed {
+            let half = BigInt::one() << (bits - 1);
+            (-half.clone(), half - 1)
+        } else {
+            (BigInt::zero(), (BigInt::one() << bits) - 1)
+        };
+        return match access.member_name.as_str() {
+            "min" => Some(min),
+            "max" => Some(max),
+            _ => None,
+        };
+    }
+    constant(access.referenced_declaration?, decls, depth)
+}
+
+fn unary(op: &UnaryOperation, decls: &HashMap<NodeId, DeclNode>, depth: usize) -> Option<BigInt> {
+    let value = eval(&op.sub_expression, decls, depth)?;
+    match op.operator.as_str() {
+        "-" => Some(-value),
+        "~" => match op
+            .type_descriptions
+            .type_string
+            .as_deref()
+            .and_then(integer_type)
+        {
+            Some((false, bits)) => Some(((BigInt::one() << bits) - 1) - value),
+            _ => Some(-value - 1),
+        },
+        _ => None,
+    }
+}
+
+fn binary(op: &BinaryOperation, decls: &HashMap<NodeId, DeclNode>, depth: usize) -> Option<BigInt> {
+    let lhs = eval(&op.left_expression, decls, depth)?;
+    let rhs = eval(&op.right_expression, decls, depth)?;
+    let ty = op.type_descriptions.type_string.as_deref().unwrap_or("");
+    // Literal-only arithmetic is exact in solc; typed arithmetic truncates.
+    let exact = ty.starts_with("int_const") || ty.starts_with("rational_const");
+    let shift = || rhs.to_u64().filter(|&n| n <= MAX_BITS);
+
+    let value = match op.operator.as_str() {
+        "+" => lhs + rhs,
+        "-" => lhs - rhs,
+        "*" => lhs * rhs,
+        "/" if rhs.is_zero() || (exact && !(&lhs % &rhs).is_zero()) => return None,
+        "/" => lhs / rhs,
+        "%" if rhs.is_zero() => return None,
+        "%" => lhs % rhs,
+        "**" => {
+            let exponent = rhs.to_u32()?;
+            if lhs.bits() * exponent as u64 > MAX_BITS {
+                return None;
+            }
+            lhs.pow(exponent)
+        }
+        "<<" => {
+            let value = lhs << shift()?;
+            match integer_type(ty) {
+                Some((false, bits)) => value & ((BigInt::one() << bits) - 1),
+                _ => value,
+            }
+        }
+        ">>" => lhs >> shift()?,
+        "&" => lhs & rhs,
+        "|" => lhs | rhs,
+        "^" => lhs ^ rhs,
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Type conversions apply the conversion to their argument; `keccak256` of
+/// a string literal hashes it.
+fn function_call(
+    call: &FunctionCall,
+    decls: &HashMap<NodeId, DeclNode>,
+    depth: usize,
+) -> Option<BigInt> {
+    let [arg] = call.arguments.as_slice() else {
+        return None;
+    };
+    if call.kind == Some(FunctionCallKind::TypeConversion) {
+        let value = eval(arg, decls, depth)?;
+        let from = arg.type_descriptions().type_string.as_deref().unwrap_or("");
+        let to = call.type_descriptions.type_string.as_deref().unwrap_or("");
+        return Some(convert(value, from, to));
+    }
+    match (call.expression.as_ref(), arg) {
+        (Expression::Identifier(callee), Expression::Literal(lit))
+            if callee.name == "keccak256"
+                && matches!(lit.kind, LiteralKind::String | LiteralKind::UnicodeString) =>
+        {
+            use tiny_keccak::{Hasher, Keccak};
+            let bytes = match lit.hex_value.as_deref() {
+                Some(hex) => hex::decode(hex).ok()?,
+                None => lit.value.as_deref()?.as_bytes().to_vec(),
+            };
+            let mut hasher = Keccak::v256();
+            hasher.update(&bytes);
+            let mut hash = [0u8; 32];
+            hasher.finalize(&mut hash);
+            Some(BigInt::from_bytes_be(Sign::Plus, &hash))
+        }
+        _ => None,
+    }
+}
+
+/// `value` of type `from` converted to `to`.
+///
+/// Between `bytesN` types the high-order bytes are kept, as in solc; integer
+/// and address targets are truncated to their width, sign-extending `intN`.
+/// Anything else passes through.
+fn convert(value: BigInt, from: &str, to: &str) -> BigInt {
+    if let (Some(from), Some(to)) = (fixed_bytes(from), fixed_bytes(to)) {
+        return if to < from {
+            value >> (8 * (from - to))
+        } else {
+            value << (8 * (to - from))
+        };
+    }
+    let (signed, bits) = match to {
+        "address" | "address payable" => (false, 160),
+        _ => match integer_type(to) {
+            Some(ty) => ty,
+            None => return value,
+        },
+    };
+    let value: BigInt = value & ((BigInt::one() << bits) - 1);
+    if signed && value.bit(bits as u64 - 1) {
+        value - (BigInt::one() << bits)
+    } else {
+        value
+    }
+}
+
+/// `N` for `bytesN` type strings.
+fn fixed_bytes(ty: &str) -> Option<usize> {
+    let n = ty.strip_prefix("bytes")?.parse().ok()?;
+    (1..=32).contains(&n).then_some(n)
+}
+
+/// `(signed, bits)` for `uintN` / `intN` type strings.
+fn integer_type(ty: &str) -> Option<(bool, usize)> {
+    let (signed, bits) = match ty.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => (true, ty.strip_prefix("int")?),
+    };
+    let bits = if bits.is_empty() {
+        256
+    } else {
+        bits.parse().ok()?
+    };
+    (8..=256).contains(&bits).then_some((signed, bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn number(value: &str) -> Value {
+        json!({"nodeType": "Literal", "id": 1, "src": "0:0:0", "kind": "number", "value": value,
+            "typeDescriptions": {"typeString": format!("int_const {value}")}})
+    }
+
+    fn binary(op: &str, lhs: Value, rhs: Value, type_string: &str) -> Value {
+        json!({"nodeType": "BinaryOperation", "id": 2, "src": "0:0:0", "operator": op,
+            "leftExpression": lhs, "rightExpression": rhs,
+            "typeDescriptions": {"typeString": type_string}})
+    }
+
+    fn expr(value: Value) -> Expression {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn constant_decl(id: i64, value: Value) -> DeclNode {
+        DeclNode::VariableDeclaration(
+            serde_json::from_value(json!({
+                "id": id, "src": "0:0:0", "name": format!("C{id}"),
+                "mutability": "constant", "constant": true, "value": value,
+                "typeDescriptions": {"typeString": "uint256"}
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_number_literal() {
+        assert_eq!(number_literal("0x1e", None), Some(BigInt::from(30)));
+        assert_eq!(number_literal("1_000", None), Some(BigInt::from(1000)));
+        assert_eq!(number_literal("2.5e3", None), Some(BigInt::from(2500)));
+        assert_eq!(
+            number_literal("2", Some("days")),
+            Some(BigInt::from(172_800))
+        );
+        assert_eq!(
+            number_literal("1.5", Some("ether")),
+            Some(BigInt::from(1_500_000_000_000_000_000u64))
+        );
+        assert_eq!(number_literal("0.5", None), None);
+        assert_eq!(number_literal("1", Some("parsecs")), None);
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(
+            format_value(&BigInt::from(1_000_000_000u64), Some("uint256")),
+            "`1000000000` · `0x3b9aca00` · `0.000000001 ether` · `1 gwei`"
+        );
+        assert_eq!(
+            format_value(&BigInt::from(-1), Some("int8")),
+            "`-1` · `-0x1`"
+        );
+        assert_eq!(
+            format_value(&BigInt::from(1_000_000_000u64), Some("bytes32")),
+            "`1000000000` · `0x000000000000000000000000000000000000000000000000000000003b9aca00`"
+        );
+    }
+
+    #[test]
+    fn test_evaluate_follows_constant_references() {
+        // uint256 constant C10 = 3 * 10**15;
+        let fee = binary(
+            "*",
+            number("3"),
+            binary(
+                "**",
+                number("10"),
+                number("15"),
+                "int_const 1000000000000000",
+            ),
+            "int_const 3000000000000000",
+        );
+        // uint256 constant C11 = C10 << 1;
+        let doubled = binary(
+            "<<",
+            json!({"nodeType": "Identifier", "id": 3, "src": "0:0:0", "name": "C10",
+                "referencedDeclaration": 10}),
+            number("1"),
+            "uint256",
+        );
+        let decls = HashMap::from([
+            (NodeId(10), constant_decl(10, fee)),
+            (NodeId(11), constant_decl(11, doubled)),
+        ]);
+
+        assert_eq!(
+            constant_value(NodeId(10), &decls),
+            Some(BigInt::from(3_000_000_000_000_000u64))
+        );
+        assert_eq!(
+            constant_value(NodeId(11), &decls),
+            Some(BigInt::from(6_000_000_000_000_000u64))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_type_bounds_and_keccak() {
+        let decls = HashMap::new();
+        let max = expr(json!({
+            "nodeType": "MemberAccess", "id": 4, "src": "0:0:0", "memberName": "max",
+            "expression": {"nodeType": "FunctionCall", "id": 5, "src": "0:0:0", "arguments": [],
+                "expression": {"nodeType": "Identifier", "id": 6, "src": "0:0:0", "name": "type"},
+                "typeDescriptions": {"typeString": "type(uint8)"}},
+            "typeDescriptions": {"typeString": "uint8"}
+        }));
+        assert_eq!(evaluate(&max, &decls), Some(BigInt::from(255)));
+
+        let hash = expr(json!({
+            "nodeType": "FunctionCall", "id": 7, "src": "0:0:0", "kind": "functionCall",
+            "expression": {"nodeType": "Identifier", "id": 8, "src": "0:0:0", "name": "keccak256"},
+            "arguments": [{"nodeType": "Literal", "id": 9, "src": "0:0:0", "kind": "string",
+                "value": "", "hexValue": ""}],
+            "typeDescriptions": {"typeString": "bytes32"}
+        }));
+        assert_eq!(
+            evaluate(&hash, &decls).map(|v| format!("{v:x}")).as_deref(),
+            Some("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+        );
+
+        // bytes4(keccak256("transfer(address,uint256)"))
+        let selector = expr(json!({
+            "nodeType": "FunctionCall", "id": 10, "src": "0:0:0", "kind": "typeConversion",
+            "expression": {"nodeType": "ElementaryTypeNameExpression", "id": 11, "src": "0:0:0",
+                "typeName": {"nodeType": "ElementaryTypeName", "id": 12, "src": "0:0:0",
+                    "name": "bytes4"}},
+            "arguments": [{"nodeType": "FunctionCall", "id": 13, "src": "0:0:0",
+                "kind": "functionCall",
+                "expression": {"nodeType": "Identifier", "id": 14, "src": "0:0:0",
+                    "name": "keccak256"},
+                "arguments": [{"nodeType": "Literal", "id": 15, "src": "0:0:0",
+                    "kind": "string", "value": "transfer(address,uint256)"}],
+                "typeDescriptions": {"typeString": "bytes32"}}],
+            "typeDescriptions": {"typeString": "bytes4"}
+        }));
+        assert_eq!(
+            evaluate(&selector, &decls),
+            Some(BigInt::from(0xa9059cbbu32))
+        );
+
+        let inexact = expr(binary(
+            "/",
+            number("1"),
+            number("3"),
+            "rational_const 1 / 3",
+        ));
+        assert_eq!(evaluate(&inexact, &decls), None);
+    }
+
+    #[test]
+    fn test_evaluate_narrowing_conversions() {
+        let conversion = |to: &str, arg: Value| {
+            expr(json!({
+                "nodeType": "FunctionCall", "id": 20, "src": "0:0:0", "kind": "typeConversion",
+                "expression": {"nodeType": "ElementaryTypeNameExpression", "id": 21,
+                    "src": "0:0:0",
+                    "typeName": {"nodeType": "ElementaryTypeName", "id": 22, "src": "0:0:0",
+                        "name": to}},
+                "arguments": [arg],
+                "typeDescriptions": {"typeString": to}
+            }))
+        };
+        let decls = HashMap::from([(NodeId(10), constant_decl(10, number("511")))]);
+        let c10 = json!({"nodeType": "Identifier", "id": 3, "src": "0:0:0", "name": "C10",
+            "referencedDeclaration": 10, "typeDescriptions": {"typeString": "uint256"}});
+
+        // uint8(C10) and int8(C10) with C10 = 511.
+        assert_eq!(
+            evaluate(&conversion("uint8", c10.clone()), &decls),
+            Some(BigInt::from(255))
+        );
+        assert_eq!(
+            evaluate(&conversion("int8", c10), &decls),
+            Some(BigInt::from(-1))
+        );
+
+        // uint160(uint256(-1)) keeps the low 160 bits.
+        let all_ones = (BigInt::one() << 256) - 1;
+        assert_eq!(
+            convert(all_ones, "uint256", "uint160"),
+            (BigInt::one() << 160) - 1
+        );
+        // Widening bytes pads on the right.
+        assert_eq!(
+            convert(BigInt::from(0xa9059cbbu32), "bytes4", "bytes8"),
+            BigInt::from(0xa9059cbb_00000000u64)
+        );
+        assert_eq!(
+            convert(BigInt::from(7), "int_const 7", "bytes32"),
+            BigInt::from(7)
+        );
+    }
+}
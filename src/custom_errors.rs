@@ -0,0 +1,686 @@
+//! Convert string reverts to custom errors.
+//!
+//! `require(cond, "message")` and `revert("message")` are replaced by a
+//! parameterless `error` declared in the enclosing contract and named after
+//! the message text. Sites sharing a message share one error, and an existing
+//! parameterless error of the same name is reused rather than redeclared,
+//! whether it is declared in the contract, at file level, or in an inherited
+//! contract (found through the build's linearized bases when one is
+//! available).
+//!
+//! A `require` becomes `if (!cond) revert Name();`, or keeps its shape as
+//! `require(cond, Name())` when the compiler accepts custom errors in
+//! `require` (see [`MIN_REQUIRE_ERRORS`]).
+
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::{Range, TextEdit};
+use tree_sitter::{Node, Tree};
+
+use crate::extract::line_indent;
+use crate::goto::CachedBuild;
+use crate::solc::SemVer;
+use crate::solc_ast::{ContractDefinitionNode, DeclNode};
+use crate::utils::byte_offset_to_position;
+
+/// Custom errors were introduced in solc 0.8.4.
+pub const MIN_CUSTOM_ERRORS: SemVer = SemVer {
+    major: 0,
+    minor: 8,
+    patch: 4,
+};
+
+/// `require(bool, error)` compiles with both pipelines from solc 0.8.27
+/// (0.8.26 accepted it with `via_ir` only).
+pub const MIN_REQUIRE_ERRORS: SemVer = SemVer {
+    major: 0,
+    minor: 8,
+    patch: 27,
+};
+
+/// A custom error conversion offered at the cursor.
+#[derive(Debug)]
+pub struct Conversion {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A `require` or `revert` with a string message.
+struct Site<'a> {
+    /// The `expression_statement` or `revert_statement`.
+    statement: Node<'a>,
+    /// The `string_literal` argument.
+    message_node: Node<'a>,
+    /// The `require` condition; `None` for a `revert`.
+    condition: Option<Node<'a>>,
+    message: String,
+    /// The enclosing contract or library.
+    contract: Node<'a>,
+}
+
+/// Whether the file has any string `require` / `revert` to convert.
+pub fn has_string_errors(source: &str, tree: &Tree) -> bool {
+    let mut sites = Vec::new();
+    collect_sites(source, tree.root_node(), &mut sites);
+    !sites.is_empty()
+}
+
+/// The conversions available at `byte`: the site under the cursor, every site
+/// in the enclosing contract, and every site in the file. A wider scope is
+/// only offered when it converts more than the narrower one.
+///
+/// `require_errors` selects the `require(cond, Name())` rewrite. `build`,
+/// when given, resolves the errors each contract inherits; `abs_path` is the
+/// file's path in that build.
+pub fn conversions_at(
+    source: &str,
+    tree: &Tree,
+    byte: usize,
+    require_errors: bool,
+    build: Option<&CachedBuild>,
+    abs_path: &str,
+) -> Vec<Conversion> {
+    let scope = Scope {
+        source,
+        build,
+        abs_path,
+    };
+    let mut sites = Vec::new();
+    collect_sites(source, tree.root_node(), &mut sites);
+
+    let at_cursor: Vec<&Site> = sites
+        .iter()
+        .filter(|s| s.statement.start_byte() <= byte && byte <= s.statement.end_byte())
+        .take(1)
+        .collect();
+    let contract = sites
+        .iter()
+        .find(|s| s.contract.start_byte() <= byte && byte <= s.contract.end_byte())
+        .map(|s| s.contract);
+    let in_contract: Vec<&Site> = sites
+        .iter()
+        .filter(|s| Some(s.contract) == contract)
+        .collect();
+    let in_file: Vec<&Site> = sites.iter().collect();
+
+    let mut conversions = Vec::new();
+    if let Some(site) = at_cursor.first() {
+        let (edits, names) = convert(&scope, &at_cursor, require_errors);
+        conversions.push(Conversion {
+            title: format!("Convert to custom error `{}`", names[&site.message]),
+            edits,
+        });
+    }
+    if let Some(contract) = contract
+        && in_contract.len() > at_cursor.len()
+    {
+        let name = contract
+            .child_by_field_name("name")
+            .map_or("", |n| &source[n.byte_range()]);
+        conversions.push(Conversion {
+            title: format!("Convert string errors in `{name}` to custom errors"),
+            edits: convert(&scope, &in_contract, require_errors).0,
+        });
+    }
+    if in_file.len() > in_contract.len() {
+        conversions.push(Conversion {
+            title: "Convert string errors in file to custom errors".to_string(),
+            edits: convert(&scope, &in_file, require_errors).0,
+        });
+    }
+    conversions
+}
+
+/// Where errors are looked up besides the enclosing contract.
+struct Scope<'a> {
+    source: &'a str,
+    build: Option<&'a CachedBuild>,
+    abs_path: &'a str,
+}
+
+/// Rewrite `sites` and declare their errors. Also returns the error name
+/// chosen for each message.
+fn convert(
+    scope: &Scope,
+    sites: &[&Site],
+    require_errors: bool,
+) -> (Vec<TextEdit>, HashMap<String, String>) {
+    let source = scope.source;
+    let mut edits = Vec::new();
+    let mut names: HashMap<String, String> = HashMap::new();
+
+    let mut contracts: Vec<Node> = Vec::new();
+    for site in sites {
+        if !contracts.contains(&site.contract) {
+            contracts.push(site.contract);
+        }
+    }
+    for contract in contracts {
+        let mut existing = declared_errors(source, contract);
+        let mut taken = identifiers(source, contract);
+        for (name, parameterless) in visible_errors(scope, contract) {
+            if parameterless {
+                existing.insert(name.clone());
+            }
+            taken.insert(name);
+        }
+        let mut declarations = Vec::new();
+        // Names are per contract: each one declares its own errors.
+        let mut contract_names: HashMap<String, String> = HashMap::new();
+
+        for site in sites.iter().filter(|s| s.contract == contract) {
+            let name = match contract_names.get(&site.message) {
+                Some(name) => name.clone(),
+                None => {
+                    let base = error_name(&site.message);
+                    let name = if existing.contains(&base) {
+                        base
+                    } else {
+                        let name = (1..)
+                            .map(|i| match i {
+                                1 => base.clone(),
+                                _ => format!("{base}{i}"),
+                            })
+                            .find(|n| !taken.contains(n))
+                            .unwrap_or_default();
+                        taken.insert(name.clone());
+                        declarations.push(name.clone());
+                        name
+                    };
+                    contract_names.insert(site.message.clone(), name.clone());
+                    name
+                }
+            };
+
+            let (start, end, text) = match site.condition {
+                Some(_) if require_errors => (
+                    site.message_node.start_byte(),
+                    site.message_node.end_byte(),
+                    format!("{name}()"),
+                ),
+                Some(condition) => (
+                    site.statement.start_byte(),
+                    site.statement.end_byte(),
+                    format!("if ({}) revert {name}();", negate(source, condition)),
+                ),
+                None => (
+                    site.statement.start_byte(),
+                    site.statement.end_byte(),
+                    format!("revert {name}();"),
+                ),
+            };
+            edits.push(edit(source, start, end, text));
+        }
+
+        if !declarations.is_empty()
+            && let Some(edit) = declare(source, contract, &declarations)
+        {
+            edits.push(edit);
+        }
+        for (message, name) in contract_names {
+            names.entry(message).or_insert(name);
+        }
+    }
+    (edits, names)
+}
+
+/// Insert `error Name();` declarations after the contract's last error, or
+/// at the top of its body.
+fn declare(source: &str, contract: Node<'_>, names: &[String]) -> Option<TextEdit> {
+    let body = contract.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let members: Vec<Node> = body.named_children(&mut cursor).collect();
+    let indent = members
+        .first()
+        .map(|m| line_indent(source, m.start_byte()))
+        .filter(|i| !i.is_empty())
+        .unwrap_or_else(|| "    ".to_string());
+    let lines: Vec<String> = names
+        .iter()
+        .map(|name| format!("{indent}error {name}();"))
+        .collect();
+
+    match members.iter().rfind(|m| m.kind() == "error_declaration") {
+        Some(last) => {
+            let at = last.end_byte();
+            Some(edit(source, at, at, format!("\n{}", lines.join("\n"))))
+        }
+        None => {
+            let at = body.start_byte() + 1;
+            Some(edit(source, at, at, format!("\n{}\n", lines.join("\n"))))
+        }
+    }
+}
+
+/// Every string `require` / `revert` under `node`.
+fn collect_sites<'a>(source: &str, node: Node<'a>, out: &mut Vec<Site<'a>>) {
+    if let Some(site) = site(source, node) {
+        out.push(site);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_sites(source, child, out);
+    }
+}
+
+fn site<'a>(source: &str, node: Node<'a>) -> Option<Site<'a>> {
+    let (condition, message_node) = match node.kind() {
+        "expression_statement" => {
+            let call = node.named_child(0)?.named_child(0)?;
+            let function = call.child_by_field_name("function")?;
+            if call.kind() != "call_expression" || &source[function.byte_range()] != "require" {
+                return None;
+            }
+            let mut cursor = call.walk();
+            let args: Vec<Node> = call
+                .named_children(&mut cursor)
+                .filter(|c| c.kind() == "call_argument")
+                .collect();
+            let [condition, message] = args[..] else {
+                return None;
+            };
+            (Some(condition.named_child(0)?), string_literal(message)?)
+        }
+        "revert_statement" => {
+            let error = node.child_by_field_name("error")?.named_child(0)?;
+            if error.kind() != "parenthesized_expression" {
+                return None;
+            }
+            (None, string_literal(error)?)
+        }
+        _ => return None,
+    };
+    let contract = enclosing_contract(node)?;
+    let message = message_node
+        .named_children(&mut message_node.walk())
+        .map(|s| {
+            let text = &source[s.byte_range()];
+            text.get(1..text.len().saturating_sub(1)).unwrap_or("")
+        })
+        .collect();
+    Some(Site {
+        statement: node,
+        message_node,
+        condition,
+        message,
+        contract,
+    })
+}
+
+/// The `string_literal` wrapped by a `call_argument` or parenthesized expression.
+fn string_literal(node: Node<'_>) -> Option<Node<'_>> {
+    let literal = node.named_child(0)?.named_child(0)?;
+    (literal.kind() == "string_literal").then_some(literal)
+}
+
+fn enclosing_contract(node: Node<'_>) -> Option<Node<'_>> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if matches!(n.kind(), "contract_declaration" | "library_declaration") {
+            return Some(n);
+        }
+        current = n.parent();
+    }
+    None
+}
+
+/// Names of the parameterless errors declared in `contract`.
+fn declared_errors(source: &str, contract: Node<'_>) -> HashSet<String> {
+    let Some(body) = contract.child_by_field_name("body") else {
+        return HashSet::new();
+    };
+    error_declarations(source, body)
+        .into_iter()
+        .filter(|(_, parameterless)| *parameterless)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// The errors declared directly under `node`, each with whether it takes
+/// no parameters.
+fn error_declarations(source: &str, node: Node<'_>) -> Vec<(String, bool)> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|m| m.kind() == "error_declaration")
+        .filter_map(|m| {
+            let name = m.child_by_field_name("name")?;
+            let mut cursor = m.walk();
+            let parameterless = !m
+                .named_children(&mut cursor)
+                .any(|p| p.kind() == "error_parameter");
+            Some((source[name.byte_range()].to_string(), parameterless))
+        })
+        .collect()
+}
+
+/// Errors visible in `contract` that it doesn't declare itself: the
+/// file-level ones, and with a build, those of its linearized bases.
+fn visible_errors(scope: &Scope, contract: Node<'_>) -> Vec<(String, bool)> {
+    let mut errors = contract
+        .parent()
+        .map(|file| error_declarations(scope.source, file))
+        .unwrap_or_default();
+
+    let Some(build) = scope.build else {
+        return errors;
+    };
+    let Some(contract_id) = contract
+        .child_by_field_name("name")
+        .map(|name| &scope.source[name.byte_range()])
+        .and_then(|name| crate::missing_functions::contract_by_name(build, scope.abs_path, name))
+    else {
+        return errors;
+    };
+    for base_id in crate::missing_functions::linearization(build, contract_id) {
+        if base_id == contract_id {
+            continue;
+        }
+        let Some(DeclNode::ContractDefinition(base)) = build.decl_index.get(&base_id) else {
+            continue;
+        };
+        for member in &base.nodes {
+            if let ContractDefinitionNode::ErrorDefinition(error) = member {
+                errors.push((error.name.clone(), error.parameters.parameters.is_empty()));
+            }
+        }
+    }
+    errors
+}
+
+fn identifiers(source: &str, node: Node<'_>) -> HashSet<String> {
+    let mut out = HashSet::new();
+    let mut stack = vec![node];
+    while let Some(n) = stack.pop() {
+        if n.kind() == "identifier" {
+            out.insert(source[n.byte_range()].to_string());
+        }
+        let mut cursor = n.walk();
+        stack.extend(n.named_children(&mut cursor));
+    }
+    out
+}
+
+/// PascalCase error name for `message`. A `Prefix: ` naming the contract is
+/// dropped, and SHOUTING words are title-cased.
+///
+/// `"Ownable: caller is not the owner"` → `CallerIsNotTheOwner`
+fn error_name(message: &str) -> String {
+    let text = message.rsplit_once(": ").map_or(message, |(_, rest)| rest);
+    let name: String = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|word| {
+            let word = if word.len() > 1 && !word.chars().any(|c| c.is_ascii_lowercase()) {
+                word.to_ascii_lowercase()
+            } else {
+                word.to_string()
+            };
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    match name.chars().next() {
+        None => "CustomError".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("Error{name}"),
+        Some(_) => name,
+    }
+}
+
+/// The negation of a `require` condition, written the way a person would:
+/// `!x` becomes `x` and comparisons flip their operator.
+fn negate(source: &str, condition: Node<'_>) -> String {
+    let text = &source[condition.byte_range()];
+    let Some(inner) = condition.named_child(0) else {
+        return format!("!({text})");
+    };
+    let operator = inner
+        .child_by_field_name("operator")
+        .map(|op| (op, &source[op.byte_range()]));
+    match (inner.kind(), operator) {
+        ("unary_expression", Some((_, "!"))) => inner
+            .child_by_field_name("argument")
+            .map_or_else(String::new, |a| source[a.byte_range()].to_string()),
+        ("binary_expression", Some((op, symbol))) => {
+            let flipped = match symbol {
+                "==" => "!=",
+                "!=" => "==",
+                "<" => ">=",
+                ">=" => "<",
+                ">" => "<=",
+                "<=" => ">",
+                _ => return format!("!({text})"),
+            };
+            format!(
+                "{}{flipped}{}",
+                &source[condition.start_byte()..op.start_byte()],
+                &source[op.end_byte()..condition.end_byte()]
+            )
+        }
+        (
+            "identifier"
+            | "call_expression"
+            | "member_expression"
+            | "array_access"
+            | "parenthesized_expression"
+            | "boolean_literal",
+            _,
+        ) => format!("!{text}"),
+        _ => format!("!({text})"),
+    }
+}
+
+fn edit(source: &str, start: usize, end: usize, new_text: String) -> TextEdit {
+    TextEdit {
+        range: Range {
+            start: byte_offset_to_position(source, start),
+            end: byte_offset_to_position(source, end),
+        },
+        new_text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"contract Vault {
+    error Paused();
+
+    function withdraw(uint amount) external {
+        require(!paused, "paused");
+        require(amount <= balance, "Vault: insufficient balance");
+        if (amount == 0) revert("Vault: insufficient balance");
+    }
+}
+
+library L {
+    function f(uint x) internal pure {
+        require(x != 0 && x < 10, "OUT_OF_RANGE");
+    }
+}
+"#;
+
+    fn apply(source: &str, edits: &[TextEdit]) -> String {
+        let mut edits = edits.to_vec();
+        edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+        let mut out = source.to_string();
+        for e in edits {
+            let start = crate::utils::position_to_byte_offset(&out, e.range.start);
+            let end = crate::utils::position_to_byte_offset(&out, e.range.end);
+            out.replace_range(start..end, &e.new_text);
+        }
+        out
+    }
+
+    #[test]
+    fn test_error_name() {
+        assert_eq!(
+            error_name("Ownable: caller is not the owner"),
+            "CallerIsNotTheOwner"
+        );
+        assert_eq!(error_name("OUT_OF_RANGE"), "OutOfRange");
+        assert_eq!(error_name("ERC20 overflow"), "Erc20Overflow");
+        assert_eq!(error_name("1 too many"), "Error1TooMany");
+        assert_eq!(error_name("!!"), "CustomError");
+    }
+
+    #[test]
+    fn test_convert_contract_shares_errors_by_message() {
+        let tree = crate::syntax::parse(SOURCE).unwrap();
+        let byte = SOURCE.find("withdraw").unwrap();
+        let conversions = conversions_at(SOURCE, &tree, byte, false, None, "");
+        let titles: Vec<&str> = conversions.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Convert string errors in `Vault` to custom errors",
+                "Convert string errors in file to custom errors",
+            ]
+        );
+        let out = apply(SOURCE, &conversions[0].edits);
+        assert!(out.contains(
+            "    error Paused();\n    error InsufficientBalance();\n\n    function withdraw"
+        ));
+        assert!(out.contains("        if (paused) revert Paused();\n"));
+        assert!(out.contains("        if (amount > balance) revert InsufficientBalance();\n"));
+        assert!(out.contains("if (amount == 0) revert InsufficientBalance();\n"));
+        assert!(out.contains(r#"require(x != 0 && x < 10, "OUT_OF_RANGE");"#));
+    }
+
+    #[test]
+    fn test_convert_site_with_require_errors() {
+        let tree = crate::syntax::parse(SOURCE).unwrap();
+        let byte = SOURCE.find("require(x").unwrap();
+        let conversions = conversions_at(SOURCE, &tree, byte, true, None, "");
+        assert_eq!(conversions[0].title, "Convert to custom error `OutOfRange`");
+        let out = apply(SOURCE, &conversions[0].edits);
+        assert!(out.contains(
+            "library L {\n    error OutOfRange();\n\n    function f(uint x) internal pure {\n        require(x != 0 && x < 10, OutOfRange());"
+        ));
+    }
+
+    #[test]
+    fn test_negate_compound_condition() {
+        let source = "contract C { function f() public { require(a && b, \"no\"); } }";
+        let tree = crate::syntax::parse(source).unwrap();
+        let conversions = conversions_at(
+            source,
+            &tree,
+            source.find("require").unwrap(),
+            false,
+            None,
+            "",
+        );
+        let out = apply(source, &conversions[0].edits);
+        assert!(out.contains("if (!(a && b)) revert No();"));
+    }
+
+    #[test]
+    fn test_convert_file_declares_shared_message_per_contract() {
+        let source = r#"contract A {
+    function f() public { require(ok, "not allowed"); }
+}
+
+contract B {
+    function g() public { revert("not allowed"); }
+}
+"#;
+        let tree = crate::syntax::parse(source).unwrap();
+        let conversions = conversions_at(
+            source,
+            &tree,
+            source.find("require").unwrap(),
+            false,
+            None,
+            "",
+        );
+        assert_eq!(
+            conversions[1].title,
+            "Convert string errors in file to custom errors"
+        );
+        let out = apply(source, &conversions[1].edits);
+        assert!(out.contains(
+            "contract A {\n    error NotAllowed();\n\n    function f() public { if (!ok) revert NotAllowed(); }"
+        ));
+        assert!(out.contains(
+            "contract B {\n    error NotAllowed();\n\n    function g() public { revert NotAllowed(); }"
+        ));
+    }
+
+    #[test]
+    fn test_convert_reuses_file_level_and_inherited_errors() {
+        let source = r#"error Unauthorized();
+
+contract Base {
+    error NotOwner();
+    error Paused(uint256 until);
+}
+
+contract Vault is Base {
+    function f() public {
+        require(owner, "Vault: not owner");
+        require(live, "paused");
+        revert("unauthorized");
+    }
+}
+"#;
+        let path = "/tmp/Vault.sol";
+        let error = |id: i64, name: &str, params: serde_json::Value| {
+            serde_json::json!({
+                "id": id, "nodeType": "ErrorDefinition", "src": "0:0:0", "name": name,
+                "parameters": { "id": id + 1, "nodeType": "ParameterList", "src": "0:0:0", "parameters": params },
+            })
+        };
+        let contract = |id: i64, name: &str, linearized: &[i64], nodes: serde_json::Value| {
+            serde_json::json!({
+                "id": id, "nodeType": "ContractDefinition", "src": "0:0:0", "name": name,
+                "contractKind": "contract", "linearizedBaseContracts": linearized, "nodes": nodes,
+            })
+        };
+        let until = serde_json::json!([{
+            "id": 14, "nodeType": "VariableDeclaration", "src": "0:0:0", "name": "until",
+            "typeDescriptions": { "typeString": "uint256" },
+        }]);
+        let nodes = serde_json::json!([
+            error(2, "Unauthorized", serde_json::json!([])),
+            contract(
+                4,
+                "Base",
+                &[4],
+                serde_json::json!([
+                    error(10, "NotOwner", serde_json::json!([])),
+                    error(12, "Paused", until)
+                ])
+            ),
+            contract(20, "Vault", &[20, 4], serde_json::json!([])),
+        ]);
+        let build = CachedBuild::new(
+            crate::test_support::output(path, source.len(), nodes),
+            0,
+            None,
+        );
+        let tree = crate::syntax::parse(source).unwrap();
+        let byte = source.find("function f").unwrap();
+
+        let conversions = conversions_at(source, &tree, byte, false, Some(&build), path);
+        let out = apply(source, &conversions[0].edits);
+        assert!(out.contains("contract Vault is Base {\n    error Paused2();\n\n"));
+        assert!(out.contains("if (!owner) revert NotOwner();"));
+        assert!(out.contains("if (!live) revert Paused2();"));
+        assert!(out.contains("revert Unauthorized();"));
+
+        // Without a build only the file-level error is known.
+        let conversions = conversions_at(source, &tree, byte, false, None, path);
+        let out = apply(source, &conversions[0].edits);
+        assert!(
+            out.contains(
+                "contract Vault is Base {\n    error NotOwner();\n    error Paused();\n\n"
+            )
+        );
+        assert!(out.contains("revert Unauthorized();"));
+    }
+}
@@ -0,0 +1,329 @@
+//! EIP-712 type strings and typehash validation.
+//!
+//! [`encode_type`] builds the canonical `encodeType` string of a struct from
+//! its typed `StructDefinition`: the struct itself followed by every struct
+//! it references, sorted by name. Hover shows it with its keccak256, and
+//! [`typehash_diagnostics`] warns when a `bytes32 constant ..._TYPEHASH =
+//! keccak256("...")` string no longer matches the struct it names.
+
+use crate::goto::CachedBuild;
+use crate::solc_ast::{
+    DeclNode, Expression, LiteralKind, Mutability, StructDefinition, TypeName, VariableDeclaration,
+};
+use crate::types::{NodeId, SourceLoc};
+use crate::utils::byte_offset_to_position;
+use std::collections::{BTreeMap, HashMap};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range, Url};
+
+/// `Diagnostic.source` for typehash diagnostics.
+pub const EIP712_SOURCE: &str = "eip712";
+
+/// The EIP-712 `encodeType` string of `def`, e.g.
+/// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+///
+/// `None` if a member has no EIP-712 encoding (mappings, function types).
+pub fn encode_type(
+    def: &StructDefinition,
+    decl_index: &HashMap<NodeId, DeclNode>,
+) -> Option<String> {
+    let mut structs = BTreeMap::new();
+    collect_struct(def, decl_index, &mut structs)?;
+    let primary = structs.remove(&def.name)?;
+    Some(structs.into_values().fold(primary, |acc, s| acc + &s))
+}
+
+/// `keccak256` of an encoded type, as `0x`-prefixed hex.
+pub fn type_hash(encoded: &str) -> String {
+    use tiny_keccak::{Hasher, Keccak};
+    let mut hasher = Keccak::v256();
+    hasher.update(encoded.as_bytes());
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    format!("0x{}", hex::encode(output))
+}
+
+/// Warn about `*TYPEHASH` constants in `file_uri` whose hashed string does
+/// not match the struct it names.
+///
+/// The struct is found by the name before `(` in the string. Ranges come
+/// from the AST, so `source` must be the text `build` was compiled from.
+pub fn typehash_diagnostics(build: &CachedBuild, file_uri: &Url, source: &str) -> Vec<Diagnostic> {
+    let Some(file_path) = file_uri.to_file_path().ok() else {
+        return vec![];
+    };
+
+    let mut diagnostics = Vec::new();
+    for (id, decl) in &build.decl_index {
+        let DeclNode::VariableDeclaration(var) = decl else {
+            continue;
+        };
+        if !build
+            .node_id_to_source_path
+            .get(id)
+            .is_some_and(|path| file_path.ends_with(path.as_str()))
+        {
+            continue;
+        }
+        let Some((declared, src)) = typehash_literal(var) else {
+            continue;
+        };
+        let name = declared.split('(').next().unwrap_or_default();
+        let mut expected: Vec<String> = build
+            .decl_index
+            .values()
+            .filter_map(|d| match d {
+                DeclNode::StructDefinition(def) if def.name == name => {
+                    encode_type(def, &build.decl_index)
+                }
+                _ => None,
+            })
+            .collect();
+        if expected.is_empty() || expected.iter().any(|e| e == declared) {
+            continue;
+        }
+        expected.sort();
+        let Some(loc) = SourceLoc::parse(src) else {
+            continue;
+        };
+        diagnostics.push(Diagnostic {
+            range: Range {
+                start: byte_offset_to_position(source, loc.offset),
+                end: byte_offset_to_position(source, loc.end()),
+            },
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: None,
+            code_description: None,
+            source: Some(EIP712_SOURCE.to_string()),
+            message: format!(
+                "{} does not match struct {name}; expected \"{}\"",
+                var.name, expected[0]
+            ),
+            related_information: None,
+            tags: None,
+            data: None,
+        });
+    }
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+/// The string and its `src` for `bytes32 constant X_TYPEHASH = keccak256("...")`.
+fn typehash_literal(var: &VariableDeclaration) -> Option<(&str, &str)> {
+    if var.mutability != Some(Mutability::Constant) || !var.name.ends_with("TYPEHASH") {
+        return None;
+    }
+    let Some(Expression::FunctionCall(call)) = &var.value else {
+        return None;
+    };
+    let Expression::Identifier(callee) = call.expression.as_ref() else {
+        return None;
+    };
+    match call.arguments.as_slice() {
+        [Expression::Literal(lit)]
+            if callee.name == "keccak256" && lit.kind == LiteralKind::String =>
+        {
+            Some((lit.value.as_deref()?, lit.src.as_str()))
+        }
+        _ => None,
+    }
+}
+
+/// Add `def` and every struct it references to `structs`, keyed by name.
+fn collect_struct<'a>(
+    def: &'a StructDefinition,
+    decl_index: &'a HashMap<NodeId, DeclNode>,
+    structs: &mut BTreeMap<String, String>,
+) -> Option<()> {
+    let mut referenced = Vec::new();
+    let mut fields = Vec::with_capacity(def.members.len());
+    for member in &def.members {
+        let ty = member_type(member.type_name.as_ref()?, decl_index, &mut referenced)?;
+        fields.push(format!("{ty} {}", member.name));
+    }
+    structs.insert(
+        def.name.clone(),
+        format!("{}({})", def.name, fields.join(",")),
+    );
+    for nested in referenced {
+        if !structs.contains_key(&nested.name) {
+            collect_struct(nested, decl_index, structs)?;
+        }
+    }
+    Some(())
+}
+
+/// The EIP-712 name of a member type. Structs are pushed to `referenced`.
+fn member_type<'a>(
+    type_name: &'a TypeName,
+    decl_index: &'a HashMap<NodeId, DeclNode>,
+    referenced: &mut Vec<&'a StructDefinition>,
+) -> Option<String> {
+    match type_name {
+        TypeName::ElementaryTypeName(elementary) => {
+            // `typeString` has canonical names (`uint` → `uint256`).
+            let ty = elementary
+                .type_descriptions
+                .type_string
+                .as_deref()
+                .unwrap_or(&elementary.name);
+            Some(ty.trim_end_matches(" payable").to_string())
+        }
+        TypeName::UserDefinedTypeName(user) => {
+            match decl_index.get(&NodeId(user.referenced_declaration?))? {
+                DeclNode::StructDefinition(def) => {
+                    referenced.push(def);
+                    Some(def.name.clone())
+                }
+                DeclNode::EnumDefinition(_) => Some("uint8".to_string()),
+                DeclNode::ContractDefinition(_) => Some("address".to_string()),
+                DeclNode::UserDefinedValueTypeDefinition(udvt) => {
+                    member_type(&udvt.underlying_type, decl_index, referenced)
+                }
+                _ => None,
+            }
+        }
+        TypeName::ArrayTypeName(array) => {
+            let base = member_type(&array.base_type, decl_index, referenced)?;
+            let length = match &array.length {
+                Some(length) => crate::const_eval::evaluate(length, decl_index)?.to_string(),
+                None => String::new(),
+            };
+            Some(format!("{base}[{length}]"))
+        }
+        TypeName::FunctionTypeName(_) | TypeName::Mapping(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    const MAIL_SOL: &str = r#"contract Mail {
+    struct Person { string name; address wallet; }
+    struct Letter { Person from; Person[] to; string contents; uint deadline; }
+    bytes32 constant LETTER_TYPEHASH = keccak256("Letter(Person from,Person[] to,string contents,uint256 deadline)Person(string name,address wallet)");
+    bytes32 constant PERSON_TYPEHASH = keccak256("Person(string name,address owner)");
+}
+"#;
+
+    fn elementary(id: i64, name: &str, type_string: &str) -> Value {
+        json!({"nodeType": "ElementaryTypeName", "id": id, "src": "0:0:0", "name": name,
+            "typeDescriptions": {"typeString": type_string}})
+    }
+
+    fn person(id: i64) -> Value {
+        json!({"nodeType": "UserDefinedTypeName", "id": id, "src": "0:0:0",
+            "referencedDeclaration": 3, "typeDescriptions": {"typeString": "struct Mail.Person"}})
+    }
+
+    fn member(id: i64, name: &str, type_name: Value) -> Value {
+        json!({"nodeType": "VariableDeclaration", "id": id, "src": "0:0:0", "name": name,
+            "typeName": type_name, "typeDescriptions": {}})
+    }
+
+    fn typehash(id: i64, name: &str, text: &str) -> Value {
+        let quoted = format!("\"{text}\"");
+        let start = MAIL_SOL.find(&quoted).unwrap();
+        json!({
+            "nodeType": "VariableDeclaration", "id": id, "src": "0:0:0", "name": name,
+            "mutability": "constant", "constant": true, "stateVariable": true,
+            "typeDescriptions": {"typeString": "bytes32"},
+            "value": {
+                "nodeType": "FunctionCall", "id": id + 1, "src": "0:0:0", "kind": "functionCall",
+                "expression": {"nodeType": "Identifier", "id": id + 2, "src": "0:0:0",
+                    "name": "keccak256"},
+                "arguments": [{"nodeType": "Literal", "id": id + 3, "kind": "string",
+                    "src": format!("{start}:{}:0", quoted.len()), "value": text}],
+                "typeDescriptions": {"typeString": "bytes32"}
+            }
+        })
+    }
+
+    /// A build of `MAIL_SOL` with solc's project-relative path, as in a
+    /// Foundry project rooted at `/tmp/eip712`.
+    fn mail_build() -> (Url, CachedBuild) {
+        let path = "src/Mail.sol";
+        let structs = |id: i64, name: &str, members: Value| {
+            json!({"nodeType": "StructDefinition", "id": id, "src": "0:0:0", "name": name,
+                "members": members})
+        };
+        let nodes = json!([{
+            "nodeType": "ContractDefinition", "id": 2, "src": "0:0:0",
+            "name": "Mail", "contractKind": "contract",
+            "nodes": [
+                structs(3, "Person", json!([
+                    member(4, "name", elementary(40, "string", "string")),
+                    member(5, "wallet", elementary(50, "address", "address")),
+                ])),
+                structs(6, "Letter", json!([
+                    member(7, "from", person(70)),
+                    member(8, "to", json!({"nodeType": "ArrayTypeName",
+                        "id": 80, "src": "0:0:0", "baseType": person(81),
+                        "typeDescriptions": {}})),
+                    member(9, "contents", elementary(90, "string", "string")),
+                    member(10, "deadline", elementary(100, "uint", "uint256")),
+                ])),
+                typehash(20, "LETTER_TYPEHASH", "Letter(Person from,Person[] to,string contents,uint256 deadline)Person(string name,address wallet)"),
+                typehash(30, "PERSON_TYPEHASH", "Person(string name,address owner)"),
+            ]
+        }]);
+        let ast = crate::test_support::output(path, MAIL_SOL.len(), nodes);
+        (
+            Url::from_file_path("/tmp/eip712/src/Mail.sol").unwrap(),
+            CachedBuild::new(ast, 0, None),
+        )
+    }
+
+    fn struct_def(build: &CachedBuild, id: i64) -> &StructDefinition {
+        match build.decl_index.get(&NodeId(id)) {
+            Some(DeclNode::StructDefinition(def)) => def,
+            other => panic!("expected a struct, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_encode_type_includes_referenced_structs() {
+        let (_, build) = mail_build();
+        assert_eq!(
+            encode_type(struct_def(&build, 3), &build.decl_index).as_deref(),
+            Some("Person(string name,address wallet)")
+        );
+        assert_eq!(
+            encode_type(struct_def(&build, 6), &build.decl_index).as_deref(),
+            Some(
+                "Letter(Person from,Person[] to,string contents,uint256 deadline)Person(string name,address wallet)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_type_hash() {
+        // The `Mail` example from the EIP-712 specification.
+        assert_eq!(
+            type_hash(
+                "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+            ),
+            "0xa0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+    }
+
+    #[test]
+    fn test_typehash_diagnostics_flag_mismatched_string() {
+        let (uri, build) = mail_build();
+        let diagnostics = typehash_diagnostics(&build, &uri, MAIL_SOL);
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.range.start.line, 4);
+        assert_eq!(diagnostic.range.start.character, 49);
+        assert_eq!(
+            diagnostic.message,
+            "PERSON_TYPEHASH does not match struct Person; expected \"Person(string name,address wallet)\""
+        );
+        assert_eq!(diagnostic.source.as_deref(), Some(EIP712_SOURCE));
+
+        // Only whole path components match.
+        let other = Url::from_file_path("/tmp/eip712/testsrc/Mail.sol").unwrap();
+        assert!(typehash_diagnostics(&build, &other, MAIL_SOL).is_empty());
+    }
+}
@@ -0,0 +1,872 @@
+//! Extract function / extract local variable refactorings.
+//!
+//! Both work on the live buffer with tree-sitter and resolve identifiers
+//! through the AST (`referencedDeclaration`), so they are only offered when
+//! the build is up to date with the buffer.
+//!
+//! - **Extract function** moves a run of whole statements into a new
+//!   `private` function placed after the enclosing one. Locals declared
+//!   before the selection and used inside it become parameters; locals
+//!   declared or written inside it and read afterwards become return values.
+//! - **Extract local variable** hoists the selected expression into a local
+//!   declared just before the enclosing statement, typed from the
+//!   expression's `typeString`.
+
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::{DocumentHighlightKind, Range, TextEdit};
+use tree_sitter::{Node, Tree};
+
+use crate::goto::NodeInfo;
+use crate::highlight::classify_highlight;
+use crate::types::{NodeId, SourceLoc};
+use crate::utils::{byte_offset_to_position, position_to_byte_offset};
+
+/// Builtins that can be used from a `pure` function.
+const PURE_BUILTINS: &[&str] = &[
+    "abi",
+    "addmod",
+    "assert",
+    "ecrecover",
+    "keccak256",
+    "mulmod",
+    "require",
+    "revert",
+    "ripemd160",
+    "sha256",
+];
+
+// ── Extract function ───────────────────────────────────────────────────────
+
+/// Edits moving the statements selected by `range` into a new function.
+///
+/// Returns `None` unless the selection covers whole statements of a single
+/// block inside a function, and the statements can be moved without
+/// changing control flow (no `return`, no `break`/`continue` out of the
+/// selection, no inline assembly).
+pub fn extract_function(
+    source: &str,
+    tree: &Tree,
+    file_nodes: &HashMap<NodeId, NodeInfo>,
+    range: Range,
+) -> Option<Vec<TextEdit>> {
+    let (start, end) = trimmed_selection(source, range)?;
+    let root = tree.root_node();
+    let statements = selected_statements(root, start, end)?;
+    let function = enclosing_function(statements[0])?;
+    if statements.iter().any(|s| !movable(*s, start)) {
+        return None;
+    }
+
+    let refs = references(file_nodes);
+    let declarations = declarations(file_nodes);
+    let fn_span = function.start_byte()..function.end_byte();
+    let is_local = |decl: &NodeInfo| {
+        decl.node_type.as_deref() == Some("VariableDeclaration")
+            && src_offset(decl).is_some_and(|o| fn_span.contains(&o))
+    };
+    let returns_span = function
+        .child_by_field_name("return_type")
+        .map(|n| n.start_byte()..n.end_byte());
+
+    // Walk the selection: parameters are outer locals in order of first
+    // use, candidates for return values are in order of first write.
+    let mut identifiers = Vec::new();
+    for statement in &statements {
+        collect_identifiers(*statement, &mut identifiers);
+    }
+    let mut params: Vec<NodeId> = Vec::new();
+    let mut written: Vec<NodeId> = Vec::new();
+    let mut pure = true;
+    for ident in identifiers {
+        let offset = ident.start_byte();
+        if let Some(&decl_id) = declarations.get(&offset) {
+            written.push(decl_id);
+            continue;
+        }
+        let Some(&decl_id) = refs.get(&offset) else {
+            // A member name is as pure as the object it is read from.
+            pure &= is_member_name(ident) || is_pure_builtin(&source[ident.byte_range()]);
+            continue;
+        };
+        let Some(decl) = file_nodes.get(&decl_id).filter(|d| is_local(d)) else {
+            pure &= decl_id.0 < 0 && is_pure_builtin(&source[ident.byte_range()]);
+            continue;
+        };
+        if src_offset(decl).is_some_and(|o| (start..end).contains(&o)) {
+            continue;
+        }
+        if !params.contains(&decl_id) {
+            params.push(decl_id);
+        }
+        if classify_highlight(ident, source) == DocumentHighlightKind::WRITE {
+            written.push(decl_id);
+        }
+    }
+
+    let used_after: HashSet<NodeId> = refs
+        .iter()
+        .filter(|(offset, _)| **offset >= end && fn_span.contains(*offset))
+        .map(|(_, id)| *id)
+        .collect();
+    let mut outputs: Vec<NodeId> = Vec::new();
+    for id in written {
+        let named_return = file_nodes
+            .get(&id)
+            .and_then(src_offset)
+            .zip(returns_span.as_ref())
+            .is_some_and(|(o, span)| span.contains(&o));
+        if (used_after.contains(&id) || named_return) && !outputs.contains(&id) {
+            outputs.push(id);
+        }
+    }
+
+    let decl = |id: &NodeId| -> Option<(String, String)> {
+        let info = file_nodes.get(id)?;
+        Some((declared_type(source, info)?, declared_name(source, info)?))
+    };
+    let params: Vec<(String, String)> = params.iter().map(decl).collect::<Option<_>>()?;
+    let outputs: Vec<(String, String, bool)> = outputs
+        .iter()
+        .map(|id| {
+            let (ty, name) = decl(id)?;
+            let inner = file_nodes
+                .get(id)
+                .and_then(src_offset)
+                .is_some_and(|o| (start..end).contains(&o));
+            Some((ty, name, inner))
+        })
+        .collect::<Option<_>>()?;
+
+    let name = unique_name(source, root, "extracted");
+    let args = params
+        .iter()
+        .map(|(_, n)| n.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let call_site = call_site(&name, &args, &outputs, &line_indent(source, start))?;
+
+    let fn_indent = line_indent(source, function.start_byte());
+    let stmt_indent = line_indent(source, start);
+    let unit = stmt_indent
+        .strip_prefix(fn_indent.as_str())
+        .filter(|u| !u.is_empty())
+        .unwrap_or("    ");
+    let body_indent = format!("{fn_indent}{unit}");
+    let mut body: Vec<String> = Vec::new();
+    for (i, line) in source[start..end].lines().enumerate() {
+        let line = if i == 0 {
+            line
+        } else {
+            line.strip_prefix(stmt_indent.as_str())
+                .unwrap_or_else(|| line.trim_start())
+        };
+        if line.is_empty() {
+            body.push(String::new());
+        } else {
+            body.push(format!("{body_indent}{line}"));
+        }
+    }
+    match outputs.as_slice() {
+        [] => {}
+        [(_, n, _)] => body.push(format!("{body_indent}return {n};")),
+        many => {
+            let names: Vec<&str> = many.iter().map(|(_, n, _)| n.as_str()).collect();
+            body.push(format!("{body_indent}return ({});", names.join(", ")));
+        }
+    }
+
+    let params_text = params
+        .iter()
+        .map(|(ty, n)| format!("{ty} {n}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut header = format!("function {name}({params_text})");
+    // Free functions cannot have a visibility.
+    if function.parent().is_some_and(|p| p.kind() != "source_file") {
+        header.push_str(" private");
+    }
+    let mutability = if pure {
+        Some("pure")
+    } else {
+        state_mutability(source, function).filter(|m| *m == "view" || *m == "pure")
+    };
+    if let Some(mutability) = mutability {
+        header.push(' ');
+        header.push_str(mutability);
+    }
+    if !outputs.is_empty() {
+        let types: Vec<&str> = outputs.iter().map(|(ty, _, _)| ty.as_str()).collect();
+        header.push_str(&format!(" returns ({})", types.join(", ")));
+    }
+    let new_function = format!(
+        "\n\n{fn_indent}{header} {{\n{}\n{fn_indent}}}",
+        body.join("\n")
+    );
+
+    let fn_end = byte_offset_to_position(source, function.end_byte());
+    Some(vec![
+        TextEdit {
+            range: Range {
+                start: byte_offset_to_position(source, start),
+                end: byte_offset_to_position(source, end),
+            },
+            new_text: call_site,
+        },
+        TextEdit {
+            range: Range {
+                start: fn_end,
+                end: fn_end,
+            },
+            new_text: new_function,
+        },
+    ])
+}
+
+/// The statement(s) replacing the selection: a call, with its results
+/// declared or assigned.
+fn call_site(
+    name: &str,
+    args: &str,
+    outputs: &[(String, String, bool)],
+    indent: &str,
+) -> Option<String> {
+    let call = format!("{name}({args})");
+    Some(match outputs {
+        [] => format!("{call};"),
+        [(ty, n, true)] => format!("{ty} {n} = {call};"),
+        [(_, n, false)] => format!("{n} = {call};"),
+        many if many.iter().all(|(_, _, inner)| *inner) => {
+            let decls: Vec<String> = many.iter().map(|(ty, n, _)| format!("{ty} {n}")).collect();
+            format!("({}) = {call};", decls.join(", "))
+        }
+        many => {
+            // A tuple cannot both declare and assign: declare the new locals
+            // first. Storage pointers cannot be declared uninitialized.
+            let mut lines = Vec::new();
+            for (ty, n, inner) in many {
+                if *inner {
+                    if ty.ends_with(" storage") {
+                        return None;
+                    }
+                    lines.push(format!("{ty} {n};"));
+                }
+            }
+            let names: Vec<&str> = many.iter().map(|(_, n, _)| n.as_str()).collect();
+            lines.push(format!("({}) = {call};", names.join(", ")));
+            lines.join(&format!("\n{indent}"))
+        }
+    })
+}
+
+/// Whole statements of a single block covering exactly `start..end`.
+fn selected_statements(root: Node<'_>, start: usize, end: usize) -> Option<Vec<Node<'_>>> {
+    let mut node = root.descendant_for_byte_range(start, end)?;
+    loop {
+        if matches!(node.kind(), "function_body" | "block_statement") {
+            let mut cursor = node.walk();
+            let statements: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter(|c| c.kind() == "statement")
+                .filter(|c| c.end_byte() > start && c.start_byte() < end)
+                .collect();
+            if let (Some(first), Some(last)) = (statements.first(), statements.last())
+                && first.start_byte() == start
+                && last.end_byte() == end
+            {
+                return Some(statements);
+            }
+        }
+        if matches!(
+            node.kind(),
+            "function_definition" | "constructor_definition"
+        ) {
+            return None;
+        }
+        node = node.parent()?;
+    }
+}
+
+/// The function or constructor whose body contains `node`. Modifiers are
+/// excluded: their `_` placeholder cannot be moved.
+fn enclosing_function(node: Node<'_>) -> Option<Node<'_>> {
+    let mut current = node.parent()?;
+    loop {
+        match current.kind() {
+            "function_definition" | "constructor_definition" | "fallback_receive_definition" => {
+                return Some(current);
+            }
+            "modifier_definition" | "contract_body" | "source_file" => return None,
+            _ => current = current.parent()?,
+        }
+    }
+}
+
+/// Whether `node` can move into another function unchanged.
+fn movable(node: Node<'_>, selection_start: usize) -> bool {
+    match node.kind() {
+        "return_statement" | "assembly_statement" => return false,
+        "break_statement" | "continue_statement" => {
+            let mut current = node.parent();
+            while let Some(parent) = current {
+                if matches!(
+                    parent.kind(),
+                    "for_statement" | "while_statement" | "do_while_statement"
+                ) {
+                    return parent.start_byte() >= selection_start;
+                }
+                current = parent.parent();
+            }
+            return false;
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .all(|child| movable(child, selection_start))
+}
+
+// ── Extract local variable ─────────────────────────────────────────────────
+
+/// Edits hoisting the expression selected by `range` into a local variable
+/// declared before the statement that contains it.
+pub fn extract_variable(
+    source: &str,
+    tree: &Tree,
+    file_nodes: &HashMap<NodeId, NodeInfo>,
+    range: Range,
+) -> Option<Vec<TextEdit>> {
+    let (start, end) = trimmed_selection(source, range)?;
+    let root = tree.root_node();
+
+    let mut node = root.descendant_for_byte_range(start, end)?;
+    let mut expression = None;
+    while node.start_byte() == start && node.end_byte() == end {
+        if node.kind() == "expression" {
+            expression = Some(node);
+        }
+        node = node.parent()?;
+    }
+    let expression = expression?;
+    if !extractable(expression) {
+        return None;
+    }
+
+    // The statement to hoist above. Loop headers are evaluated more than
+    // once, so an expression there cannot be hoisted.
+    let mut statement = expression.parent()?;
+    while !(statement.kind() == "statement"
+        && statement
+            .parent()
+            .is_some_and(|p| matches!(p.kind(), "function_body" | "block_statement")))
+    {
+        if matches!(
+            statement.kind(),
+            "function_definition" | "modifier_definition" | "contract_body"
+        ) {
+            return None;
+        }
+        statement = statement.parent()?;
+    }
+    if statement.named_child(0).is_some_and(|s| {
+        matches!(
+            s.kind(),
+            "for_statement" | "while_statement" | "do_while_statement"
+        )
+    }) {
+        return None;
+    }
+    if !hoistable(expression, statement) {
+        return None;
+    }
+
+    let ty = file_nodes
+        .values()
+        .filter(|info| {
+            SourceLoc::parse(info.src.as_str())
+                .is_some_and(|loc| loc.offset == start && loc.end() == end)
+        })
+        .find_map(|info| info.type_string.as_deref())
+        .and_then(local_type)?;
+    let name = unique_name(source, root, &suggested_name(source, expression));
+
+    let indent = line_indent(source, statement.start_byte());
+    let new_text = format!(
+        "{ty} {name} = {};\n{indent}{}{name}",
+        &source[start..end],
+        &source[statement.start_byte()..start],
+    );
+    Some(vec![TextEdit {
+        range: Range {
+            start: byte_offset_to_position(source, statement.start_byte()),
+            end: byte_offset_to_position(source, end),
+        },
+        new_text,
+    }])
+}
+
+/// Whether the value of `expression` can be read into a variable: not an
+/// assignment target and not the callee of a call.
+fn extractable(expression: Node<'_>) -> bool {
+    let Some(parent) = expression.parent() else {
+        return false;
+    };
+    let is_field = |field: &str| {
+        parent
+            .child_by_field_name(field)
+            .is_some_and(|n| n.id() == expression.id())
+    };
+    match parent.kind() {
+        "assignment_expression" | "augmented_assignment_expression" => !is_field("left"),
+        "update_expression" | "delete_expression" => false,
+        "call_expression" => !is_field("function"),
+        _ => true,
+    }
+}
+
+/// Whether hoisting `expression` above `statement` keeps when and in what
+/// order it runs: it must not sit in a branch that may be skipped (the right
+/// operand of `&&`/`||`, a ternary branch, an `if` body or `else if`
+/// condition), and nothing with side effects may run before it.
+fn hoistable(expression: Node<'_>, statement: Node<'_>) -> bool {
+    let mut child = expression;
+    while child.id() != statement.id() {
+        let Some(parent) = child.parent() else {
+            return false;
+        };
+        let is_field = |field: &str| {
+            parent
+                .child_by_field_name(field)
+                .is_some_and(|n| n.id() == child.id())
+        };
+        let conditional = match parent.kind() {
+            "binary_expression" => {
+                is_field("right")
+                    && parent
+                        .child_by_field_name("operator")
+                        .is_some_and(|op| matches!(op.kind(), "&&" | "||"))
+            }
+            "ternary_expression" => parent.named_child(0).is_none_or(|c| c.id() != child.id()),
+            "if_statement" => !is_field("condition"),
+            _ => false,
+        };
+        if conditional {
+            return false;
+        }
+        child = parent;
+    }
+    !has_effects_before(statement, expression.start_byte())
+}
+
+/// Whether a call, assignment or update inside `node` completes before
+/// `byte`.
+fn has_effects_before(node: Node<'_>, byte: usize) -> bool {
+    if node.start_byte() >= byte {
+        return false;
+    }
+    if node.end_byte() <= byte
+        && matches!(
+            node.kind(),
+            "call_expression"
+                | "assignment_expression"
+                | "augmented_assignment_expression"
+                | "update_expression"
+                | "delete_expression"
+        )
+    {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|child| has_effects_before(child, byte))
+}
+
+/// Local variable type for a solc `typeString`, e.g.
+/// `struct Pool.State storage pointer` → `Pool.State storage`. `None` for
+/// types a variable cannot have (tuples, rationals, function types).
+pub fn local_type(type_string: &str) -> Option<String> {
+    if type_string.starts_with("literal_string") {
+        return Some("string memory".to_string());
+    }
+    if let Some(value) = type_string.strip_prefix("int_const ") {
+        let ty = if value.starts_with('-') {
+            "int256"
+        } else {
+            "uint256"
+        };
+        return Some(ty.to_string());
+    }
+    if [
+        "rational_const",
+        "tuple(",
+        "type(",
+        "function ",
+        "function(",
+    ]
+    .iter()
+    .any(|prefix| type_string.starts_with(prefix))
+    {
+        return None;
+    }
+    let mut t = type_string;
+    for prefix in ["struct ", "enum ", "contract ", "interface ", "library "] {
+        if let Some(rest) = t.strip_prefix(prefix) {
+            t = rest;
+        }
+    }
+    for (suffix, location) in [
+        (" storage ref", " storage"),
+        (" storage pointer", " storage"),
+        (" memory", " memory"),
+        (" calldata", " calldata"),
+    ] {
+        if let Some(rest) = t.strip_suffix(suffix) {
+            return Some(format!("{rest}{location}"));
+        }
+    }
+    Some(t.to_string())
+}
+
+/// Variable name suggested by the expression: the member it reads, or the
+/// function it calls without a `get` prefix.
+fn suggested_name(source: &str, expression: Node<'_>) -> String {
+    let Some(inner) = expression.named_child(0) else {
+        return "value".to_string();
+    };
+    let name = match inner.kind() {
+        "member_expression" => inner
+            .child_by_field_name("property")
+            .map(|p| source[p.byte_range()].to_string()),
+        "call_expression" => inner.child_by_field_name("function").and_then(|f| {
+            let text = &source[f.byte_range()];
+            let last = text.rsplit('.').next()?.trim();
+            let last = last.strip_prefix("get").unwrap_or(last);
+            let mut chars = last.chars();
+            let first = chars.next()?;
+            Some(first.to_lowercase().chain(chars).collect())
+        }),
+        _ => None,
+    };
+    name.filter(|n: &String| n.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        .unwrap_or_else(|| "value".to_string())
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+/// Byte range of `range` with surrounding whitespace trimmed, if non-empty.
+fn trimmed_selection(source: &str, range: Range) -> Option<(usize, usize)> {
+    let start = position_to_byte_offset(source, range.start);
+    let end = position_to_byte_offset(source, range.end);
+    let text = source.get(start..end)?;
+    let trimmed_start = start + (text.len() - text.trim_start().len());
+    let trimmed_end = end - (text.len() - text.trim_end().len());
+    (trimmed_start < trimmed_end).then_some((trimmed_start, trimmed_end))
+}
+
+/// Offset of each `Identifier` / `IdentifierPath` → the declaration it
+/// references.
+fn references(file_nodes: &HashMap<NodeId, NodeInfo>) -> HashMap<usize, NodeId> {
+    file_nodes
+        .values()
+        .filter(|info| {
+            matches!(
+                info.node_type.as_deref(),
+                Some("Identifier" | "IdentifierPath")
+            )
+        })
+        .filter_map(|info| Some((src_offset(info)?, info.referenced_declaration?)))
+        .collect()
+}
+
+/// Offset of each variable declaration's name → the declaration.
+fn declarations(file_nodes: &HashMap<NodeId, NodeInfo>) -> HashMap<usize, NodeId> {
+    file_nodes
+        .iter()
+        .filter(|(_, info)| info.node_type.as_deref() == Some("VariableDeclaration"))
+        .filter_map(|(id, info)| {
+            let name = SourceLoc::parse(info.name_location.as_deref()?)?;
+            Some((name.offset, *id))
+        })
+        .collect()
+}
+
+fn collect_identifiers<'a>(node: Node<'a>, out: &mut Vec<Node<'a>>) {
+    if node.kind() == "identifier" {
+        out.push(node);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_identifiers(child, out);
+    }
+}
+
+fn is_member_name(ident: Node<'_>) -> bool {
+    ident.parent().is_some_and(|p| {
+        p.kind() == "member_expression"
+            && p.child_by_field_name("property")
+                .is_some_and(|n| n.id() == ident.id())
+    })
+}
+
+fn is_pure_builtin(name: &str) -> bool {
+    PURE_BUILTINS.contains(&name)
+}
+
+fn src_offset(info: &NodeInfo) -> Option<usize> {
+    SourceLoc::parse(info.src.as_str()).map(|loc| loc.offset)
+}
+
+/// Type of a variable declaration as written, e.g. `uint256[] memory`.
+fn declared_type(source: &str, info: &NodeInfo) -> Option<String> {
+    let start = src_offset(info)?;
+    let name = SourceLoc::parse(info.name_location.as_deref()?)?;
+    let ty = source.get(start..name.offset)?.trim();
+    (!ty.is_empty()).then(|| ty.to_string())
+}
+
+fn declared_name(source: &str, info: &NodeInfo) -> Option<String> {
+    let name = SourceLoc::parse(info.name_location.as_deref()?)?;
+    let name = source.get(name.offset..name.end())?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn state_mutability<'a>(source: &'a str, function: Node<'_>) -> Option<&'a str> {
+    let mut cursor = function.walk();
+    function
+        .children(&mut cursor)
+        .find(|c| c.kind() == "state_mutability")
+        .map(|c| source[c.byte_range()].trim())
+}
+
+/// `base`, or `base` followed by the first number that makes it unused in
+/// the file.
+fn unique_name(source: &str, root: Node<'_>, base: &str) -> String {
+    let mut identifiers = Vec::new();
+    collect_identifiers(root, &mut identifiers);
+    let taken: HashSet<&str> = identifiers
+        .iter()
+        .map(|n| &source[n.byte_range()])
+        .collect();
+    if !taken.contains(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|i| format!("{base}{i}"))
+        .find(|name| !taken.contains(name.as_str()))
+        .unwrap_or_default()
+}
+
+/// Leading whitespace of the line containing `byte`.
+pub(crate) fn line_indent(source: &str, byte: usize) -> String {
+    let line_start = source[..byte].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    const SOURCE: &str = "contract C {
+    function f(uint a) public pure returns (uint) {
+        uint b = a + 1;
+        b *= 2;
+        uint c = b + a * 2;
+        return c;
+    }
+}
+";
+
+    fn apply(source: &str, edits: &[TextEdit]) -> String {
+        crate::file_operations::apply_text_edits(source, edits)
+    }
+
+    /// Byte offset of the `nth` occurrence of `needle`.
+    fn at(needle: &str, nth: usize) -> usize {
+        SOURCE.match_indices(needle).nth(nth).unwrap().0
+    }
+
+    fn node(
+        offset: usize,
+        len: usize,
+        node_type: &str,
+        referenced: Option<i64>,
+        name_at: Option<(usize, usize)>,
+        type_string: Option<&str>,
+    ) -> NodeInfo {
+        serde_json::from_value(serde_json::json!({
+            "src": format!("{offset}:{len}:0"),
+            "node_type": node_type,
+            "referenced_declaration": referenced,
+            "name_location": name_at.map(|(o, l)| format!("{o}:{l}:0")),
+            "type_string": type_string,
+        }))
+        .unwrap()
+    }
+
+    fn nodes() -> HashMap<NodeId, NodeInfo> {
+        let decl = |needle: &str, name_len: usize| {
+            let offset = at(needle, 0);
+            let name = offset + needle.len() - name_len;
+            node(
+                offset,
+                needle.len(),
+                "VariableDeclaration",
+                None,
+                Some((name, name_len)),
+                Some("uint256"),
+            )
+        };
+        let ident = |needle: &str, nth: usize, decl: i64| {
+            node(at(needle, nth), 1, "Identifier", Some(decl), None, None)
+        };
+        HashMap::from([
+            (NodeId(1), decl("uint a", 1)),
+            (NodeId(2), decl("uint b", 1)),
+            (NodeId(3), decl("uint c", 1)),
+            // `a` in `a + 1`, `b` in `b *= 2`, `b + a * 2`, `return c`.
+            (NodeId(10), ident("a + 1", 0, 1)),
+            (NodeId(11), ident("b *= 2", 0, 2)),
+            (NodeId(12), ident("b + a", 0, 2)),
+            (NodeId(13), ident("a * 2", 0, 1)),
+            (NodeId(14), ident("c;", 0, 3)),
+            (
+                NodeId(20),
+                node(
+                    at("a * 2", 0),
+                    5,
+                    "BinaryOperation",
+                    None,
+                    None,
+                    Some("uint256"),
+                ),
+            ),
+        ])
+    }
+
+    fn range(start: usize, end: usize) -> Range {
+        Range::new(
+            byte_offset_to_position(SOURCE, start),
+            byte_offset_to_position(SOURCE, end),
+        )
+    }
+
+    #[test]
+    fn test_extract_function_parameters_and_return_value() {
+        let tree = crate::syntax::parse(SOURCE).unwrap();
+        // Whole lines, including the leading indentation.
+        let selection = Range::new(Position::new(2, 0), Position::new(4, 0));
+        let edits = extract_function(SOURCE, &tree, &nodes(), selection).unwrap();
+        assert_eq!(
+            apply(SOURCE, &edits),
+            "contract C {
+    function f(uint a) public pure returns (uint) {
+        uint b = extracted(a);
+        uint c = b + a * 2;
+        return c;
+    }
+
+    function extracted(uint a) private pure returns (uint) {
+        uint b = a + 1;
+        b *= 2;
+        return b;
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_extract_function_refuses_partial_statements_and_returns() {
+        let tree = crate::syntax::parse(SOURCE).unwrap();
+        let nodes = nodes();
+        // Part of a statement.
+        let start = at("a + 1", 0);
+        assert!(extract_function(SOURCE, &tree, &nodes, range(start, start + 5)).is_none());
+        // Contains a `return`.
+        let start = at("uint c", 0);
+        let end = at("return c;", 0) + "return c;".len();
+        assert!(extract_function(SOURCE, &tree, &nodes, range(start, end)).is_none());
+    }
+
+    #[test]
+    fn test_extract_variable() {
+        let tree = crate::syntax::parse(SOURCE).unwrap();
+        let start = at("a * 2", 0);
+        let edits = extract_variable(SOURCE, &tree, &nodes(), range(start, start + 5)).unwrap();
+        assert_eq!(
+            apply(SOURCE, &edits),
+            SOURCE.replace(
+                "        uint c = b + a * 2;",
+                "        uint256 value = a * 2;\n        uint c = b + value;"
+            )
+        );
+
+        // An assignment target cannot be extracted.
+        let start = at("b *= 2", 0);
+        assert!(extract_variable(SOURCE, &tree, &nodes(), range(start, start + 1)).is_none());
+    }
+
+    #[test]
+    fn test_extract_variable_keeps_evaluation_order() {
+        let source = "contract C {
+    function f(uint a) public returns (uint) {
+        bool ok = a > 0 && g(a) > 1;
+        uint b = ok ? g(a) : 0;
+        if (a == 0) {} else if (g(a) == 2) {}
+        uint c = h(k(), g(a));
+        uint d = a + g(a);
+        return b + c + d;
+    }
+}
+";
+        let tree = crate::syntax::parse(source).unwrap();
+        let extract = |nth: usize| {
+            let start = source.match_indices("g(a)").nth(nth).unwrap().0;
+            let end = start + "g(a)".len();
+            let call = node(start, 4, "FunctionCall", None, None, Some("uint256"));
+            let range = Range::new(
+                byte_offset_to_position(source, start),
+                byte_offset_to_position(source, end),
+            );
+            extract_variable(source, &tree, &HashMap::from([(NodeId(1), call)]), range)
+        };
+        // Right operand of `&&`, ternary branch, `else if` condition.
+        assert!(extract(0).is_none());
+        assert!(extract(1).is_none());
+        assert!(extract(2).is_none());
+        // `k()` runs first.
+        assert!(extract(3).is_none());
+        let edits = extract(4).unwrap();
+        let out = apply(source, &edits);
+        assert!(
+            out.contains("uint256 g2 = g(a);\n        uint d = a + g2;"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_local_type() {
+        assert_eq!(local_type("uint256").as_deref(), Some("uint256"));
+        assert_eq!(
+            local_type("struct Pool.State storage pointer").as_deref(),
+            Some("Pool.State storage")
+        );
+        assert_eq!(
+            local_type("uint256[] memory").as_deref(),
+            Some("uint256[] memory")
+        );
+        assert_eq!(
+            local_type("literal_string \"hi\"").as_deref(),
+            Some("string memory")
+        );
+        assert_eq!(local_type("int_const -1").as_deref(), Some("int256"));
+        assert_eq!(local_type("contract IERC20").as_deref(), Some("IERC20"));
+        assert!(local_type("tuple(uint256,bool)").is_none());
+        assert!(local_type("rational_const 1 / 2").is_none());
+    }
+}
@@ -2,7 +2,10 @@ use crate::links;
 use crate::utils;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tower_lsp::lsp_types::{Position, Range, TextEdit, Url};
+use tower_lsp::lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    Position, Range, RenameFile, ResourceOp, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -541,6 +544,54 @@ pub fn rename_imports_single(
     .edits
 }
 
+/// Combine symbol-rename `changes`, the `import_edits` for moving `old_uri`
+/// to `new_uri`, and the `RenameFile` operation itself into one
+/// `documentChanges` edit, so the client applies them in a single undo step.
+///
+/// Text edits address files by their current URI, so the rename goes last.
+pub fn rename_file_edit(
+    changes: HashMap<Url, Vec<TextEdit>>,
+    import_edits: HashMap<Url, Vec<TextEdit>>,
+    old_uri: Url,
+    new_uri: Url,
+) -> WorkspaceEdit {
+    let mut merged = changes;
+    for (uri, edits) in import_edits {
+        let existing = merged.entry(uri).or_default();
+        for edit in edits {
+            if !existing.contains(&edit) {
+                existing.push(edit);
+            }
+        }
+    }
+    let mut merged: Vec<_> = merged.into_iter().collect();
+    merged.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+
+    let mut operations: Vec<DocumentChangeOperation> = merged
+        .into_iter()
+        .map(|(uri, edits)| {
+            DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits.into_iter().map(OneOf::Left).collect(),
+            })
+        })
+        .collect();
+    operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+        RenameFile {
+            old_uri,
+            new_uri,
+            options: None,
+            annotation_id: None,
+        },
+    )));
+
+    WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Cache patching
 // ---------------------------------------------------------------------------
@@ -1,15 +1,19 @@
 use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
-use tree_sitter::{Node, Parser};
+use tree_sitter::{Node, Tree};
 
 /// Extract folding ranges from Solidity source using tree-sitter.
 ///
 /// Returns ranges for contracts, functions, structs, enums, block statements,
 /// multi-line comments, consecutive single-line comments, and import groups.
 pub fn folding_ranges(source: &str) -> Vec<FoldingRange> {
-    let tree = match parse(source) {
-        Some(t) => t,
-        None => return vec![],
-    };
+    match crate::syntax::parse(source) {
+        Some(tree) => folding_ranges_with_tree(&tree, source),
+        None => vec![],
+    }
+}
+
+/// Folding ranges from an already-parsed `tree` of `source`.
+pub fn folding_ranges_with_tree(tree: &Tree, source: &str) -> Vec<FoldingRange> {
     let mut ranges = Vec::new();
     collect_folding_ranges(tree.root_node(), source, &mut ranges);
     collect_comment_folds(tree.root_node(), source, &mut ranges);
@@ -221,14 +225,6 @@ fn collect_import_folds(root: Node, out: &mut Vec<FoldingRange>) {
 
 // ── Helpers ────────────────────────────────────────────────────────────────
 
-fn parse(source: &str) -> Option<tree_sitter::Tree> {
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_solidity::LANGUAGE.into())
-        .expect("failed to load Solidity grammar");
-    parser.parse(source, None)
-}
-
 /// Push a fold for a brace-delimited node (e.g. `{ ... }`).
 /// Only emits a fold when the node spans multiple lines.
 fn push_brace_fold(node: Node, kind: Option<FoldingRangeKind>, out: &mut Vec<FoldingRange>) {
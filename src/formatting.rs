@@ -0,0 +1,614 @@
+//! Formatting edits.
+//!
+//! `forge fmt` only formats whole files. Its output is diffed against the
+//! buffer line by line, and each changed run of lines is diffed again
+//! character by character, so the client receives a minimal set of edits
+//! instead of a whole-document replacement (which would drop the cursor,
+//! folds and marks). Range formatting keeps only the hunks that touch the
+//! declarations under the selection, so untouched code is never
+//! reformatted.
+//!
+//! On-type formatting is computed locally from the buffer:
+//!
+//! - Enter after a `///` line, or inside a `/** ... */` block, continues the
+//!   comment prefix on the new line.
+//! - `}` re-indents the block it closes: the brace lines up with the line of
+//!   its `{`, every statement inside is one level deeper than its enclosing
+//!   braces, and continuation lines keep their offset from the line they
+//!   continue.
+
+use std::ops::Range as Span;
+
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+use tree_sitter::{Node, Tree};
+
+use crate::utils::{byte_offset_to_position, position_to_byte_offset};
+
+/// Upper bound on the Myers trace size (in `V` cells). Past it, the
+/// remaining difference is reported as a single hunk.
+const MAX_TRACE_CELLS: usize = 1 << 22;
+
+/// A changed region: lines `old` of the original are replaced by lines `new`
+/// of the formatted text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old: Span<usize>,
+    pub new: Span<usize>,
+}
+
+/// Hunks turning `a` into `b`, in order.
+pub fn diff_hunks<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Hunk> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+    let (a_mid, b_mid) = (&a[prefix..a_end], &b[prefix..b_end]);
+    if a_mid.is_empty() && b_mid.is_empty() {
+        return Vec::new();
+    }
+    let Some(matches) = myers_matches(a_mid, b_mid) else {
+        return vec![Hunk {
+            old: prefix..a_end,
+            new: prefix..b_end,
+        }];
+    };
+
+    let mut hunks = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    for (mx, my) in matches
+        .into_iter()
+        .chain(std::iter::once((a_mid.len(), b_mid.len())))
+    {
+        if mx > x || my > y {
+            hunks.push(Hunk {
+                old: prefix + x..prefix + mx,
+                new: prefix + y..prefix + my,
+            });
+        }
+        (x, y) = (mx + 1, my + 1);
+    }
+    hunks
+}
+
+/// Index pairs of equal elements on a shortest edit script from `a` to `b`,
+/// or `None` if the trace would grow past [`MAX_TRACE_CELLS`].
+fn myers_matches<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<(usize, usize)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max as isize {
+        if (trace.len() + 1) * v.len() > MAX_TRACE_CELLS {
+            return None;
+        }
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m, offset));
+            }
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize, offset: isize) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k =
+            if k == -d || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize]) {
+                k + 1
+            } else {
+                k - 1
+            };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        if d > 0 {
+            (x, y) = (prev_x, prev_y);
+        }
+    }
+    matches.reverse();
+    matches
+}
+
+// ── Document formatting ──────────────────────────────────────────────────────
+
+/// Minimal edits turning `original` into `formatted`.
+pub fn minimal_edits(original: &str, formatted: &str) -> Vec<TextEdit> {
+    let old_lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = formatted.split_inclusive('\n').collect();
+    diff_hunks(&old_lines, &new_lines)
+        .into_iter()
+        .flat_map(|hunk| refine(&old_lines, &new_lines, hunk))
+        .collect()
+}
+
+/// Character-level edits for a line hunk.
+fn refine(old_lines: &[&str], new_lines: &[&str], hunk: Hunk) -> Vec<TextEdit> {
+    let old_text = old_lines[hunk.old.clone()].concat();
+    let new_text = new_lines[hunk.new].concat();
+    let old_chars: Vec<char> = old_text.chars().collect();
+    let new_chars: Vec<char> = new_text.chars().collect();
+    let old_offsets: Vec<usize> = old_text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(old_text.len()))
+        .collect();
+    let new_offsets: Vec<usize> = new_text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(new_text.len()))
+        .collect();
+
+    // Positions are computed within the hunk, which starts at column 0.
+    let position = |offset: usize| {
+        let pos = byte_offset_to_position(&old_text, offset);
+        Position::new(hunk.old.start as u32 + pos.line, pos.character)
+    };
+    diff_hunks(&old_chars, &new_chars)
+        .into_iter()
+        .map(|chars| TextEdit {
+            range: Range {
+                start: position(old_offsets[chars.old.start]),
+                end: position(old_offsets[chars.old.end]),
+            },
+            new_text: new_text[new_offsets[chars.new.start]..new_offsets[chars.new.end]]
+                .to_string(),
+        })
+        .collect()
+}
+
+// ── Range formatting ─────────────────────────────────────────────────────────
+
+/// Edits applying only the parts of `formatted` that touch `range`.
+///
+/// `range` is first widened to the declarations it touches (contract
+/// members or top-level items), so a selection inside a function formats
+/// that whole function and nothing around it.
+pub fn range_edits(original: &str, formatted: &str, range: Range) -> Vec<TextEdit> {
+    let old_lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = formatted.split_inclusive('\n').collect();
+    let (first, last) = selected_lines(original, range);
+
+    diff_hunks(&old_lines, &new_lines)
+        .into_iter()
+        .flat_map(split_lines)
+        .filter(|hunk| {
+            if hunk.old.is_empty() {
+                first < hunk.old.start && hunk.old.start <= last
+            } else {
+                hunk.old.start <= last && hunk.old.end > first
+            }
+        })
+        .flat_map(|hunk| refine(&old_lines, &new_lines, hunk))
+        .collect()
+}
+
+/// Split a hunk that rewrites lines one for one into one hunk per line, so
+/// a selection can take some of them without the others.
+fn split_lines(hunk: Hunk) -> Vec<Hunk> {
+    if hunk.old.len() != hunk.new.len() {
+        return vec![hunk];
+    }
+    hunk.old
+        .zip(hunk.new)
+        .map(|(old, new)| Hunk {
+            old: old..old + 1,
+            new: new..new + 1,
+        })
+        .collect()
+}
+
+/// First and last line of `range`, widened to whole declarations.
+fn selected_lines(source: &str, range: Range) -> (usize, usize) {
+    let mut first = range.start.line as usize;
+    let mut last = range.end.line as usize;
+    let start = position_to_byte_offset(source, range.start);
+    let mut end = position_to_byte_offset(source, range.end);
+    // A selection of whole lines ends at column 0 of the next line.
+    if range.end.character == 0 && range.end.line > range.start.line {
+        last -= 1;
+        end = end.saturating_sub(1);
+    }
+
+    if let Some(tree) = crate::syntax::parse(source) {
+        let root = tree.root_node();
+        if let Some(decl) = declaration_at(root, start) {
+            first = first.min(decl.start_position().row);
+        }
+        if let Some(decl) = declaration_at(root, end.max(start)) {
+            last = last.max(decl.end_position().row);
+        }
+    }
+    (first, last)
+}
+
+/// The top-level item or contract member containing `byte`.
+fn declaration_at(root: Node<'_>, byte: usize) -> Option<Node<'_>> {
+    let mut node = root.descendant_for_byte_range(byte, byte)?;
+    if matches!(node.kind(), "source_file" | "contract_body") {
+        return None;
+    }
+    loop {
+        let parent = node.parent()?;
+        if matches!(parent.kind(), "source_file" | "contract_body") {
+            return Some(node);
+        }
+        node = parent;
+    }
+}
+
+// ── On-type formatting ───────────────────────────────────────────────────────
+
+/// Edits for `ch` typed just before `position`.
+pub fn on_type_edits(
+    source: &str,
+    tree: Option<&Tree>,
+    position: Position,
+    ch: &str,
+) -> Vec<TextEdit> {
+    match ch {
+        "\n" => continue_comment(source, position).into_iter().collect(),
+        "}" => tree
+            .map(|tree| reindent_block(source, tree, position))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Continue a NatSpec comment onto the line the cursor just moved to.
+fn continue_comment(source: &str, position: Position) -> Option<TextEdit> {
+    let line = position.line as usize;
+    let lines: Vec<&str> = source.split('\n').collect();
+    let previous = lines.get(line.checked_sub(1)?)?.trim_end_matches('\r');
+    let current = lines.get(line)?;
+
+    let indent = &previous[..previous.len() - previous.trim_start().len()];
+    let trimmed = previous.trim_start();
+    let prefix = if trimmed.starts_with("///") {
+        format!("{indent}/// ")
+    } else if trimmed.starts_with("/**") && !trimmed.contains("*/") {
+        format!("{indent} * ")
+    } else if trimmed.starts_with('*') && in_block_comment(&lines[..line]) {
+        format!("{indent}* ")
+    } else {
+        return None;
+    };
+
+    let rest = current.trim_start();
+    if rest.starts_with("///") || rest.starts_with('*') {
+        return None;
+    }
+    let line_start = position_to_byte_offset(source, Position::new(position.line, 0));
+    let ws_end = line_start + current.len() - rest.len();
+    Some(TextEdit {
+        range: Range {
+            start: byte_offset_to_position(source, line_start),
+            end: byte_offset_to_position(source, ws_end),
+        },
+        new_text: prefix,
+    })
+}
+
+/// Whether the last of `lines` is inside a `/** ... */` comment that is
+/// still open.
+fn in_block_comment(lines: &[&str]) -> bool {
+    for line in lines.iter().rev() {
+        let trimmed = line.trim();
+        if trimmed.contains("*/") {
+            return false;
+        }
+        if trimmed.starts_with("/**") {
+            return true;
+        }
+        if !trimmed.starts_with('*') {
+            return false;
+        }
+    }
+    false
+}
+
+/// Re-indent the block closed by the `}` just before `position`.
+fn reindent_block(source: &str, tree: &Tree, position: Position) -> Vec<TextEdit> {
+    let byte = position_to_byte_offset(source, position);
+    if byte == 0 || source.as_bytes().get(byte - 1) != Some(&b'}') {
+        return Vec::new();
+    }
+    let Some(close) = tree
+        .root_node()
+        .descendant_for_byte_range(byte - 1, byte)
+        .filter(|n| n.kind() == "}")
+    else {
+        return Vec::new();
+    };
+    let Some(block) = close.parent().filter(|b| b.kind() != "ERROR") else {
+        return Vec::new();
+    };
+    let Some(open) = child_of_kind(block, "{") else {
+        return Vec::new();
+    };
+    let (open_row, close_row) = (open.start_position().row, close.start_position().row);
+    if open_row == close_row {
+        return Vec::new();
+    }
+
+    let lines: Vec<&str> = source.split('\n').collect();
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut offset = 0;
+    for line in &lines {
+        line_starts.push(offset);
+        offset += line.len() + 1;
+    }
+    let indent_of = |row: usize| {
+        let line = lines[row];
+        &line[..line.len() - line.trim_start().len()]
+    };
+    let base = indent_of(open_row).to_string();
+    let unit = if base.contains('\t') { "\t" } else { "    " };
+
+    // New indentation of each re-indented row, for the rows that follow.
+    let mut new_indent: Vec<Option<String>> = vec![None; close_row + 1];
+    new_indent[open_row] = Some(base.clone());
+    let mut edits = Vec::new();
+    for row in open_row + 1..=close_row {
+        let old = indent_of(row);
+        if lines[row].trim().is_empty() {
+            continue;
+        }
+        let first_byte = line_starts[row] + old.len();
+        let Some(node) = block.descendant_for_byte_range(first_byte, first_byte + 1) else {
+            continue;
+        };
+
+        let indent = if node.kind() == "}" {
+            // A closing brace lines up with the line of its `{`.
+            node.parent()
+                .and_then(|p| child_of_kind(p, "{"))
+                .and_then(|o| new_indent.get(o.start_position().row).cloned().flatten())
+        } else if node.start_position().row < row {
+            // Inside a multi-line token: strings must not change; comment
+            // lines move with the line the comment starts on.
+            if node.kind() == "comment" {
+                shifted(old, node.start_position().row, &new_indent, indent_of)
+            } else {
+                None
+            }
+        } else {
+            match element_start_row(node, first_byte) {
+                Some(start) if start < row => shifted(old, start, &new_indent, indent_of),
+                _ => Some(format!(
+                    "{base}{}",
+                    unit.repeat(brace_depth(node, block, first_byte))
+                )),
+            }
+        };
+
+        let Some(indent) = indent else {
+            continue;
+        };
+        if indent != old {
+            edits.push(TextEdit {
+                range: Range {
+                    start: byte_offset_to_position(source, line_starts[row]),
+                    end: byte_offset_to_position(source, first_byte),
+                },
+                new_text: indent.clone(),
+            });
+        }
+        new_indent[row] = Some(indent);
+    }
+    edits
+}
+
+/// Indentation for a continuation line: its old offset from the line it
+/// continues, applied to that line's new indentation.
+fn shifted<'a>(
+    old: &str,
+    start_row: usize,
+    new_indent: &[Option<String>],
+    indent_of: impl Fn(usize) -> &'a str,
+) -> Option<String> {
+    let start_new = new_indent.get(start_row)?.as_deref()?;
+    let extra = old.strip_prefix(indent_of(start_row))?;
+    Some(format!("{start_new}{extra}"))
+}
+
+fn child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).find(|c| c.kind() == kind)
+}
+
+/// Whether `node`'s own braces enclose `byte`.
+fn braces_enclose(node: Node<'_>, byte: usize) -> bool {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    let open = children.iter().find(|c| c.kind() == "{");
+    let close = children.iter().rev().find(|c| c.kind() == "}");
+    matches!((open, close), (Some(o), Some(c)) if o.end_byte() <= byte && c.start_byte() > byte)
+}
+
+/// Number of brace pairs enclosing `byte`, from `node` up to `block`.
+fn brace_depth(node: Node<'_>, block: Node<'_>, byte: usize) -> usize {
+    let mut depth = 0;
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if braces_enclose(n, byte) {
+            depth += 1;
+        }
+        if n.id() == block.id() {
+            break;
+        }
+        current = n.parent();
+    }
+    depth
+}
+
+/// Row where the statement or member containing `node` starts: the child of
+/// the nearest brace pair around `byte`.
+fn element_start_row(node: Node<'_>, byte: usize) -> Option<usize> {
+    let mut element = node;
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if braces_enclose(n, byte) {
+            return Some(element.start_position().row);
+        }
+        element = n;
+        current = n.parent();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(source: &str, edits: &[TextEdit]) -> String {
+        crate::file_operations::apply_text_edits(source, edits)
+    }
+
+    #[test]
+    fn test_diff_hunks() {
+        let a = ["a", "b", "c", "d", "e"];
+        let b = ["a", "x", "c", "e", "f"];
+        assert_eq!(
+            diff_hunks(&a, &b),
+            vec![
+                Hunk {
+                    old: 1..2,
+                    new: 1..2
+                },
+                Hunk {
+                    old: 3..4,
+                    new: 3..3
+                },
+                Hunk {
+                    old: 5..5,
+                    new: 4..5
+                },
+            ]
+        );
+        assert!(diff_hunks(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_minimal_edits() {
+        let original = "contract C {\n  function f() public {\n  uint x=1;\n  }\n}\n";
+        let formatted =
+            "contract C {\n    function f() public {\n        uint256 x = 1;\n    }\n}\n";
+        let edits = minimal_edits(original, formatted);
+        assert_eq!(apply(original, &edits), formatted);
+        // Only insertions: nothing outside the changed characters is replaced.
+        assert!(edits.iter().all(|e| e.range.start == e.range.end));
+        assert_eq!(
+            edits
+                .iter()
+                .find(|e| e.new_text == "256")
+                .map(|e| e.range.start),
+            Some(Position::new(2, 6))
+        );
+        assert!(minimal_edits(formatted, formatted).is_empty());
+    }
+
+    #[test]
+    fn test_minimal_edits_line_insertions_and_deletions() {
+        let original = "// a\n\n\n// b\nstring s = \"é\";\n";
+        let formatted = "// a\n\n// b\nstring s = \"é\";\n\n// c\n";
+        let edits = minimal_edits(original, formatted);
+        assert_eq!(apply(original, &edits), formatted);
+    }
+
+    #[test]
+    fn test_range_edits_only_touch_selected_function() {
+        let original = "contract C {\n    function f() public {\n  uint x=1;\n    }\n\n    function g() public {\n  uint y=2;\n    }\n}\n";
+        let formatted = "contract C {\n    function f() public {\n        uint256 x = 1;\n    }\n\n    function g() public {\n        uint256 y = 2;\n    }\n}\n";
+        // Cursor on `uint y=2;` only.
+        let range = Range::new(Position::new(6, 3), Position::new(6, 5));
+        let edits = range_edits(original, formatted, range);
+        assert_eq!(
+            apply(original, &edits),
+            "contract C {\n    function f() public {\n  uint x=1;\n    }\n\n    function g() public {\n        uint256 y = 2;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_range_edits_whole_line_selection() {
+        let original = "uint constant A=1;\nuint constant B=2;\n";
+        let formatted = "uint256 constant A = 1;\nuint256 constant B = 2;\n";
+        let range = Range::new(Position::new(0, 0), Position::new(1, 0));
+        let edits = range_edits(original, formatted, range);
+        assert_eq!(
+            apply(original, &edits),
+            "uint256 constant A = 1;\nuint constant B=2;\n"
+        );
+    }
+
+    #[test]
+    fn test_continue_triple_slash() {
+        let source = "contract C {\n    /// @notice Hi\n\n}\n";
+        let edits = on_type_edits(source, None, Position::new(2, 0), "\n");
+        assert_eq!(
+            apply(source, &edits),
+            "contract C {\n    /// @notice Hi\n    /// \n}\n"
+        );
+    }
+
+    #[test]
+    fn test_continue_block_comment() {
+        let source = "    /**\n    \n";
+        let edits = on_type_edits(source, None, Position::new(1, 4), "\n");
+        assert_eq!(apply(source, &edits), "    /**\n     * \n");
+
+        let source = "    /**\n     * @notice Hi\n";
+        let edits = on_type_edits(&format!("{source}\n"), None, Position::new(2, 0), "\n");
+        assert_eq!(edits[0].new_text, "     * ");
+
+        // Closed comment: nothing to continue.
+        let source = "    /** @notice Hi */\n\n";
+        assert!(on_type_edits(source, None, Position::new(1, 0), "\n").is_empty());
+    }
+
+    #[test]
+    fn test_reindent_block_on_close_brace() {
+        let source = "contract C {\n    function f(uint a) public {\n  if (a > 0) {\n  a = 1;\n        }\n          uint b = f2(\n            a\n          );\n  /* note\n     more */\n}\n}\n";
+        let tree = crate::syntax::parse(source).unwrap();
+        // Cursor right after the function's closing brace (line 10).
+        let edits = on_type_edits(source, Some(&tree), Position::new(10, 1), "}");
+        assert_eq!(
+            apply(source, &edits),
+            "contract C {\n    function f(uint a) public {\n        if (a > 0) {\n            a = 1;\n        }\n        uint b = f2(\n          a\n        );\n        /* note\n           more */\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_reindent_ignores_other_characters() {
+        let source = "contract C {}\n";
+        let tree = crate::syntax::parse(source).unwrap();
+        assert!(on_type_edits(source, Some(&tree), Position::new(0, 13), "}").is_empty());
+        assert!(on_type_edits(source, Some(&tree), Position::new(0, 5), ";").is_empty());
+    }
+}
@@ -43,6 +43,16 @@ pub struct NodeInfo {
     /// equivalence in references and call hierarchy.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub base_functions: Vec<NodeId>,
+    /// The `typeDescriptions.typeString` of expression and declaration
+    /// nodes (e.g. `"uint256"`, `"struct Pool.State storage pointer"`).
+    /// Used to type extracted local variables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_string: Option<String>,
+    /// The `src` of the declaration's `documentation`
+    /// (`StructuredDocumentation`) node. Used to rename the NatSpec tags
+    /// that name a parameter, return variable or contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
 }
 
 /// All AST child keys to traverse (Solidity + Yul).
@@ -119,6 +129,19 @@ pub struct StorageSlotInfo {
     pub slot: String,
     /// Byte offset within the slot (0 unless packed).
     pub offset: u32,
+    /// Whether the variable lives in transient storage.
+    pub transient: bool,
+}
+
+/// Storage usage of a single contract, as shown by inlay hints.
+#[derive(Debug, Clone, Default)]
+pub struct ContractStorage {
+    /// Slots of the state variables the contract itself declares, by name.
+    pub variables: HashMap<String, StorageSlotInfo>,
+    /// Persistent storage slots used, inherited variables included.
+    pub slots: u64,
+    /// Transient storage slots used, inherited variables included.
+    pub transient_slots: u64,
 }
 
 /// Pre-computed AST index. Built once when an AST enters the cache,
@@ -171,6 +194,13 @@ pub struct CachedBuild {
     /// `callHierarchy/incomingCalls` to unify interface and implementation IDs.
     /// Empty in warm-loaded builds (`from_reference_index`).
     pub base_function_implementation: HashMap<NodeId, Vec<NodeId>>,
+    /// Function and modifier declarations per file, keyed by the byte offset
+    /// of their name.
+    ///
+    /// Lets inheritance inlay hints match a tree-sitter declaration to its
+    /// AST node without scanning every node in the file. Built from `nodes`,
+    /// so it is also populated in warm-loaded builds.
+    pub callables: HashMap<AbsPath, HashMap<usize, NodeId>>,
     /// Storage layout index: maps state variable AST node IDs to their
     /// storage slot and byte offset within that slot.
     ///
@@ -181,6 +211,11 @@ pub struct CachedBuild {
     /// Offset is non-zero only for packed variables (e.g. two `bool`s
     /// sharing the same slot).
     pub storage_layout: HashMap<NodeId, StorageSlotInfo>,
+    /// Per-contract storage usage, keyed by source path and contract name.
+    ///
+    /// Built from the same layouts as `storage_layout`, but addressed by
+    /// name so inlay hints can be placed on the live buffer.
+    pub contract_storage: HashMap<AbsPath, HashMap<String, ContractStorage>>,
     /// Low-level external calls from Yul inline assembly.
     ///
     /// Contains `call`, `staticcall`, `delegatecall` Yul opcodes which
@@ -316,9 +351,11 @@ impl CachedBuild {
         // Works uniformly on both fresh and warm-loaded builds since NodeInfo
         // now persists the base_functions field.
         let base_function_implementation = build_base_function_implementation(&nodes);
+        let callables = build_callable_index(&nodes);
 
         // Build storage layout index from contracts[path][name].storageLayout.storage[].
         let storage_layout = build_storage_layout(&ast);
+        let contract_storage = build_contract_storage(&ast);
 
         // Canonicalize Yul low-level call src strings if interner is active.
         if let Some(ref remap) = canonical_remap {
@@ -344,11 +381,24 @@ impl CachedBuild {
             build_version,
             qualifier_refs,
             base_function_implementation,
+            callables,
             storage_layout,
+            contract_storage,
             low_level_calls,
         }
     }
 
+    /// Build the index from output that may carry errors, such as a compile
+    /// of an unsaved buffer that solc rejected.
+    ///
+    /// Solc still emits ASTs when only analysis fails (e.g. error 3656), but
+    /// leaves `sources` empty for errors it cannot get past. Returns `None`
+    /// when the output has no AST for `path`.
+    pub fn from_partial_output(ast: Value, path: &str, build_version: i32) -> Option<Self> {
+        ast.get("sources")?.get(path)?.get("ast")?;
+        Some(Self::new(ast, build_version, None))
+    }
+
     /// Absorb data from a previous build for files this build doesn't cover.
     ///
     /// For each file in `other.nodes` that is **not** already present in
@@ -397,12 +447,22 @@ impl CachedBuild {
                 }
             }
         }
+        for (abs_path, file_callables) in &other.callables {
+            self.callables
+                .entry(abs_path.clone())
+                .or_insert_with(|| file_callables.clone());
+        }
         // Merge storage layout: keep self's entries, add missing from other.
         for (node_id, info) in &other.storage_layout {
             self.storage_layout
                 .entry(*node_id)
                 .or_insert_with(|| info.clone());
         }
+        for (abs_path, contracts) in &other.contract_storage {
+            self.contract_storage
+                .entry(abs_path.clone())
+                .or_insert_with(|| contracts.clone());
+        }
     }
 
     /// Construct a minimal cached build from persisted reference/goto indexes.
@@ -441,6 +501,7 @@ impl CachedBuild {
         // Build base_function_implementation from the warm-loaded nodes.
         // NodeInfo now persists `base_functions`, so this works on warm loads.
         let base_function_implementation = build_base_function_implementation(&nodes);
+        let callables = build_callable_index(&nodes);
 
         Self {
             nodes,
@@ -455,7 +516,9 @@ impl CachedBuild {
             build_version,
             qualifier_refs,
             base_function_implementation,
+            callables,
             storage_layout: HashMap::new(),
+            contract_storage: HashMap::new(),
             low_level_calls: Vec::new(),
         }
     }
@@ -479,9 +542,9 @@ fn build_storage_layout(ast: &Value) -> HashMap<NodeId, StorageSlotInfo> {
         };
         for (_name, contract_data) in file_contracts {
             // Process both storageLayout and transientStorageLayout
-            for key in &["storageLayout", "transientStorageLayout"] {
+            for (key, transient) in [("storageLayout", false), ("transientStorageLayout", true)] {
                 let entries = match contract_data
-                    .get(*key)
+                    .get(key)
                     .and_then(|v| v.get("storage"))
                     .and_then(|v| v.as_array())
                 {
@@ -493,15 +556,83 @@ fn build_storage_layout(ast: &Value) -> HashMap<NodeId, StorageSlotInfo> {
                         Some(id) => id,
                         None => continue,
                     };
-                    let slot = entry
-                        .get("slot")
+                    map.insert(NodeId(ast_id), storage_slot_info(entry, transient));
+                }
+            }
+        }
+    }
+    map
+}
+
+/// Slot, offset and storage kind of one `storageLayout.storage[]` entry.
+fn storage_slot_info(entry: &Value, transient: bool) -> StorageSlotInfo {
+    let slot = entry
+        .get("slot")
+        .and_then(|v| v.as_str())
+        .unwrap_or("0")
+        .to_string();
+    let offset = entry.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    StorageSlotInfo {
+        slot,
+        offset,
+        transient,
+    }
+}
+
+/// Build the per-contract storage usage index from solc's `contracts` output.
+///
+/// A contract's layout lists inherited variables too; only entries whose
+/// `contract` is the contract itself are recorded under `variables`, while
+/// the slot totals cover the whole layout. A variable spans
+/// `ceil((offset + numberOfBytes) / 32)` slots from its own, with the size
+/// read from the layout's `types` table.
+fn build_contract_storage(ast: &Value) -> HashMap<AbsPath, HashMap<String, ContractStorage>> {
+    let mut map: HashMap<AbsPath, HashMap<String, ContractStorage>> = HashMap::new();
+    let Some(contracts) = ast.get("contracts").and_then(|v| v.as_object()) else {
+        return map;
+    };
+    for (path, contracts_in_file) in contracts {
+        let Some(file_contracts) = contracts_in_file.as_object() else {
+            continue;
+        };
+        for (name, contract_data) in file_contracts {
+            let qualified = format!("{path}:{name}");
+            let mut storage = ContractStorage::default();
+            for (key, transient) in [("storageLayout", false), ("transientStorageLayout", true)] {
+                let Some(layout) = contract_data.get(key) else {
+                    continue;
+                };
+                let Some(entries) = layout.get("storage").and_then(|v| v.as_array()) else {
+                    continue;
+                };
+                let mut used = 0;
+                for entry in entries {
+                    let info = storage_slot_info(entry, transient);
+                    let size = entry
+                        .get("type")
                         .and_then(|v| v.as_str())
-                        .unwrap_or("0")
-                        .to_string();
-                    let offset = entry.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-                    map.insert(NodeId(ast_id), StorageSlotInfo { slot, offset });
+                        .and_then(|ty| layout.get("types")?.get(ty)?.get("numberOfBytes"))
+                        .and_then(|v| v.as_str())
+                        .and_then(|n| n.parse::<u64>().ok())
+                        .unwrap_or(32);
+                    if let Ok(slot) = info.slot.parse::<u64>() {
+                        used = used.max(slot + (info.offset as u64 + size).div_ceil(32));
+                    }
+                    if entry.get("contract").and_then(|v| v.as_str()) == Some(qualified.as_str())
+                        && let Some(label) = entry.get("label").and_then(|v| v.as_str())
+                    {
+                        storage.variables.insert(label.to_string(), info);
+                    }
+                }
+                if transient {
+                    storage.transient_slots = used;
+                } else {
+                    storage.slots = used;
                 }
             }
+            map.entry(AbsPath::new(path))
+                .or_default()
+                .insert(name.clone(), storage);
         }
     }
     map
@@ -588,6 +719,35 @@ fn build_base_function_implementation(
     index
 }
 
+/// Build the per-file `callables` index from nodes.
+///
+/// Maps the `nameLocation` offset of every function and modifier definition
+/// to its node ID.
+fn build_callable_index(
+    nodes: &HashMap<AbsPath, HashMap<NodeId, NodeInfo>>,
+) -> HashMap<AbsPath, HashMap<usize, NodeId>> {
+    let mut index: HashMap<AbsPath, HashMap<usize, NodeId>> = HashMap::new();
+
+    for (abs_path, file_nodes) in nodes {
+        for (id, info) in file_nodes {
+            if !matches!(
+                info.node_type.as_deref(),
+                Some("FunctionDefinition" | "ModifierDefinition")
+            ) {
+                continue;
+            }
+            if let Some(loc) = info.name_location.as_deref().and_then(SourceLoc::parse) {
+                index
+                    .entry(abs_path.clone())
+                    .or_default()
+                    .insert(loc.offset, *id);
+            }
+        }
+    }
+
+    index
+}
+
 /// A low-level call detected in the AST that has no `referencedDeclaration`.
 ///
 /// Covers Yul opcodes (`call`, `staticcall`, `delegatecall`) from inline
@@ -661,6 +821,9 @@ fn canonicalize_node_info(info: &mut NodeInfo, remap: &HashMap<u64, FileId>) {
     if let Some(loc) = info.member_location.as_mut() {
         *loc = remap_src_canonical(loc, remap);
     }
+    if let Some(loc) = info.documentation.as_mut() {
+        *loc = remap_src_canonical(loc, remap);
+    }
 }
 
 pub fn cache_ids(sources: &Value) -> CachedIds {
@@ -721,6 +884,8 @@ pub fn cache_ids(sources: &Value) -> CachedIds {
                                 .map(|s| s.to_string()),
                             scope: ast.get("scope").and_then(|v| v.as_i64()).map(NodeId),
                             base_functions: vec![],
+                            type_string: None,
+                            documentation: None,
                         },
                     );
                 }
@@ -808,6 +973,16 @@ pub fn cache_ids(sources: &Value) -> CachedIds {
                                     arr.iter().filter_map(|v| v.as_i64().map(NodeId)).collect()
                                 })
                                 .unwrap_or_default(),
+                            type_string: tree
+                                .get("typeDescriptions")
+                                .and_then(|v| v.get("typeString"))
+                                .and_then(|v| v.as_str())
+                                .map(|s| s.to_string()),
+                            documentation: tree
+                                .get("documentation")
+                                .and_then(|v| v.get("src"))
+                                .and_then(|v| v.as_str())
+                                .map(|s| s.to_string()),
                         };
 
                         nodes.get_mut(&abs_path).unwrap().insert(id, node_info);
@@ -1287,6 +1462,139 @@ pub fn goto_declaration_by_name(
     })
 }
 
+// ── Type definition ────────────────────────────────────────────────────────
+
+/// Resolve the declaration ID of the type named by a solc `typeIdentifier`.
+///
+/// Peels off mapping and array layers down to the innermost value/element
+/// type, then extracts the trailing `_$<id>` of the struct, contract, enum,
+/// or UDVT. Elementary and function types have no declaration.
+///
+/// e.g. `t_struct$_PoolKey_$6871_memory_ptr` → `6871`
+///      `t_mapping$_t_address_$_t_contract$_IERC20_$45_$` → `45`
+///      `t_array$_t_contract$_IERC20_$45_$3_storage` → `45`
+pub fn type_identifier_decl_id(type_id: &str) -> Option<NodeId> {
+    let mut current = type_id.to_string();
+    loop {
+        if current.starts_with("t_mapping$_") {
+            current = crate::completion::extract_mapping_value_type(&current)?;
+        } else if let Some(inner) = current.strip_prefix("t_array$_") {
+            // Arrays end in `_$<length|dyn>[_<location>]` — the element type
+            // is everything before that last `_$`.
+            let end = inner.rfind("_$")?;
+            current = inner[..end].to_string();
+        } else {
+            break;
+        }
+    }
+    if current.starts_with("t_function") {
+        return None;
+    }
+    crate::completion::extract_node_id_from_type(&current)
+}
+
+/// Resolve the type declaration behind a declaration node.
+///
+/// Type declarations (contracts, structs, enums, UDVTs) resolve to
+/// themselves, variables to the declaration of their (unwrapped) type, and
+/// functions with a single return value to that value's type.
+///
+/// Local variables are not in `decl_index` (function bodies are stripped),
+/// so they fall back to [`local_variable_type_id`] on the current file.
+fn type_definition_id(
+    build: &CachedBuild,
+    decl_id: NodeId,
+    file_nodes: &HashMap<NodeId, NodeInfo>,
+    source_bytes: &[u8],
+) -> Option<NodeId> {
+    use crate::solc_ast::DeclNode;
+
+    match build.decl_index.get(&decl_id) {
+        Some(
+            DeclNode::ContractDefinition(_)
+            | DeclNode::StructDefinition(_)
+            | DeclNode::EnumDefinition(_)
+            | DeclNode::UserDefinedValueTypeDefinition(_),
+        ) => Some(decl_id),
+        Some(DeclNode::VariableDeclaration(v)) => {
+            type_identifier_decl_id(v.type_descriptions.type_identifier.as_deref()?)
+        }
+        Some(DeclNode::FunctionDefinition(f)) => match f.return_parameters.parameters.as_slice() {
+            [ret] => type_identifier_decl_id(ret.type_descriptions.type_identifier.as_deref()?),
+            _ => None,
+        },
+        Some(_) => None,
+        None => local_variable_type_id(decl_id, file_nodes, source_bytes),
+    }
+}
+
+/// Find the user-defined type of a local `VariableDeclaration` from its
+/// `UserDefinedTypeName` children.
+///
+/// Candidates lie between the start of the declaration and its name. Mapping
+/// key types are skipped by requiring that no `=>` follows the type name, so
+/// `mapping(PoolId => Pool.State) storage s` resolves to `Pool.State`.
+fn local_variable_type_id(
+    decl_id: NodeId,
+    file_nodes: &HashMap<NodeId, NodeInfo>,
+    source_bytes: &[u8],
+) -> Option<NodeId> {
+    let decl = file_nodes.get(&decl_id)?;
+    if decl.node_type.as_deref() != Some("VariableDeclaration") {
+        return None;
+    }
+    let decl_loc = SourceLoc::parse(decl.src.as_str())?;
+    let name_start = decl
+        .name_location
+        .as_deref()
+        .and_then(SourceLoc::parse)
+        .map(|loc| loc.offset)
+        .unwrap_or(decl_loc.end());
+
+    file_nodes
+        .values()
+        .filter(|info| info.node_type.as_deref() == Some("UserDefinedTypeName"))
+        .filter_map(|info| {
+            let loc = SourceLoc::parse(info.src.as_str())?;
+            if loc.offset < decl_loc.offset || loc.end() > name_start {
+                return None;
+            }
+            let trailing = source_bytes.get(loc.end()..name_start)?;
+            if trailing.windows(2).any(|w| w == b"=>") {
+                return None;
+            }
+            Some((loc.offset, info.referenced_declaration?))
+        })
+        .max_by_key(|(offset, _)| *offset)
+        .map(|(_, id)| id)
+}
+
+/// Go-to-type-definition using pre-built `CachedBuild` indices.
+///
+/// Resolves the node under the cursor (following `referencedDeclaration`
+/// for references), then jumps to the struct/contract/enum/UDVT declaration
+/// of its type. Mappings and arrays resolve to their value/element type.
+pub fn goto_type_definition_cached(
+    build: &CachedBuild,
+    file_uri: &Url,
+    position: Position,
+    source_bytes: &[u8],
+) -> Option<Location> {
+    let byte_position = pos_to_bytes(source_bytes, position);
+    let path = file_uri.to_file_path().ok()?;
+    let abs_path = build.path_to_abs.get(path.to_str()?)?;
+    let file_nodes = build.nodes.get(abs_path)?;
+
+    let node_id = crate::references::byte_to_id(&build.nodes, abs_path, byte_position)?;
+    let decl_id = file_nodes
+        .get(&node_id)
+        .and_then(|info| info.referenced_declaration)
+        .unwrap_or(node_id);
+
+    let type_id = type_definition_id(build, decl_id, file_nodes, source_bytes)?;
+    crate::references::id_to_location(&build.nodes, &build.id_to_path_map, type_id)
+}
+
 // ── Tree-sitter enhanced goto ──────────────────────────────────────────────
 
 /// Context extracted from the cursor position via tree-sitter.
@@ -2814,6 +3122,8 @@ contract B { uint256 public x; }
             absolute_path: None,
             scope: None,
             base_functions: vec![],
+            type_string: None,
+            documentation: None,
         };
         let serialized = serde_json::to_string(&info).unwrap();
         // Must NOT contain null-valued or empty-array keys
@@ -2825,6 +3135,7 @@ contract B { uint256 public x; }
         assert!(!serialized.contains("absolute_path"));
         assert!(!serialized.contains("scope"));
         assert!(!serialized.contains("base_functions"));
+        assert!(!serialized.contains("type_string"));
         // Must contain the populated fields
         assert!(serialized.contains("\"src\""));
         assert!(serialized.contains("\"node_type\""));
@@ -2851,6 +3162,8 @@ contract B { uint256 public x; }
             absolute_path: Some("/src/Foo.sol".to_string()),
             scope: Some(NodeId(10)),
             base_functions: vec![NodeId(100), NodeId(200)],
+            type_string: Some("function (uint256) returns (bool)".to_string()),
+            documentation: Some("180:19:7".to_string()),
         };
         let serialized = serde_json::to_string(&info).unwrap();
         // All fields must be present
@@ -2862,6 +3175,8 @@ contract B { uint256 public x; }
         assert!(serialized.contains("absolute_path"));
         assert!(serialized.contains("scope"));
         assert!(serialized.contains("base_functions"));
+        assert!(serialized.contains("type_string"));
+        assert!(serialized.contains("documentation"));
 
         let deserialized: NodeInfo = serde_json::from_str(&serialized).unwrap();
         assert_eq!(deserialized.name_location.as_deref(), Some("205:4:7"));
@@ -2870,5 +3185,109 @@ contract B { uint256 public x; }
         assert_eq!(deserialized.scope, Some(NodeId(10)));
         assert_eq!(deserialized.base_functions.len(), 2);
     }
+
+    #[test]
+    fn test_type_identifier_decl_id_user_defined() {
+        assert_eq!(
+            type_identifier_decl_id("t_struct$_PoolKey_$6871_memory_ptr"),
+            Some(NodeId(6871))
+        );
+        assert_eq!(
+            type_identifier_decl_id("t_contract$_IERC20_$45"),
+            Some(NodeId(45))
+        );
+        assert_eq!(
+            type_identifier_decl_id("t_userDefinedValueType$_Currency_$6525"),
+            Some(NodeId(6525))
+        );
+        assert_eq!(type_identifier_decl_id("t_uint256"), None);
+    }
+
+    #[test]
+    fn test_type_identifier_decl_id_unwraps_mappings_and_arrays() {
+        assert_eq!(
+            type_identifier_decl_id(
+                "t_mapping$_t_userDefinedValueType$_PoolId_$8841_$_t_struct$_State_$4809_storage_$"
+            ),
+            Some(NodeId(4809))
+        );
+        assert_eq!(
+            type_identifier_decl_id("t_mapping$_t_contract$_IERC20_$45_$_t_uint256_$"),
+            None
+        );
+        // Fixed-size length must not be mistaken for a node ID.
+        assert_eq!(
+            type_identifier_decl_id("t_array$_t_contract$_IERC20_$45_$3_storage"),
+            Some(NodeId(45))
+        );
+        assert_eq!(
+            type_identifier_decl_id(
+                "t_array$_t_array$_t_struct$_PoolKey_$6871_memory_ptr_$2_memory_ptr_$dyn_memory_ptr"
+            ),
+            Some(NodeId(6871))
+        );
+        assert_eq!(
+            type_identifier_decl_id("t_array$_t_uint256_$dyn_storage"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_type_identifier_decl_id_function_type() {
+        assert_eq!(
+            type_identifier_decl_id(
+                "t_function_internal_view$_t_uint256_$returns$_t_struct$_PoolKey_$6871_memory_ptr_$"
+            ),
+            None
+        );
+    }
+
+    fn node(src: &str, node_type: &str, referenced: Option<i64>) -> NodeInfo {
+        NodeInfo {
+            src: SrcLocation::new(src),
+            name_location: None,
+            name_locations: vec![],
+            referenced_declaration: referenced.map(NodeId),
+            node_type: Some(node_type.to_string()),
+            member_location: None,
+            member_name: None,
+            absolute_path: None,
+            scope: None,
+            base_functions: vec![],
+            type_string: None,
+            documentation: None,
+        }
+    }
+
+    #[test]
+    fn test_local_variable_type_id_skips_mapping_key() {
+        let source = "mapping(PoolId => Pool.State) storage s;";
+        let mut decl = node("0:39:0", "VariableDeclaration", None);
+        decl.name_location = Some("38:1:0".to_string());
+        let file_nodes = HashMap::from([
+            (NodeId(1), decl),
+            (NodeId(2), node("8:6:0", "UserDefinedTypeName", Some(10))),
+            (NodeId(3), node("18:10:0", "UserDefinedTypeName", Some(20))),
+        ]);
+        assert_eq!(
+            local_variable_type_id(NodeId(1), &file_nodes, source.as_bytes()),
+            Some(NodeId(20))
+        );
+    }
+
+    #[test]
+    fn test_local_variable_type_id_elementary_mapping_value() {
+        let source = "mapping(PoolId => uint256) storage s;";
+        let mut decl = node("0:36:0", "VariableDeclaration", None);
+        decl.name_location = Some("35:1:0".to_string());
+        let file_nodes = HashMap::from([
+            (NodeId(1), decl),
+            (NodeId(2), node("8:6:0", "UserDefinedTypeName", Some(10))),
+        ]);
+        assert_eq!(
+            local_variable_type_id(NodeId(1), &file_nodes, source.as_bytes()),
+            None
+        );
+    }
 }
 // temp
@@ -1,5 +1,5 @@
 use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, Position, Range};
-use tree_sitter::{Node, Parser};
+use tree_sitter::{Node, Tree};
 
 /// Return all highlights for the identifier under the cursor.
 ///
@@ -7,11 +7,19 @@ use tree_sitter::{Node, Parser};
 /// identifier text and classifies each as Read or Write based on its
 /// syntactic context.
 pub fn document_highlights(source: &str, position: Position) -> Vec<DocumentHighlight> {
-    let tree = match parse(source) {
-        Some(t) => t,
-        None => return vec![],
-    };
+    match crate::syntax::parse(source) {
+        Some(tree) => document_highlights_with_tree(&tree, source, position),
+        None => vec![],
+    }
+}
 
+/// Highlights for the identifier under the cursor from an already-parsed
+/// `tree` of `source`.
+pub fn document_highlights_with_tree(
+    tree: &Tree,
+    source: &str,
+    position: Position,
+) -> Vec<DocumentHighlight> {
     let root = tree.root_node();
 
     // Find the identifier node at the cursor position.
@@ -112,7 +120,7 @@ fn collect_matching_identifiers(
 ///   `count++`    → update_expression > expression > identifier
 ///   `x = 5`      → assignment_expression > expression > identifier
 /// So we check both the parent and grandparent to classify correctly.
-fn classify_highlight(node: Node, _source: &str) -> DocumentHighlightKind {
+pub(crate) fn classify_highlight(node: Node, _source: &str) -> DocumentHighlightKind {
     let parent = match node.parent() {
         Some(p) => p,
         None => return DocumentHighlightKind::READ,
@@ -250,14 +258,6 @@ fn is_lhs_of_assignment(assignment: Node, node: Node) -> bool {
 
 // ── Helpers ────────────────────────────────────────────────────────────────
 
-fn parse(source: &str) -> Option<tree_sitter::Tree> {
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_solidity::LANGUAGE.into())
-        .expect("failed to load Solidity grammar");
-    parser.parse(source, None)
-}
-
 fn range(node: Node) -> Range {
     let s = node.start_position();
     let e = node.end_position();
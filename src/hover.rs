@@ -1248,6 +1248,17 @@ pub fn hover_info(
 
     let byte_pos = pos_to_bytes(source_bytes, position);
 
+    // Number literals have no declaration: show their value directly.
+    if let Some(value) = number_literal_hover(source_bytes, byte_pos) {
+        return Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        });
+    }
+
     // Resolve: first try Yul external refs, then normal node lookup
     let node_id = byte_to_decl_via_external_refs(external_refs, id_to_path, &abs_path, byte_pos)
         .or_else(|| byte_to_id(nodes, &abs_path, byte_pos))?;
@@ -1281,6 +1292,17 @@ pub fn hover_info(
         parts.push(format!("Selector: `{}`", selector.to_prefixed()));
     }
 
+    // EIP-712 type string and typehash for structs.
+    if let Some(crate::solc_ast::DeclNode::StructDefinition(def)) = typed_decl
+        && let Some(encoded) = crate::eip712::encode_type(def, &cached_build.decl_index)
+    {
+        parts.push(format!("EIP-712 type: `{encoded}`"));
+        parts.push(format!(
+            "Typehash: `{}`",
+            crate::eip712::type_hash(&encoded)
+        ));
+    }
+
     // Node ID for debugging: show the cursor-hit node and the resolved
     // declaration (if different, e.g. when hovering a reference).
     if node_id == decl_id {
@@ -1304,6 +1326,17 @@ pub fn hover_info(
         }
     }
 
+    // Compile-time value of constants.
+    if let Some(crate::solc_ast::DeclNode::VariableDeclaration(var)) = typed_decl
+        && let Some(value) = crate::const_eval::constant_value(decl_id, &cached_build.decl_index)
+    {
+        let type_string = var.type_descriptions.type_string.as_deref();
+        parts.push(format!(
+            "Value: {}",
+            crate::const_eval::format_value(&value, type_string)
+        ));
+    }
+
     let di = &cached_build.decl_index;
     let id_to_path = &cached_build.node_id_to_source_path;
 
@@ -1399,6 +1432,62 @@ pub fn hover_info(
     })
 }
 
+/// Hover text for the number literal (e.g. `0x1e`, `1 gwei`) under `byte_pos`.
+fn number_literal_hover(source_bytes: &[u8], byte_pos: usize) -> Option<String> {
+    if !maybe_number_literal(source_bytes, byte_pos) {
+        return None;
+    }
+    let source = std::str::from_utf8(source_bytes).ok()?;
+    let tree = crate::inlay_hints::ts_parse(source)?;
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(byte_pos, byte_pos)?;
+    while node.kind() != "number_literal" {
+        node = node.parent()?;
+    }
+    let mut cursor = node.walk();
+    let unit = node
+        .named_children(&mut cursor)
+        .find(|c| c.kind() == "number_unit");
+    let end = unit.map_or(node.end_byte(), |u| u.start_byte());
+    let text = source[node.start_byte()..end].trim();
+    let unit = unit.map(|u| &source[u.byte_range()]);
+    let value = crate::const_eval::number_literal(text, unit)?;
+    Some(format!(
+        "Value: {}",
+        crate::const_eval::format_value(&value, None)
+    ))
+}
+
+/// Cheap check before parsing: the word under `byte_pos` starts with a
+/// digit, or is a unit word following one (`1 gwei`).
+fn maybe_number_literal(source: &[u8], byte_pos: usize) -> bool {
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'.';
+    let word_start = |mut pos: usize| {
+        while pos > 0 && is_word(source[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    };
+    let pos = byte_pos.min(source.len());
+    let start = word_start(pos);
+    match source.get(start) {
+        Some(b) if b.is_ascii_digit() => true,
+        Some(b) if b.is_ascii_alphabetic() => {
+            let mut before = start;
+            while before > 0 && source[before - 1].is_ascii_whitespace() {
+                before -= 1;
+            }
+            before < start
+                && before > 0
+                && source
+                    .get(word_start(before))
+                    .is_some_and(u8::is_ascii_digit)
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2351,4 +2440,25 @@ mod tests {
         let build = crate::goto::CachedBuild::new(ast, 0, None);
         assert!(mapping_signature_help_typed(&build.decl_index, "owner").is_none());
     }
+
+    #[test]
+    fn test_number_literal_hover() {
+        let source = "contract C { uint256 constant FEE = 1 gwei + 0x1e; }";
+        let gwei = source.find("gwei").unwrap();
+        assert_eq!(
+            number_literal_hover(source.as_bytes(), gwei).as_deref(),
+            Some("Value: `1000000000` · `0x3b9aca00` · `0.000000001 ether` · `1 gwei`")
+        );
+        let hex = source.find("0x1e").unwrap() + 2;
+        assert_eq!(
+            number_literal_hover(source.as_bytes(), hex).as_deref(),
+            Some("Value: `30` · `0x1e`")
+        );
+        assert_eq!(number_literal_hover(source.as_bytes(), 2), None);
+        assert!(!maybe_number_literal(
+            source.as_bytes(),
+            source.find("FEE").unwrap()
+        ));
+        assert!(maybe_number_literal(source.as_bytes(), gwei + 2));
+    }
 }
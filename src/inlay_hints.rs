@@ -1,9 +1,11 @@
-use crate::goto::CachedBuild;
+use crate::config::InlayHintsSettings;
+use crate::goto::{CachedBuild, ContractStorage};
+use crate::references;
 use crate::types::{AbsPath, NodeId, SourceLoc};
 use serde_json::Value;
 use std::collections::HashMap;
 use tower_lsp::lsp_types::*;
-use tree_sitter::{Node, Parser};
+use tree_sitter::Node;
 
 /// Parameter info resolved from the AST for a callable.
 #[derive(Debug, Clone)]
@@ -36,12 +38,17 @@ pub struct ResolvedCallSite {
 
 /// Both lookup strategies: exact byte-offset match and (name, arg_count) fallback.
 /// Built once per file when the AST is cached, reused on every inlay hint request.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct HintLookup {
     /// Primary: byte_offset → CallSite (exact match when AST offsets are fresh).
     by_offset: HashMap<usize, CallSite>,
     /// Fallback: (name, arg_count) → CallSite (works even with stale offsets).
     by_name: HashMap<(String, usize), CallSite>,
+    /// Destructuring tuple byte_offset → type to show in each component slot
+    /// (`None` for components whose type is already written out).
+    tuple_types: HashMap<usize, Vec<Option<String>>>,
+    /// `return (…)` tuple byte_offset → the function's named return variables.
+    return_names: HashMap<usize, Vec<String>>,
 }
 
 impl HintLookup {
@@ -176,12 +183,16 @@ pub fn build_hint_index(
 ///
 /// Uses tree-sitter on the **live buffer** for argument positions (so hints
 /// follow edits in real time) and the pre-cached hint index for semantic
-/// info (parameter names via `referencedDeclaration`).
+/// info (parameter names via `referencedDeclaration`). Hint kinds disabled
+/// in `settings` are not computed.
 pub fn inlay_hints(
     build: &CachedBuild,
+    other_builds: &[&CachedBuild],
     uri: &Url,
     range: Range,
     live_source: &[u8],
+    live_tree: &tree_sitter::Tree,
+    settings: &InlayHintsSettings,
 ) -> Vec<InlayHint> {
     let path_str = match uri.to_file_path() {
         Ok(p) => p.to_str().unwrap_or("").to_string(),
@@ -197,32 +208,47 @@ pub fn inlay_hints(
         None => return vec![],
     };
 
-    // Use the pre-cached hint lookup for this file
-    let lookup = match build.hint_index.get(&abs) {
-        Some(l) => l,
-        None => return vec![],
-    };
-
     // Walk tree-sitter on the live buffer for real-time argument positions
     let source_str = String::from_utf8_lossy(live_source);
-    let tree = match ts_parse(&source_str) {
-        Some(t) => t,
-        None => return vec![],
-    };
-
     let mut hints = Vec::new();
-    collect_ts_hints(tree.root_node(), &source_str, &range, lookup, &mut hints);
+
+    // Use the pre-cached hint lookup for this file
+    if let Some(lookup) = build.hint_index.get(&abs) {
+        collect_ts_hints(
+            live_tree.root_node(),
+            &source_str,
+            &range,
+            lookup,
+            settings,
+            &mut hints,
+        );
+    }
+
+    let builds: Vec<&CachedBuild> = std::iter::once(build)
+        .chain(other_builds.iter().copied())
+        .collect();
+    if settings.inheritance {
+        collect_override_hints(live_tree.root_node(), &range, &abs, &builds, &mut hints);
+    }
+
+    if settings.storage_slots
+        && let Some(storage) = build.contract_storage.get(&abs)
+    {
+        collect_storage_hints(
+            live_tree.root_node(),
+            &source_str,
+            &range,
+            storage,
+            &mut hints,
+        );
+    }
 
     hints
 }
 
 /// Parse Solidity source with tree-sitter.
 pub fn ts_parse(source: &str) -> Option<tree_sitter::Tree> {
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_solidity::LANGUAGE.into())
-        .expect("failed to load Solidity grammar");
-    parser.parse(source, None)
+    crate::syntax::parse(source)
 }
 
 /// Build both lookup strategies from the AST.
@@ -231,10 +257,7 @@ fn build_hint_lookup(
     decl_index: &HashMap<NodeId, crate::solc_ast::DeclNode>,
     constructor_index: &ConstructorIndex,
 ) -> HintLookup {
-    let mut lookup = HintLookup {
-        by_offset: HashMap::new(),
-        by_name: HashMap::new(),
-    };
+    let mut lookup = HintLookup::default();
     collect_ast_calls(file_ast, decl_index, constructor_index, &mut lookup);
     lookup
 }
@@ -280,6 +303,75 @@ fn collect_ast_calls(
                     .or_insert(site);
             }
         }
+        "VariableDeclarationStatement" => {
+            // `(, uint256 b, ) = foo()`: hint the elided components.
+            if let Some(declarations) = node.get("declarations").and_then(|v| v.as_array())
+                && declarations.iter().any(Value::is_null)
+                && let Some(offset) = parse_src_offset(node)
+            {
+                let rhs = tuple_component_types(node.get("initialValue"));
+                let types: Vec<Option<String>> = declarations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, decl)| {
+                        if decl.is_null() {
+                            rhs.get(i).cloned().flatten()
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                if types.iter().any(Option::is_some) {
+                    lookup.tuple_types.insert(offset, types);
+                }
+            }
+        }
+        "Assignment" => {
+            // `(a, b) = pool.slot0()`: hint every component.
+            if let Some(lhs) = node.get("leftHandSide")
+                && lhs.get("nodeType").and_then(|v| v.as_str()) == Some("TupleExpression")
+                && let Some(components) = lhs.get("components").and_then(|v| v.as_array())
+                && let Some(offset) = parse_src_offset(lhs)
+            {
+                let rhs = tuple_component_types(node.get("rightHandSide"));
+                let types: Vec<Option<String>> = components
+                    .iter()
+                    .enumerate()
+                    .map(|(i, component)| {
+                        if component.is_null() {
+                            rhs.get(i).cloned().flatten()
+                        } else {
+                            hint_type(type_string(component)?)
+                        }
+                    })
+                    .collect();
+                if types.iter().any(Option::is_some) {
+                    lookup.tuple_types.insert(offset, types);
+                }
+            }
+        }
+        "FunctionDefinition" => {
+            // `return (x, y)`: hint the named return variables.
+            if let Some(returns) = node.get("returnParameters")
+                && let Some(returns_id) = returns.get("id").and_then(|v| v.as_i64())
+                && let Some(params) = returns.get("parameters").and_then(|v| v.as_array())
+                && params.len() > 1
+                && let Some(body) = node.get("body")
+            {
+                let names: Vec<String> = params
+                    .iter()
+                    .map(|p| {
+                        p.get("name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string()
+                    })
+                    .collect();
+                if names.iter().any(|n| !n.is_empty()) {
+                    collect_return_names(body, returns_id, &names, lookup);
+                }
+            }
+        }
         "EmitStatement" => {
             if let Some(event_call) = node.get("eventCall")
                 && let Some(call_info) =
@@ -327,6 +419,85 @@ fn collect_ast_calls(
     }
 }
 
+/// Record `names` for every `return (…)` tuple under `node` that returns
+/// from the function whose return parameter list is `returns_id`.
+fn collect_return_names(node: &Value, returns_id: i64, names: &[String], lookup: &mut HintLookup) {
+    if node.get("nodeType").and_then(|v| v.as_str()) == Some("Return")
+        && node
+            .get("functionReturnParameters")
+            .and_then(|v| v.as_i64())
+            == Some(returns_id)
+        && let Some(tuple) = node.get("expression")
+        && tuple.get("nodeType").and_then(|v| v.as_str()) == Some("TupleExpression")
+        && tuple
+            .get("components")
+            .and_then(|v| v.as_array())
+            .is_some_and(|c| c.len() == names.len())
+        && let Some(offset) = parse_src_offset(tuple)
+    {
+        lookup.return_names.insert(offset, names.to_vec());
+    }
+
+    for key in crate::goto::CHILD_KEYS {
+        match node.get(*key) {
+            Some(Value::Array(items)) => {
+                for item in items {
+                    collect_return_names(item, returns_id, names, lookup);
+                }
+            }
+            Some(child @ Value::Object(_)) => {
+                collect_return_names(child, returns_id, names, lookup)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The `typeDescriptions.typeString` of an expression node.
+fn type_string(node: &Value) -> Option<&str> {
+    node.get("typeDescriptions")?.get("typeString")?.as_str()
+}
+
+/// A type worth showing as a hint: literal and nested tuple types are
+/// noise (`int_const 1`, `tuple(uint256,bool)`).
+fn hint_type(type_string: &str) -> Option<String> {
+    let skip = ["int_const", "rational_const", "literal_string", "tuple("];
+    if type_string.is_empty() || skip.iter().any(|p| type_string.starts_with(p)) {
+        return None;
+    }
+    Some(type_string.to_string())
+}
+
+/// Per-component hint types of a tuple-typed expression such as a call
+/// returning `tuple(uint160,int24)`. Empty when the expression is not a
+/// tuple.
+fn tuple_component_types(expr: Option<&Value>) -> Vec<Option<String>> {
+    let Some(inner) = expr
+        .and_then(type_string)
+        .and_then(|t| t.strip_prefix("tuple("))
+        .and_then(|t| t.strip_suffix(')'))
+    else {
+        return vec![];
+    };
+
+    let mut components = Vec::new();
+    let (mut depth, mut in_string, mut start) = (0i32, false, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' | '[' if !in_string => depth += 1,
+            ')' | ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                components.push(hint_type(&inner[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    components.push(hint_type(&inner[start..]));
+    components
+}
+
 /// Resolved call info including the declaration id of the called function/event.
 struct CallInfo {
     /// Function/event name.
@@ -491,6 +662,7 @@ fn collect_ts_hints(
     source: &str,
     range: &Range,
     lookup: &HintLookup,
+    settings: &InlayHintsSettings,
     hints: &mut Vec<InlayHint>,
 ) {
     // Quick range check — skip nodes entirely outside the visible range
@@ -501,19 +673,43 @@ fn collect_ts_hints(
     }
 
     match node.kind() {
-        "call_expression" => {
+        "call_expression" if settings.parameters => {
             emit_call_hints(node, source, lookup, hints);
         }
-        "emit_statement" => {
+        "emit_statement" if settings.parameters => {
             emit_emit_hints(node, source, lookup, hints);
         }
+        "variable_declaration_statement" if settings.types => {
+            if let Some(tuple) = first_named_child(node)
+                && tuple.kind() == "variable_declaration_tuple"
+            {
+                emit_tuple_type_hints(tuple, lookup, hints);
+            }
+        }
+        "assignment_expression" if settings.types => {
+            if let Some(tuple) = node
+                .child_by_field_name("left")
+                .and_then(first_named_child)
+                .filter(|n| n.kind() == "tuple_expression")
+            {
+                emit_tuple_type_hints(tuple, lookup, hints);
+            }
+        }
+        "return_statement" if settings.parameters => {
+            if let Some(tuple) = first_named_child(node)
+                .and_then(first_named_child)
+                .filter(|n| n.kind() == "tuple_expression")
+            {
+                emit_return_name_hints(tuple, source, lookup, hints);
+            }
+        }
         _ => {}
     }
 
     // Recurse into children
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_ts_hints(child, source, range, lookup, hints);
+        collect_ts_hints(child, source, range, lookup, settings, hints);
     }
 }
 
@@ -581,6 +777,323 @@ fn emit_param_hints(args: &[Node], info: &ParamInfo, hints: &mut Vec<InlayHint>)
     }
 }
 
+/// Emit inheritance hints after the header of every function and modifier
+/// in range: `↑ Base.f` for the declarations it overrides and `↓ N overrides`
+/// for the ones overriding it, each part linking to its target.
+///
+/// Declarations are matched to the AST through each build's `callables`
+/// index, since overrides of a base usually live in files only the project
+/// build covers.
+/// Base names come from the first build whose `decl_index` knows them.
+fn collect_override_hints(
+    node: Node,
+    range: &Range,
+    abs: &AbsPath,
+    builds: &[&CachedBuild],
+    hints: &mut Vec<InlayHint>,
+) {
+    if (node.end_position().row as u32) < range.start.line
+        || (node.start_position().row as u32) > range.end.line
+    {
+        return;
+    }
+    if matches!(node.kind(), "function_definition" | "modifier_definition") {
+        hints.extend(override_hint(node, abs, builds));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_override_hints(child, range, abs, builds, hints);
+    }
+}
+
+fn override_hint(node: Node, abs: &AbsPath, builds: &[&CachedBuild]) -> Option<InlayHint> {
+    let name_offset = node.child_by_field_name("name")?.start_byte();
+    let mut bases: Vec<InlayHintLabelPart> = Vec::new();
+    let mut overrides: Vec<Location> = Vec::new();
+
+    for build in builds {
+        let Some((id, info)) = build
+            .callables
+            .get(abs)
+            .and_then(|callables| callables.get(&name_offset))
+            .and_then(|id| Some((id, build.nodes.get(abs)?.get(id)?)))
+        else {
+            continue;
+        };
+
+        if bases.is_empty() {
+            for base_id in &info.base_functions {
+                let Some(decl) = build.decl_index.get(base_id) else {
+                    continue;
+                };
+                let value = match decl
+                    .scope()
+                    .and_then(|scope| build.decl_index.get(&NodeId(scope)))
+                {
+                    Some(contract) => format!("{}.{}", contract.name(), decl.name()),
+                    None => decl.name().to_string(),
+                };
+                if !bases.is_empty() {
+                    bases.push(label_part(", ".to_string(), None));
+                }
+                let location =
+                    references::id_to_location(&build.nodes, &build.id_to_path_map, *base_id);
+                bases.push(label_part(value, location));
+            }
+        }
+
+        for derived_id in build
+            .base_function_implementation
+            .get(id)
+            .into_iter()
+            .flatten()
+        {
+            let overrides_this = build
+                .nodes
+                .values()
+                .find_map(|nodes| nodes.get(derived_id))
+                .is_some_and(|derived| derived.base_functions.contains(id));
+            if overrides_this
+                && let Some(location) =
+                    references::id_to_location(&build.nodes, &build.id_to_path_map, *derived_id)
+                && !overrides.contains(&location)
+            {
+                overrides.push(location);
+            }
+        }
+    }
+
+    let mut parts = Vec::new();
+    if !bases.is_empty() {
+        parts.push(label_part("↑ ".to_string(), None));
+        parts.extend(bases);
+    }
+    if !overrides.is_empty() {
+        overrides.sort_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line).cmp(&(b.uri.as_str(), b.range.start.line))
+        });
+        if !parts.is_empty() {
+            parts.push(label_part(" · ".to_string(), None));
+        }
+        let count = overrides.len();
+        let tooltip = overrides
+            .iter()
+            .map(|loc| {
+                let file = loc.uri.path().rsplit('/').next().unwrap_or_default();
+                format!("{file}:{}", loc.range.start.line + 1)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let noun = if count == 1 { "override" } else { "overrides" };
+        parts.push(InlayHintLabelPart {
+            value: format!("↓ {count} {noun}"),
+            tooltip: Some(InlayHintLabelPartTooltip::String(tooltip)),
+            location: overrides.into_iter().next(),
+            command: None,
+        });
+    }
+    if parts.is_empty() {
+        return None;
+    }
+
+    // After the header: before the body, or after the `;` of a bodiless
+    // declaration.
+    let (point, before_body) = match node.child_by_field_name("body") {
+        Some(body) => (body.start_position(), true),
+        None => (node.end_position(), false),
+    };
+    Some(InlayHint {
+        position: Position::new(point.row as u32, point.column as u32),
+        kind: None,
+        label: InlayHintLabel::LabelParts(parts),
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(!before_body),
+        padding_right: Some(before_body),
+        data: None,
+    })
+}
+
+fn label_part(value: String, location: Option<Location>) -> InlayHintLabelPart {
+    InlayHintLabelPart {
+        value,
+        tooltip: None,
+        location,
+        command: None,
+    }
+}
+
+/// Emit storage hints for every contract in range: `slot N` (with the byte
+/// offset when packed) after each state variable declaration, and the
+/// number of slots the contract uses after its closing brace.
+///
+/// Contracts and variables are matched by name, so hints stay on the right
+/// lines while the buffer is edited; variables added since the last build
+/// get no hint.
+fn collect_storage_hints(
+    root: Node,
+    source: &str,
+    range: &Range,
+    storage: &HashMap<String, ContractStorage>,
+    hints: &mut Vec<InlayHint>,
+) {
+    let mut cursor = root.walk();
+    for contract in root.children(&mut cursor) {
+        if contract.kind() != "contract_declaration"
+            || (contract.end_position().row as u32) < range.start.line
+            || (contract.start_position().row as u32) > range.end.line
+        {
+            continue;
+        }
+        let Some(layout) = contract
+            .child_by_field_name("name")
+            .and_then(|n| storage.get(&source[n.byte_range()]))
+        else {
+            continue;
+        };
+
+        if let Some(body) = contract.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for decl in body.children(&mut cursor) {
+                if decl.kind() != "state_variable_declaration" {
+                    continue;
+                }
+                let Some(info) = decl
+                    .child_by_field_name("name")
+                    .and_then(|n| layout.variables.get(&source[n.byte_range()]))
+                else {
+                    continue;
+                };
+                let mut label = format!("slot {}", info.slot);
+                if info.transient {
+                    label = format!("transient {label}");
+                }
+                if info.offset > 0 {
+                    label = format!("{label}, offset {}", info.offset);
+                }
+                hints.push(storage_hint(decl.end_position(), label));
+            }
+        }
+
+        let mut totals = Vec::new();
+        if layout.slots > 0 {
+            totals.push(plural(layout.slots, "slot"));
+        }
+        if layout.transient_slots > 0 {
+            totals.push(plural(layout.transient_slots, "transient slot"));
+        }
+        if !totals.is_empty() {
+            hints.push(storage_hint(contract.end_position(), totals.join(", ")));
+        }
+    }
+}
+
+fn storage_hint(point: tree_sitter::Point, label: String) -> InlayHint {
+    InlayHint {
+        position: Position::new(point.row as u32, point.column as u32),
+        kind: None,
+        label: InlayHintLabel::String(label),
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    }
+}
+
+fn plural(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Emit type hints in the slots of a destructuring tuple: `: T` after a
+/// component, or `T` alone in an elided slot.
+fn emit_tuple_type_hints(tuple: Node, lookup: &HintLookup, hints: &mut Vec<InlayHint>) {
+    let Some(types) = lookup.tuple_types.get(&tuple.start_byte()) else {
+        return;
+    };
+    let slots = tuple_slots(tuple);
+    if slots.len() != types.len() {
+        return;
+    }
+    for ((component, close), ty) in slots.into_iter().zip(types) {
+        let Some(ty) = ty else {
+            continue;
+        };
+        let (point, label) = match component {
+            Some(component) => (component.end_position(), format!(": {ty}")),
+            None => (close, ty.clone()),
+        };
+        hints.push(InlayHint {
+            position: Position::new(point.row as u32, point.column as u32),
+            kind: Some(InlayHintKind::TYPE),
+            label: InlayHintLabel::String(label),
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        });
+    }
+}
+
+/// Emit return-variable name hints before each component of `return (…)`,
+/// skipping components that already spell the name.
+fn emit_return_name_hints(
+    tuple: Node,
+    source: &str,
+    lookup: &HintLookup,
+    hints: &mut Vec<InlayHint>,
+) {
+    let Some(names) = lookup.return_names.get(&tuple.start_byte()) else {
+        return;
+    };
+    let slots = tuple_slots(tuple);
+    if slots.len() != names.len() {
+        return;
+    }
+    for ((component, _), name) in slots.into_iter().zip(names) {
+        let Some(component) = component else {
+            continue;
+        };
+        if name.is_empty() || &source[component.byte_range()] == name {
+            continue;
+        }
+        let start = component.start_position();
+        hints.push(InlayHint {
+            position: Position::new(start.row as u32, start.column as u32),
+            kind: Some(InlayHintKind::PARAMETER),
+            label: InlayHintLabel::String(format!("{name}:")),
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: Some(true),
+            data: None,
+        });
+    }
+}
+
+/// The component slots of a tuple node, in order: the component (`None`
+/// when elided) and the position of the `,` or `)` closing the slot.
+fn tuple_slots(tuple: Node) -> Vec<(Option<Node>, tree_sitter::Point)> {
+    let mut slots = Vec::new();
+    let mut component = None;
+    let mut cursor = tuple.walk();
+    for child in tuple.children(&mut cursor) {
+        match child.kind() {
+            "," | ")" => slots.push((component.take(), child.start_position())),
+            "(" | "comment" => {}
+            _ if child.is_named() => component = Some(child),
+            _ => {}
+        }
+    }
+    slots
+}
+
 // ── Tree-sitter helpers ───────────────────────────────────────────────────
 
 /// Get the function name from a `call_expression` node.
@@ -1440,10 +1953,7 @@ contract Foo {
     #[test]
     fn test_resolve_callsite_param_basic() {
         // Build a HintLookup manually with a known call site
-        let mut lookup = HintLookup {
-            by_offset: HashMap::new(),
-            by_name: HashMap::new(),
-        };
+        let mut lookup = HintLookup::default();
         lookup.by_name.insert(
             ("transfer".to_string(), 2),
             CallSite {
@@ -1470,10 +1980,7 @@ contract Foo {
     #[test]
     fn test_resolve_callsite_param_with_skip() {
         // Simulate a using-for library call where skip=1
-        let mut lookup = HintLookup {
-            by_offset: HashMap::new(),
-            by_name: HashMap::new(),
-        };
+        let mut lookup = HintLookup::default();
         lookup.by_name.insert(
             ("addTax".to_string(), 2),
             CallSite {
@@ -1497,10 +2004,7 @@ contract Foo {
 
     #[test]
     fn test_resolve_callsite_param_out_of_bounds() {
-        let mut lookup = HintLookup {
-            by_offset: HashMap::new(),
-            by_name: HashMap::new(),
-        };
+        let mut lookup = HintLookup::default();
         lookup.by_name.insert(
             ("foo".to_string(), 1),
             CallSite {
@@ -1519,10 +2023,7 @@ contract Foo {
 
     #[test]
     fn test_resolve_callsite_param_unknown_function() {
-        let lookup = HintLookup {
-            by_offset: HashMap::new(),
-            by_name: HashMap::new(),
-        };
+        let lookup = HintLookup::default();
         assert!(lookup.resolve_callsite_param(0, "unknown", 1, 0).is_none());
     }
 
@@ -1742,4 +2243,353 @@ contract Foo {
         assert_eq!(ctx.name, "orders");
         assert!(ctx.is_index_access);
     }
+
+    #[test]
+    fn test_tuple_component_types() {
+        let expr = serde_json::json!({
+            "typeDescriptions": {
+                "typeString": "tuple(struct Pool.Slot memory,uint256[2] memory,int_const 1,,string memory)"
+            }
+        });
+        assert_eq!(
+            tuple_component_types(Some(&expr)),
+            vec![
+                Some("struct Pool.Slot memory".to_string()),
+                Some("uint256[2] memory".to_string()),
+                None,
+                None,
+                Some("string memory".to_string()),
+            ]
+        );
+        let expr = serde_json::json!({ "typeDescriptions": { "typeString": "uint256" } });
+        assert!(tuple_component_types(Some(&expr)).is_empty());
+    }
+
+    #[test]
+    fn test_tuple_type_and_return_name_hints() {
+        let source = "contract Pool {
+    function f() public returns (uint160 price, int24 tick) {
+        (, int24 t) = slot0();
+        (price, tick) = slot0();
+        return (price, t);
+    }
+}
+";
+        let at = |needle: &str| source.find(needle).unwrap();
+        let slot0 = serde_json::json!({
+            "nodeType": "FunctionCall",
+            "typeDescriptions": { "typeString": "tuple(uint160,int24)" },
+        });
+        let ast = serde_json::json!({
+            "nodeType": "FunctionDefinition",
+            "returnParameters": {
+                "id": 5, "nodeType": "ParameterList",
+                "parameters": [{ "name": "price" }, { "name": "tick" }],
+            },
+            "body": {
+                "nodeType": "Block",
+                "statements": [
+                    {
+                        "nodeType": "VariableDeclarationStatement",
+                        "src": format!("{}:21:0", at("(, int24")),
+                        "declarations": [null, { "nodeType": "VariableDeclaration", "name": "t" }],
+                        "initialValue": slot0,
+                    },
+                    {
+                        "nodeType": "ExpressionStatement",
+                        "expression": {
+                            "nodeType": "Assignment",
+                            "leftHandSide": {
+                                "nodeType": "TupleExpression",
+                                "src": format!("{}:13:0", at("(price, tick)")),
+                                "components": [
+                                    { "typeDescriptions": { "typeString": "uint160" } },
+                                    { "typeDescriptions": { "typeString": "int24" } },
+                                ],
+                            },
+                            "rightHandSide": slot0,
+                        },
+                    },
+                    {
+                        "nodeType": "Return",
+                        "functionReturnParameters": 5,
+                        "expression": {
+                            "nodeType": "TupleExpression",
+                            "src": format!("{}:10:0", at("(price, t)")),
+                            "components": [{}, {}],
+                        },
+                    },
+                ],
+            },
+        });
+        let lookup = build_hint_lookup(&ast, &HashMap::new(), &HashMap::new());
+        let tree = ts_parse(source).unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(7, 0));
+        let mut hints = Vec::new();
+        collect_ts_hints(
+            tree.root_node(),
+            source,
+            &range,
+            &lookup,
+            &InlayHintsSettings::default(),
+            &mut hints,
+        );
+        let labels: Vec<(u32, u32, String, Option<InlayHintKind>)> = hints
+            .iter()
+            .map(|h| match &h.label {
+                InlayHintLabel::String(s) => {
+                    (h.position.line, h.position.character, s.clone(), h.kind)
+                }
+                InlayHintLabel::LabelParts(_) => panic!("expected a string label"),
+            })
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (2, 9, "uint160".to_string(), Some(InlayHintKind::TYPE)),
+                (3, 14, ": uint160".to_string(), Some(InlayHintKind::TYPE)),
+                (3, 20, ": int24".to_string(), Some(InlayHintKind::TYPE)),
+                (4, 23, "tick:".to_string(), Some(InlayHintKind::PARAMETER)),
+            ]
+        );
+
+        let settings = InlayHintsSettings {
+            types: false,
+            ..InlayHintsSettings::default()
+        };
+        let mut hints = Vec::new();
+        collect_ts_hints(
+            tree.root_node(),
+            source,
+            &range,
+            &lookup,
+            &settings,
+            &mut hints,
+        );
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].kind, Some(InlayHintKind::PARAMETER));
+    }
+
+    #[test]
+    fn test_override_hints_link_bases_and_overrides() {
+        let source = "contract Base {
+    function f() public virtual {}
+}
+
+contract Child is Base {
+    function f() public override {}
+}
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Base.sol");
+        std::fs::write(&path, source).unwrap();
+        let path = path.to_str().unwrap();
+        let at = |needle: &str, nth: usize| source.match_indices(needle).nth(nth).unwrap().0;
+        let src = |offset: usize, len: usize| format!("{offset}:{len}:0");
+        let function = |id: i64, nth: usize, scope: i64, base: Option<i64>| {
+            let start = at("function f", nth);
+            let body = at("{}", nth);
+            serde_json::json!({
+                "id": id, "nodeType": "FunctionDefinition", "name": "f",
+                "src": src(start, body + 2 - start),
+                "nameLocation": src(start + 9, 1),
+                "kind": "function", "stateMutability": "nonpayable", "visibility": "public",
+                "virtual": base.is_none(), "scope": scope,
+                "baseFunctions": base.map(|b| vec![b]),
+                "parameters": { "id": id + 1, "nodeType": "ParameterList", "src": src(start + 10, 2), "parameters": [] },
+                "returnParameters": { "id": id + 2, "nodeType": "ParameterList", "src": src(body, 0), "parameters": [] },
+                "body": { "id": id + 3, "nodeType": "Block", "src": src(body, 2), "statements": [] },
+            })
+        };
+        let contract = |id: i64, name: &str, nth: usize, f: serde_json::Value| {
+            let start = at("contract", nth);
+            let end = source[start..].find("\n}").unwrap() + start + 2;
+            serde_json::json!({
+                "id": id, "nodeType": "ContractDefinition", "name": name,
+                "src": src(start, end - start),
+                "nameLocation": src(start + 9, name.len()),
+                "contractKind": "contract", "scope": 1,
+                "nodes": [f],
+            })
+        };
+        let nodes = serde_json::json!([
+            contract(2, "Base", 0, function(10, 0, 2, None)),
+            contract(20, "Child", 1, function(30, 1, 20, Some(10))),
+        ]);
+        let ast = crate::test_support::output(path, source.len(), nodes);
+        let build = CachedBuild::new(ast, 0, None);
+        let uri = Url::from_file_path(path).unwrap();
+        let tree = ts_parse(source).unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(7, 0));
+        let hints = inlay_hints(
+            &build,
+            &[],
+            &uri,
+            range,
+            source.as_bytes(),
+            &tree,
+            &InlayHintsSettings::default(),
+        );
+        let parts: Vec<_> = hints
+            .iter()
+            .map(|h| match &h.label {
+                InlayHintLabel::LabelParts(parts) => (
+                    h.position.line,
+                    parts
+                        .iter()
+                        .map(|p| {
+                            let line = p.location.as_ref().map(|l| l.range.start.line);
+                            (p.value.clone(), line)
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                InlayHintLabel::String(_) => panic!("expected label parts"),
+            })
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                (1, vec![("↓ 1 override".to_string(), Some(5))]),
+                (
+                    5,
+                    vec![("↑ ".to_string(), None), ("Base.f".to_string(), Some(1)),]
+                ),
+            ]
+        );
+        assert_eq!(hints[0].position.character, 32);
+
+        let settings = InlayHintsSettings {
+            types: false,
+            inheritance: false,
+            ..InlayHintsSettings::default()
+        };
+        let hints = inlay_hints(
+            &build,
+            &[],
+            &uri,
+            range,
+            source.as_bytes(),
+            &tree,
+            &settings,
+        );
+        assert!(hints.is_empty());
+    }
+
+    const STORAGE_SOL: &str = "contract Base {
+    uint256 public total;
+}
+
+contract Vault is Base {
+    address owner;
+    bool paused;
+    uint256[2] pair;
+    uint constant FEE = 1;
+    uint256 transient lock;
+}
+";
+
+    /// A build of `STORAGE_SOL` with solc's storage layouts for both contracts.
+    fn storage_build() -> (Url, CachedBuild) {
+        let path = "/tmp/storage/src/Vault.sol";
+        let entry = |contract: &str, label: &str, slot: &str, offset: u64, ty: &str| {
+            serde_json::json!({
+                "astId": 0, "contract": format!("{path}:{contract}"), "label": label,
+                "offset": offset, "slot": slot, "type": ty,
+            })
+        };
+        let types = serde_json::json!({
+            "t_uint256": { "numberOfBytes": "32" },
+            "t_address": { "numberOfBytes": "20" },
+            "t_bool": { "numberOfBytes": "1" },
+            "t_array(t_uint256)2_storage": { "numberOfBytes": "64" },
+        });
+        let mut ast = crate::test_support::output(path, STORAGE_SOL.len(), serde_json::json!([]));
+        ast["contracts"] = serde_json::json!({
+            path: {
+                "Base": {
+                    "storageLayout": {
+                        "storage": [entry("Base", "total", "0", 0, "t_uint256")],
+                        "types": types,
+                    }
+                },
+                "Vault": {
+                    "storageLayout": {
+                        "storage": [
+                            entry("Base", "total", "0", 0, "t_uint256"),
+                            entry("Vault", "owner", "1", 0, "t_address"),
+                            entry("Vault", "paused", "1", 20, "t_bool"),
+                            entry("Vault", "pair", "2", 0, "t_array(t_uint256)2_storage"),
+                        ],
+                        "types": types,
+                    },
+                    "transientStorageLayout": {
+                        "storage": [entry("Vault", "lock", "0", 0, "t_uint256")],
+                        "types": types,
+                    }
+                }
+            }
+        });
+        (
+            Url::from_file_path(path).unwrap(),
+            CachedBuild::new(ast, 0, None),
+        )
+    }
+
+    #[test]
+    fn test_storage_slot_hints() {
+        let (uri, build) = storage_build();
+        let tree = ts_parse(STORAGE_SOL).unwrap();
+        let range = Range::new(Position::new(0, 0), Position::new(12, 0));
+        let settings = InlayHintsSettings {
+            parameters: true,
+            storage_slots: true,
+            types: false,
+            inheritance: false,
+        };
+        let hints = inlay_hints(
+            &build,
+            &[],
+            &uri,
+            range,
+            STORAGE_SOL.as_bytes(),
+            &tree,
+            &settings,
+        );
+        let labels: Vec<(u32, u32, String)> = hints
+            .iter()
+            .map(|h| match &h.label {
+                InlayHintLabel::String(s) => (h.position.line, h.position.character, s.clone()),
+                InlayHintLabel::LabelParts(_) => panic!("expected a string label"),
+            })
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (1, 25, "slot 0".to_string()),
+                (2, 1, "1 slot".to_string()),
+                (5, 18, "slot 1".to_string()),
+                (6, 16, "slot 1, offset 20".to_string()),
+                (7, 20, "slot 2".to_string()),
+                (9, 27, "transient slot 0".to_string()),
+                (10, 1, "4 slots, 1 transient slot".to_string()),
+            ]
+        );
+
+        let settings = InlayHintsSettings {
+            parameters: true,
+            storage_slots: false,
+            types: false,
+            inheritance: false,
+        };
+        let hints = inlay_hints(
+            &build,
+            &[],
+            &uri,
+            range,
+            STORAGE_SOL.as_bytes(),
+            &tree,
+            &settings,
+        );
+        assert!(hints.is_empty());
+    }
 }
@@ -0,0 +1,746 @@
+//! Inline local variable / inline internal function refactorings.
+//!
+//! The inverse of [`crate::extract`]. Use sites come from
+//! [`references::goto_references_cached`] and the declaration is read from
+//! the live buffer with tree-sitter, so both need a build of the current
+//! buffer.
+//!
+//! - **Inline variable** replaces every read of a local variable with its
+//!   initializer and removes the declaration. The variable must be assigned
+//!   exactly once, at its declaration.
+//! - **Inline function** replaces every call of a small `internal` /
+//!   `private` function with its body and removes the function. A function
+//!   returning a value must consist of a single `return` statement; one
+//!   without a return value is inlined statement by statement.
+//!
+//! When the cursor is on a candidate that cannot be inlined, the action is
+//! still returned, with the reason the client shows as `disabled.reason`.
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{DocumentHighlightKind, Position, Range, TextEdit, Url};
+use tree_sitter::{Node, Tree};
+
+use crate::extract::line_indent;
+use crate::goto::{CachedBuild, NodeInfo};
+use crate::highlight::classify_highlight;
+use crate::references;
+use crate::types::{NodeId, SourceLoc};
+use crate::utils::{byte_offset_to_position, position_to_byte_offset};
+
+/// An inline refactoring for the symbol under the cursor.
+#[derive(Debug)]
+pub struct InlineAction {
+    pub title: String,
+    /// The edits, or why the symbol cannot be inlined.
+    pub edits: Result<Vec<TextEdit>, String>,
+}
+
+/// The inline refactoring for the local variable or function at `position`,
+/// if there is one.
+pub fn inline_at(
+    source: &str,
+    tree: &Tree,
+    build: &CachedBuild,
+    uri: &Url,
+    position: Position,
+) -> Option<InlineAction> {
+    let path = uri.to_file_path().ok()?;
+    let path_str = path.to_str()?;
+    let abs_path = build
+        .path_to_abs
+        .get(path_str)
+        .map(|p| p.as_str())
+        .unwrap_or(path_str);
+    let file_nodes = build.nodes.get(abs_path)?;
+    let byte = position_to_byte_offset(source, position);
+    let node_id = references::byte_to_id(&build.nodes, abs_path, byte)?;
+    let decl_id = file_nodes
+        .get(&node_id)?
+        .referenced_declaration
+        .unwrap_or(node_id);
+    let decl = file_nodes.get(&decl_id)?;
+    let name = SourceLoc::parse(decl.name_location.as_deref()?)?;
+    // On the declaration itself, only its name counts.
+    if decl_id == node_id && !(name.offset..=name.end()).contains(&byte) {
+        return None;
+    }
+    let ident = tree
+        .root_node()
+        .descendant_for_byte_range(name.offset, name.end())?;
+    if ident.kind() != "identifier" {
+        return None;
+    }
+
+    let mut other_files = false;
+    let mut uses = Vec::new();
+    for location in
+        references::goto_references_cached(build, uri, position, source.as_bytes(), None, false)
+    {
+        if &location.uri != uri {
+            other_files = true;
+            continue;
+        }
+        let start = position_to_byte_offset(source, location.range.start);
+        let end = position_to_byte_offset(source, location.range.end);
+        if start != name.offset {
+            uses.push((start, end));
+        }
+    }
+    uses.sort_unstable();
+    uses.dedup();
+
+    let name_text = &source[ident.byte_range()];
+    match decl.node_type.as_deref() {
+        Some("VariableDeclaration") => Some(InlineAction {
+            title: format!("Inline variable `{name_text}`"),
+            edits: inline_variable(source, tree, ident, &uses)?,
+        }),
+        Some("FunctionDefinition") => Some(InlineAction {
+            title: format!("Inline function `{name_text}`"),
+            edits: if other_files {
+                Err("Function is used in another file".to_string())
+            } else {
+                inline_function(source, tree, file_nodes, ident, &uses)?
+            },
+        }),
+        _ => None,
+    }
+}
+
+// ── Inline variable ────────────────────────────────────────────────────────
+
+/// `None` when `ident` does not name a local variable.
+fn inline_variable(
+    source: &str,
+    tree: &Tree,
+    ident: Node<'_>,
+    uses: &[(usize, usize)],
+) -> Option<Result<Vec<TextEdit>, String>> {
+    let declaration = ident
+        .parent()
+        .filter(|p| p.kind() == "variable_declaration")?;
+    let statement = declaration.parent()?;
+    match statement.kind() {
+        "variable_declaration_statement" => {}
+        "variable_declaration_tuple" => {
+            return Some(Err("Variable is declared in a tuple".to_string()));
+        }
+        _ => return None,
+    }
+    Some(inline_variable_edits(source, tree, statement, uses))
+}
+
+fn inline_variable_edits(
+    source: &str,
+    tree: &Tree,
+    statement: Node<'_>,
+    uses: &[(usize, usize)],
+) -> Result<Vec<TextEdit>, String> {
+    let wrapper = statement
+        .parent()
+        .filter(|p| p.kind() == "statement")
+        .ok_or("Variable is declared in a loop header")?;
+    let value = statement
+        .child_by_field_name("value")
+        .ok_or("Variable is not initialized at its declaration")?;
+    if uses.is_empty() {
+        return Err("Variable is never read".to_string());
+    }
+    if uses.len() > 1 && contains_kind(value, "call_expression") {
+        return Err("Initializer calls a function and would run once per use".to_string());
+    }
+
+    let root = tree.root_node();
+    let init = &source[value.byte_range()];
+    let mut edits = Vec::new();
+    for &(start, end) in uses {
+        let site = root
+            .descendant_for_byte_range(start, end)
+            .filter(|n| n.kind() == "identifier")
+            .ok_or("Variable is used in inline assembly")?;
+        if classify_highlight(site, source) == DocumentHighlightKind::WRITE {
+            return Err("Variable is written after its declaration".to_string());
+        }
+        edits.push(replace(source, start, end, parenthesize(init, value, site)));
+    }
+    edits.push(delete_lines(
+        source,
+        wrapper.start_byte(),
+        wrapper.end_byte(),
+    ));
+    Ok(edits)
+}
+
+// ── Inline function ────────────────────────────────────────────────────────
+
+/// `None` when `ident` does not name a function.
+fn inline_function(
+    source: &str,
+    tree: &Tree,
+    file_nodes: &HashMap<NodeId, NodeInfo>,
+    ident: Node<'_>,
+    uses: &[(usize, usize)],
+) -> Option<Result<Vec<TextEdit>, String>> {
+    let function = ident
+        .parent()
+        .filter(|p| p.kind() == "function_definition")?;
+    Some(inline_function_edits(
+        source, tree, file_nodes, function, uses,
+    ))
+}
+
+fn inline_function_edits(
+    source: &str,
+    tree: &Tree,
+    file_nodes: &HashMap<NodeId, NodeInfo>,
+    function: Node<'_>,
+    uses: &[(usize, usize)],
+) -> Result<Vec<TextEdit>, String> {
+    let free = function.parent().is_some_and(|p| p.kind() == "source_file");
+    let child = |kind: &str| {
+        let mut cursor = function.walk();
+        function.children(&mut cursor).find(|c| c.kind() == kind)
+    };
+    let visibility = child("visibility").map(|v| source[v.byte_range()].trim());
+    if !free && !matches!(visibility, Some("internal" | "private")) {
+        return Err("Only internal and private functions can be inlined".to_string());
+    }
+    if child("virtual").is_some() {
+        return Err("Function is virtual".to_string());
+    }
+    if child("override_specifier").is_some() {
+        return Err("Function overrides another function".to_string());
+    }
+    if child("modifier_invocation").is_some() {
+        return Err("Function has modifiers".to_string());
+    }
+    let body = function
+        .child_by_field_name("body")
+        .ok_or("Function has no body")?;
+
+    // The part of the body that replaces each call.
+    let mut cursor = body.walk();
+    let statements: Vec<Node> = body
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "statement")
+        .collect();
+    let returns = count_kind(body, "return_statement");
+    let returns_value = function.child_by_field_name("return_type").is_some();
+    let template = if returns_value {
+        if returns > 1 {
+            return Err("Function has multiple return paths".to_string());
+        }
+        match statements.as_slice() {
+            [only] => only
+                .named_child(0)
+                .filter(|s| s.kind() == "return_statement")
+                .and_then(|s| s.named_child(0))
+                .ok_or("Function body is not a single return statement")?,
+            _ if returns == 0 => {
+                return Err("Function uses named return variables".to_string());
+            }
+            _ => return Err("Function body is not a single return statement".to_string()),
+        }
+        .byte_range()
+    } else {
+        let mut statements = statements.as_slice();
+        if let Some((last, rest)) = statements.split_last()
+            && last
+                .named_child(0)
+                .is_some_and(|s| s.kind() == "return_statement" && s.named_child_count() == 0)
+        {
+            statements = rest;
+        }
+        if statements
+            .iter()
+            .any(|s| contains_kind(*s, "return_statement"))
+        {
+            return Err("Function has multiple return paths".to_string());
+        }
+        if statements
+            .iter()
+            .any(|s| contains_kind(*s, "variable_declaration"))
+        {
+            return Err("Function declares local variables".to_string());
+        }
+        match (statements.first(), statements.last()) {
+            (Some(first), Some(last)) => first.start_byte()..last.end_byte(),
+            _ => body.start_byte() + 1..body.start_byte() + 1,
+        }
+    };
+
+    // Parameters and where the template reads them.
+    let mut cursor = function.walk();
+    let params: Vec<Option<NodeId>> = function
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "parameter")
+        .map(|p| {
+            let name = p.child_by_field_name("name")?;
+            file_nodes.iter().find_map(|(id, info)| {
+                let loc = SourceLoc::parse(info.name_location.as_deref()?)?;
+                (info.node_type.as_deref() == Some("VariableDeclaration")
+                    && loc.offset == name.start_byte())
+                .then_some(*id)
+            })
+        })
+        .collect();
+    let root = tree.root_node();
+    let mut param_uses: Vec<(Node, usize)> = Vec::new();
+    for info in file_nodes.values() {
+        let Some(loc) = SourceLoc::parse(info.src.as_str()) else {
+            continue;
+        };
+        if info.node_type.as_deref() != Some("Identifier")
+            || !(template.start <= loc.offset && loc.end() <= template.end)
+        {
+            continue;
+        }
+        let Some(index) = params
+            .iter()
+            .position(|p| p.is_some() && *p == info.referenced_declaration)
+        else {
+            continue;
+        };
+        let Some(site) = root
+            .descendant_for_byte_range(loc.offset, loc.end())
+            .filter(|n| n.kind() == "identifier")
+        else {
+            continue;
+        };
+        if classify_highlight(site, source) == DocumentHighlightKind::WRITE {
+            return Err("Function assigns to a parameter".to_string());
+        }
+        param_uses.push((site, index));
+    }
+    param_uses.sort_by_key(|(site, _)| std::cmp::Reverse(site.start_byte()));
+
+    let contract = enclosing_contract(function);
+    let mut edits = Vec::new();
+    let mut replaced: Vec<(usize, usize)> = Vec::new();
+    for &(start, end) in uses {
+        let site = root
+            .descendant_for_byte_range(start, end)
+            .ok_or("Function is referenced without being called")?;
+        if site
+            .parent()
+            .is_some_and(|p| p.kind() == "member_expression")
+        {
+            return Err("Function is called through a member access".to_string());
+        }
+        let call = site
+            .parent()
+            .filter(|e| e.kind() == "expression")
+            .and_then(|e| {
+                let call = e.parent().filter(|c| c.kind() == "call_expression")?;
+                (call.child_by_field_name("function")?.id() == e.id()).then_some(call)
+            })
+            .ok_or("Function is referenced without being called")?;
+        if function.start_byte() <= start && end <= function.end_byte() {
+            return Err("Function is recursive".to_string());
+        }
+        if enclosing_contract(call) != contract {
+            return Err("Function is called from another contract".to_string());
+        }
+        if replaced
+            .iter()
+            .any(|&(s, e)| s < call.end_byte() && call.start_byte() < e)
+        {
+            return Err("Calls are nested".to_string());
+        }
+
+        let mut cursor = call.walk();
+        let args: Vec<Node> = call
+            .named_children(&mut cursor)
+            .filter(|c| c.kind() == "call_argument")
+            .map(|a| {
+                a.named_child(0)
+                    .filter(|e| e.kind() == "expression" && a.named_child_count() == 1)
+                    .ok_or("Call uses named arguments")
+            })
+            .collect::<Result<_, _>>()?;
+        if args.len() != params.len() {
+            return Err("Call does not match the function's parameters".to_string());
+        }
+        for (index, arg) in args.iter().enumerate() {
+            let count = param_uses.iter().filter(|(_, i)| *i == index).count();
+            if !is_simple(*arg) && count > 1 {
+                return Err("An argument would be evaluated more than once".to_string());
+            }
+            if !is_simple(*arg) && count == 0 {
+                return Err("An argument would not be evaluated".to_string());
+            }
+        }
+
+        // Substitute the arguments, last use first so offsets stay valid.
+        let mut text = source[template.clone()].to_string();
+        for (param_site, index) in &param_uses {
+            let arg = args[*index];
+            let range =
+                param_site.start_byte() - template.start..param_site.end_byte() - template.start;
+            text.replace_range(
+                range,
+                &parenthesize(&source[arg.byte_range()], arg, *param_site),
+            );
+        }
+
+        if returns_value {
+            let value = root
+                .descendant_for_byte_range(template.start, template.end)
+                .and_then(|n| outermost_expression(n, template.start, template.end))
+                .ok_or("Function body is not a single return statement")?;
+            edits.push(replace(
+                source,
+                call.start_byte(),
+                call.end_byte(),
+                parenthesize(&text, value, call),
+            ));
+            replaced.push((call.start_byte(), call.end_byte()));
+        } else {
+            let statement = call
+                .parent()
+                .and_then(|e| e.parent())
+                .filter(|s| s.kind() == "expression_statement")
+                .and_then(|s| s.parent())
+                .ok_or("Call is not a statement")?;
+            if text.trim().is_empty() {
+                edits.push(delete_lines(
+                    source,
+                    statement.start_byte(),
+                    statement.end_byte(),
+                ));
+            } else {
+                let from = line_indent(source, template.start);
+                let to = line_indent(source, statement.start_byte());
+                edits.push(replace(
+                    source,
+                    statement.start_byte(),
+                    statement.end_byte(),
+                    reindent(&text, &from, &to),
+                ));
+            }
+            replaced.push((statement.start_byte(), statement.end_byte()));
+        }
+    }
+
+    // Remove the function with its doc comments and the blank line above.
+    let mut start = function.start_byte();
+    let mut previous = function.prev_sibling();
+    while let Some(comment) = previous
+        .filter(|p| p.kind() == "comment" && p.end_position().row + 1 >= start_row(source, start))
+    {
+        start = comment.start_byte();
+        previous = comment.prev_sibling();
+    }
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    if source[..line_start].ends_with("\n\n") {
+        start = line_start - 1;
+    }
+    edits.push(delete_lines(source, start, function.end_byte()));
+    Ok(edits)
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────────
+
+fn replace(source: &str, start: usize, end: usize, new_text: String) -> TextEdit {
+    TextEdit {
+        range: Range {
+            start: byte_offset_to_position(source, start),
+            end: byte_offset_to_position(source, end),
+        },
+        new_text,
+    }
+}
+
+/// Delete `start..end`, together with its lines when nothing else is on
+/// them.
+fn delete_lines(source: &str, mut start: usize, mut end: usize) -> TextEdit {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+    if source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+        start = line_start;
+        end = (line_end + 1).min(source.len());
+    }
+    replace(source, start, end, String::new())
+}
+
+/// `text` (the source of expression `value`) wrapped in parentheses when
+/// substituting it at `site` could change how it binds.
+fn parenthesize(text: &str, value: Node<'_>, site: Node<'_>) -> String {
+    let loose = value.named_child(0).is_some_and(|n| {
+        matches!(
+            n.kind(),
+            "binary_expression"
+                | "ternary_expression"
+                | "assignment_expression"
+                | "augmented_assignment_expression"
+        )
+    });
+    let mut context = site.parent();
+    if site.kind() != "expression" && context.is_some_and(|c| c.kind() == "expression") {
+        context = context.and_then(|c| c.parent());
+    }
+    let enclosed = context.is_none_or(|c| {
+        matches!(
+            c.kind(),
+            "call_argument"
+                | "variable_declaration_statement"
+                | "return_statement"
+                | "expression_statement"
+                | "assignment_expression"
+                | "augmented_assignment_expression"
+                | "parenthesized_expression"
+        )
+    });
+    if loose && !enclosed {
+        format!("({text})")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Identifiers and literals: arguments that can be repeated or dropped.
+fn is_simple(arg: Node<'_>) -> bool {
+    arg.named_child(0).is_some_and(|n| {
+        matches!(
+            n.kind(),
+            "identifier"
+                | "number_literal"
+                | "boolean_literal"
+                | "string_literal"
+                | "hex_string_literal"
+        )
+    })
+}
+
+/// The outermost `expression` spanning exactly `start..end`.
+fn outermost_expression(node: Node<'_>, start: usize, end: usize) -> Option<Node<'_>> {
+    let mut found = None;
+    let mut current = Some(node);
+    while let Some(n) = current.filter(|n| n.start_byte() == start && n.end_byte() == end) {
+        if n.kind() == "expression" {
+            found = Some(n);
+        }
+        current = n.parent();
+    }
+    found
+}
+
+fn enclosing_contract(node: Node<'_>) -> Option<usize> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if matches!(
+            n.kind(),
+            "contract_declaration" | "library_declaration" | "interface_declaration"
+        ) {
+            return Some(n.id());
+        }
+        current = n.parent();
+    }
+    None
+}
+
+/// Re-indent lines after the first from `from` to `to`.
+fn reindent(text: &str, from: &str, to: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 || line.trim().is_empty() {
+                line.trim_end().to_string()
+            } else {
+                format!(
+                    "{to}{}",
+                    line.strip_prefix(from).unwrap_or_else(|| line.trim_start())
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn start_row(source: &str, byte: usize) -> usize {
+    source[..byte].matches('\n').count()
+}
+
+fn contains_kind(node: Node<'_>, kind: &str) -> bool {
+    count_kind(node, kind) > 0
+}
+
+fn count_kind(node: Node<'_>, kind: &str) -> usize {
+    let mut cursor = node.walk();
+    let nested: usize = node
+        .children(&mut cursor)
+        .map(|child| count_kind(child, kind))
+        .sum();
+    nested + usize::from(node.kind() == kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, node};
+
+    fn apply(source: &str, edits: &[TextEdit]) -> String {
+        crate::file_operations::apply_text_edits(source, edits)
+    }
+
+    /// Byte offset of the `nth` occurrence of `needle` in `source`.
+    fn at(source: &str, needle: &str, nth: usize) -> usize {
+        source.match_indices(needle).nth(nth).unwrap().0
+    }
+
+    /// Length of the last function in `source`, which ends the contract.
+    fn last_function_len(source: &str) -> usize {
+        let start = source.rfind("    function").unwrap() + 4;
+        source.rfind("\n}").unwrap() - start
+    }
+
+    fn inline(source: &str, build: &CachedBuild, byte: usize) -> Option<InlineAction> {
+        let tree = crate::syntax::parse(source).unwrap();
+        let path = build.path_to_abs.values().next().unwrap();
+        let uri = Url::from_file_path(path.as_str()).unwrap();
+        inline_at(
+            source,
+            &tree,
+            build,
+            &uri,
+            byte_offset_to_position(source, byte),
+        )
+    }
+
+    #[test]
+    fn test_inline_variable() {
+        let source = "contract C {
+    function f(uint a) public pure returns (uint) {
+        uint b = a + 1;
+        return b * b;
+    }
+}
+";
+        let decl = at(source, "uint b", 0);
+        let b = decl + 5;
+        let (_dir, build) = build(
+            source,
+            &[
+                node(2, "VariableDeclaration", decl, 6).name(b, 1),
+                node(3, "Identifier", at(source, "b * b", 0), 1).references(2),
+                node(4, "Identifier", at(source, "b;", 0), 1).references(2),
+            ],
+        );
+        let action = inline(source, &build, b).unwrap();
+        assert_eq!(action.title, "Inline variable `b`");
+        assert_eq!(
+            apply(source, &action.edits.unwrap()),
+            "contract C {
+    function f(uint a) public pure returns (uint) {
+        return (a + 1) * (a + 1);
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_inline_variable_refuses_reassigned() {
+        let source = "contract C {
+    function f() public pure returns (uint) {
+        uint b = 1;
+        b = 2;
+        return b;
+    }
+}
+";
+        let decl = at(source, "uint b", 0);
+        let (_dir, build) = build(
+            source,
+            &[
+                node(2, "VariableDeclaration", decl, 6).name(decl + 5, 1),
+                node(3, "Identifier", at(source, "b = 2", 0), 1).references(2),
+                node(4, "Identifier", at(source, "b;", 0), 1).references(2),
+            ],
+        );
+        let action = inline(source, &build, at(source, "b;", 0)).unwrap();
+        assert_eq!(
+            action.edits.unwrap_err(),
+            "Variable is written after its declaration"
+        );
+    }
+
+    #[test]
+    fn test_inline_function() {
+        let source = "contract C {
+    function f(uint a) public pure returns (uint) {
+        return double(a + 1) + 1;
+    }
+
+    /// Twice `x`.
+    function double(uint x) private pure returns (uint) {
+        return x * 2;
+    }
+}
+";
+        let name = at(source, "double(uint", 0);
+        let x = at(source, "uint x", 0);
+        let (_dir, build) = build(
+            source,
+            &[
+                node(
+                    10,
+                    "FunctionDefinition",
+                    at(source, "function double", 0),
+                    last_function_len(source),
+                )
+                .name(name, 6),
+                node(11, "VariableDeclaration", x, 6).name(x + 5, 1),
+                node(12, "Identifier", at(source, "x * 2", 0), 1).references(11),
+                node(13, "Identifier", at(source, "double(a", 0), 6).references(10),
+            ],
+        );
+        let action = inline(source, &build, name + 1).unwrap();
+        assert_eq!(action.title, "Inline function `double`");
+        assert_eq!(
+            apply(source, &action.edits.unwrap()),
+            "contract C {
+    function f(uint a) public pure returns (uint) {
+        return ((a + 1) * 2) + 1;
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_inline_function_refuses_multiple_returns() {
+        let source = "contract C {
+    function f(uint a) public pure returns (uint) {
+        return pick(a);
+    }
+
+    function pick(uint x) internal pure returns (uint) {
+        if (x > 1) { return x; }
+        return 1;
+    }
+}
+";
+        let name = at(source, "pick(uint", 0);
+        let (_dir, build) = build(
+            source,
+            &[
+                node(
+                    10,
+                    "FunctionDefinition",
+                    at(source, "function pick", 0),
+                    last_function_len(source),
+                )
+                .name(name, 4),
+                node(13, "Identifier", at(source, "pick(a", 0), 4).references(10),
+            ],
+        );
+        // From a call site.
+        let action = inline(source, &build, at(source, "pick(a", 0)).unwrap();
+        assert_eq!(
+            action.edits.unwrap_err(),
+            "Function has multiple return paths"
+        );
+    }
+}
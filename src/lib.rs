@@ -1,18 +1,30 @@
 pub mod build;
 pub mod call_hierarchy;
+pub mod change_signature;
 pub mod code_actions;
+pub mod code_lens;
 pub mod completion;
 pub mod config;
+pub mod const_eval;
+pub mod custom_errors;
+pub mod eip712;
+pub mod extract;
 pub mod file_operations;
 pub mod folding;
+pub mod formatting;
 pub mod goto;
 pub mod highlight;
 pub mod hover;
 pub mod inlay_hints;
+pub mod inline;
 pub mod links;
 pub mod lint;
 pub mod lsp;
+pub mod missing_functions;
+pub mod natspec;
+pub mod organize_imports;
 pub mod project_cache;
+pub mod pull_diagnostics;
 pub mod references;
 pub mod rename;
 pub mod runner;
@@ -22,5 +34,9 @@ pub mod solar_runner;
 pub mod solc;
 pub mod solc_ast;
 pub mod symbols;
+pub mod syntax;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod type_hierarchy;
 pub mod types;
 pub mod utils;
@@ -26,6 +26,79 @@ use tower_lsp::{Client, LanguageServer, lsp_types::*};
 /// Per-document semantic token cache: `result_id` + token list.
 type SemanticTokenCache = HashMap<DocumentUri, (String, Vec<SemanticToken>)>;
 
+/// Project-wide diagnostics keyed by absolute file path.
+type ProjectDiagnostics = HashMap<PathBuf, Vec<Diagnostic>>;
+
+/// Diagnostics from the last project-wide compile, shared by every pull.
+#[derive(Default)]
+struct ProjectDiagnosticsCache {
+    /// The last compile's diagnostics and the open-buffer versions it
+    /// compiled; `None` once invalidated.
+    last: RwLock<Option<(ProjectDiagnostics, HashMap<DocumentUri, i32>)>>,
+    /// Bumped by every invalidation, so a compile that raced one is dropped.
+    generation: AtomicU64,
+    /// Held while compiling, so concurrent pulls share a single compile.
+    compile: tokio::sync::Mutex<()>,
+}
+
+impl ProjectDiagnosticsCache {
+    async fn get(&self) -> Option<ProjectDiagnostics> {
+        self.last
+            .read()
+            .await
+            .as_ref()
+            .map(|(diags, _)| diags.clone())
+    }
+
+    /// Diagnostics recorded for `path`, if the cache holds a compile.
+    async fn get_file(&self, path: &Path) -> Option<Vec<Diagnostic>> {
+        let last = self.last.read().await;
+        let (diags, _) = last.as_ref()?;
+        Some(diags.get(path).cloned().unwrap_or_default())
+    }
+
+    /// Record a compile started at `generation`, unless it was invalidated
+    /// in the meantime.
+    async fn store(
+        &self,
+        generation: u64,
+        diags: ProjectDiagnostics,
+        versions: HashMap<DocumentUri, i32>,
+    ) {
+        let mut last = self.last.write().await;
+        if self.generation.load(Ordering::Acquire) == generation {
+            *last = Some((diags, versions));
+        }
+    }
+
+    async fn invalidate(&self) {
+        let mut last = self.last.write().await;
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *last = None;
+    }
+
+    /// Invalidate after `uri` was saved at `version`, unless the last
+    /// compile already saw that text (unchanged saves cost nothing).
+    async fn invalidate_saved(&self, uri: &str, version: i32) {
+        let seen = self
+            .last
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|(_, versions)| versions.get(uri) == Some(&version));
+        if !seen {
+            self.invalidate().await;
+        }
+    }
+}
+
+/// Project completion cache, root and remappings used by auto-import fixes.
+type AutoImportContext = (
+    Option<Arc<completion::CompletionCache>>,
+    PathBuf,
+    Vec<String>,
+);
+
 // ── Update check ──────────────────────────────────────────────────────
 
 /// The current version from Cargo.toml (e.g. "0.1.31").
@@ -91,6 +164,24 @@ pub struct ForgeLsp {
     ///
     /// The key is the file's URI converted to string, and the value is a tuple of (version, content).
     text_cache: Arc<RwLock<HashMap<DocumentUri, (i32, String)>>>,
+    /// Tree-sitter parse tree for each open document, kept in step with
+    /// `text_cache` by incremental `didChange` edits.
+    syntax_trees: Arc<RwLock<HashMap<DocumentUri, crate::syntax::DocumentTree>>>,
+    /// Latest diagnostics computed for each file, served to pull-diagnostics
+    /// clients by `textDocument/diagnostic` and `workspace/diagnostic`.
+    diagnostics: Arc<RwLock<HashMap<DocumentUri, Vec<Diagnostic>>>>,
+    /// Text version each file's diagnostics were computed from.  Save-time
+    /// fixes only trust diagnostics that still match the buffer.
+    diagnostics_versions: Arc<RwLock<HashMap<DocumentUri, i32>>>,
+    /// Diagnostics for every project file from the last project-wide solc
+    /// compile.  Empty until `workspace/diagnostic` first needs them; reset
+    /// when a save changes what that compile saw and when `.sol` files
+    /// change on disk.
+    project_diagnostics: Arc<ProjectDiagnosticsCache>,
+    /// Every Foundry project in the workspace folders, including the one the
+    /// fields above belong to.  Requests are routed to the project whose root
+    /// contains the file; see [`ForgeLsp::route`].
+    projects: Arc<RwLock<Vec<ProjectState>>>,
     completion_cache: Arc<RwLock<HashMap<DocumentUri, Arc<completion::CompletionCache>>>>,
     /// Cached lint configuration from `foundry.toml`.
     lint_config: Arc<RwLock<LintConfig>>,
@@ -175,6 +266,63 @@ pub struct ForgeLsp {
     cross_file_diag_uris: Arc<RwLock<HashSet<Url>>>,
 }
 
+/// State owned by a single Foundry project.
+///
+/// `ForgeLsp` holds the state of one project directly in its fields; a
+/// request for a file in another workspace project runs on a clone of the
+/// server with these fields swapped in ([`ForgeLsp::with_project`]), so the
+/// config, project index, sync workers and lib sub-caches never mix across
+/// projects.  Text, per-file builds and editor settings stay shared.
+#[derive(Clone)]
+struct ProjectState {
+    foundry_config: Arc<RwLock<FoundryConfig>>,
+    lint_config: Arc<RwLock<LintConfig>>,
+    root_uri: Arc<RwLock<Option<Url>>>,
+    project_indexed: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_dirty: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_sync_running: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_sync_pending: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_force_full_rebuild: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_upsert_running: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_upsert_pending: Arc<std::sync::atomic::AtomicBool>,
+    project_cache_changed_files: Arc<RwLock<HashSet<String>>>,
+    project_cache_upsert_files: Arc<RwLock<HashSet<String>>>,
+    sub_caches: Arc<RwLock<Vec<Arc<goto::CachedBuild>>>>,
+    sub_caches_loading: Arc<std::sync::atomic::AtomicBool>,
+    project_diagnostics: Arc<ProjectDiagnosticsCache>,
+}
+
+impl ProjectState {
+    fn new(foundry_config: FoundryConfig, lint_config: LintConfig, root_uri: Option<Url>) -> Self {
+        let flag = || Arc::new(std::sync::atomic::AtomicBool::new(false));
+        Self {
+            foundry_config: Arc::new(RwLock::new(foundry_config)),
+            lint_config: Arc::new(RwLock::new(lint_config)),
+            root_uri: Arc::new(RwLock::new(root_uri)),
+            project_indexed: flag(),
+            project_cache_dirty: flag(),
+            project_cache_sync_running: flag(),
+            project_cache_sync_pending: flag(),
+            project_cache_force_full_rebuild: flag(),
+            project_cache_upsert_running: flag(),
+            project_cache_upsert_pending: flag(),
+            project_cache_changed_files: Arc::new(RwLock::new(HashSet::new())),
+            project_cache_upsert_files: Arc::new(RwLock::new(HashSet::new())),
+            sub_caches: Arc::new(RwLock::new(Vec::new())),
+            sub_caches_loading: flag(),
+            project_diagnostics: Arc::new(ProjectDiagnosticsCache::default()),
+        }
+    }
+
+    /// Load a project from its `foundry.toml`.
+    fn load(toml_path: &Path) -> Self {
+        let foundry_config = config::load_foundry_config_from_toml(toml_path);
+        let lint_config = config::load_lint_config_from_toml(toml_path);
+        let root_uri = Url::from_directory_path(&foundry_config.root).ok();
+        Self::new(foundry_config, lint_config, root_uri)
+    }
+}
+
 /// Spawn a background task to discover, build (if missing), and load caches
 /// from lib sub-projects.  Non-blocking: returns immediately.
 ///
@@ -399,42 +547,556 @@ impl ForgeLsp {
         let ast_cache = Arc::new(RwLock::new(HashMap::new()));
         let text_cache = Arc::new(RwLock::new(HashMap::new()));
         let completion_cache = Arc::new(RwLock::new(HashMap::new()));
-        let lint_config = Arc::new(RwLock::new(LintConfig::default()));
-        let foundry_config = Arc::new(RwLock::new(FoundryConfig::default()));
         let client_capabilities = Arc::new(RwLock::new(None));
         let settings = Arc::new(RwLock::new(Settings::default()));
+        let project = ProjectState::new(FoundryConfig::default(), LintConfig::default(), None);
         Self {
             client,
             compiler,
             ast_cache,
             text_cache,
             completion_cache,
-            lint_config,
-            foundry_config,
+            lint_config: project.lint_config,
+            foundry_config: project.foundry_config,
             client_capabilities,
             settings,
             use_solc,
             semantic_token_cache: Arc::new(RwLock::new(HashMap::new())),
             semantic_token_id: Arc::new(AtomicU64::new(0)),
-            root_uri: Arc::new(RwLock::new(None)),
-            project_indexed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_dirty: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_sync_running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_sync_pending: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_force_full_rebuild: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_upsert_running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_upsert_pending: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-            project_cache_changed_files: Arc::new(RwLock::new(HashSet::new())),
-            project_cache_upsert_files: Arc::new(RwLock::new(HashSet::new())),
+            root_uri: project.root_uri,
+            project_indexed: project.project_indexed,
+            project_cache_dirty: project.project_cache_dirty,
+            project_cache_sync_running: project.project_cache_sync_running,
+            project_cache_sync_pending: project.project_cache_sync_pending,
+            project_cache_force_full_rebuild: project.project_cache_force_full_rebuild,
+            project_cache_upsert_running: project.project_cache_upsert_running,
+            project_cache_upsert_pending: project.project_cache_upsert_pending,
+            project_cache_changed_files: project.project_cache_changed_files,
+            project_cache_upsert_files: project.project_cache_upsert_files,
             pending_create_scaffold: Arc::new(RwLock::new(HashSet::new())),
             settings_from_init: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             did_save_workers: Arc::new(RwLock::new(HashMap::new())),
             code_action_db: Arc::new(crate::code_actions::load()),
-            sub_caches: Arc::new(RwLock::new(Vec::new())),
-            sub_caches_loading: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            sub_caches: project.sub_caches,
+            sub_caches_loading: project.sub_caches_loading,
             path_interner: Arc::new(RwLock::new(crate::types::PathInterner::new())),
             cross_file_diag_uris: Arc::new(RwLock::new(HashSet::new())),
+            syntax_trees: Arc::new(RwLock::new(HashMap::new())),
+            diagnostics: Arc::new(RwLock::new(HashMap::new())),
+            diagnostics_versions: Arc::new(RwLock::new(HashMap::new())),
+            project_diagnostics: project.project_diagnostics,
+            projects: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// The state of the project this server instance is scoped to.
+    fn project_state(&self) -> ProjectState {
+        ProjectState {
+            foundry_config: self.foundry_config.clone(),
+            lint_config: self.lint_config.clone(),
+            root_uri: self.root_uri.clone(),
+            project_indexed: self.project_indexed.clone(),
+            project_cache_dirty: self.project_cache_dirty.clone(),
+            project_cache_sync_running: self.project_cache_sync_running.clone(),
+            project_cache_sync_pending: self.project_cache_sync_pending.clone(),
+            project_cache_force_full_rebuild: self.project_cache_force_full_rebuild.clone(),
+            project_cache_upsert_running: self.project_cache_upsert_running.clone(),
+            project_cache_upsert_pending: self.project_cache_upsert_pending.clone(),
+            project_cache_changed_files: self.project_cache_changed_files.clone(),
+            project_cache_upsert_files: self.project_cache_upsert_files.clone(),
+            sub_caches: self.sub_caches.clone(),
+            sub_caches_loading: self.sub_caches_loading.clone(),
+            project_diagnostics: self.project_diagnostics.clone(),
+        }
+    }
+
+    /// A clone of this server scoped to `project`.
+    fn with_project(&self, project: &ProjectState) -> Self {
+        let project = project.clone();
+        Self {
+            foundry_config: project.foundry_config,
+            lint_config: project.lint_config,
+            root_uri: project.root_uri,
+            project_indexed: project.project_indexed,
+            project_cache_dirty: project.project_cache_dirty,
+            project_cache_sync_running: project.project_cache_sync_running,
+            project_cache_sync_pending: project.project_cache_sync_pending,
+            project_cache_force_full_rebuild: project.project_cache_force_full_rebuild,
+            project_cache_upsert_running: project.project_cache_upsert_running,
+            project_cache_upsert_pending: project.project_cache_upsert_pending,
+            project_cache_changed_files: project.project_cache_changed_files,
+            project_cache_upsert_files: project.project_cache_upsert_files,
+            sub_caches: project.sub_caches,
+            sub_caches_loading: project.sub_caches_loading,
+            project_diagnostics: project.project_diagnostics,
+            ..self.clone()
+        }
+    }
+
+    /// The registered project whose root most closely contains `path`.
+    async fn project_for_path(&self, path: &Path) -> Option<ProjectState> {
+        let projects = self.projects.read().await;
+        let mut best: Option<(usize, &ProjectState)> = None;
+        for project in projects.iter() {
+            let root = project.foundry_config.read().await.root.clone();
+            let depth = root.components().count();
+            if path.starts_with(&root) && best.is_none_or(|(d, _)| depth > d) {
+                best = Some((depth, project));
+            }
+        }
+        best.map(|(_, project)| project.clone())
+    }
+
+    /// Route a request for `path` to the workspace project that owns it.
+    ///
+    /// Returns a clone of the server scoped to that project, or `None` when
+    /// this instance is already scoped to it (or no project contains the
+    /// path), in which case the caller handles the request itself.
+    async fn route(&self, path: &Path) -> Option<Self> {
+        let project = self.project_for_path(path).await?;
+        if Arc::ptr_eq(&project.foundry_config, &self.foundry_config) {
+            return None;
+        }
+        Some(self.with_project(&project))
+    }
+
+    /// [`ForgeLsp::route`] for a `file://` URI.
+    async fn route_uri(&self, uri: &Url) -> Option<Self> {
+        self.route(&uri.to_file_path().ok()?).await
+    }
+
+    /// Register the project rooted at `toml_path`'s directory, unless a
+    /// project with that root is already known.
+    async fn register_project(&self, toml_path: &Path) {
+        let root = toml_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        for project in self.projects.read().await.iter() {
+            if project.foundry_config.read().await.root == root {
+                return;
+            }
+        }
+        let project = ProjectState::load(toml_path);
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("registered foundry project: {}", root.display()),
+            )
+            .await;
+        self.projects.write().await.push(project);
+    }
+
+    /// Register every Foundry project in a workspace folder.
+    async fn add_workspace_folder(&self, folder: &Path) {
+        for toml_path in config::discover_foundry_projects(folder) {
+            self.register_project(&toml_path).await;
+        }
+    }
+
+    /// Forget the projects inside a removed workspace folder and drop their
+    /// project-wide builds.  The project this instance is scoped to is kept.
+    async fn remove_workspace_folder(&self, folder: &Path) {
+        let mut removed = Vec::new();
+        {
+            let mut projects = self.projects.write().await;
+            let mut kept = Vec::with_capacity(projects.len());
+            for project in projects.drain(..) {
+                let root = project.foundry_config.read().await.root.clone();
+                if root.starts_with(folder)
+                    && !Arc::ptr_eq(&project.foundry_config, &self.foundry_config)
+                {
+                    removed.push(project);
+                } else {
+                    kept.push(project);
+                }
+            }
+            *projects = kept;
+        }
+        for project in removed {
+            if let Some(key) = self.with_project(&project).project_cache_key().await {
+                self.ast_cache.write().await.remove(&key);
+            }
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "unregistered foundry project: {}",
+                        project.foundry_config.read().await.root.display()
+                    ),
+                )
+                .await;
+        }
+    }
+
+    /// Whether the client pulls diagnostics instead of receiving pushes.
+    async fn pull_diagnostics_enabled(&self) -> bool {
+        self.client_capabilities
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(crate::pull_diagnostics::client_supports_pull)
+    }
+
+    /// Record diagnostics for `uri` and push them unless the client pulls.
+    async fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics
+            .write()
+            .await
+            .insert(uri.to_string().into(), diagnostics.clone());
+        if !self.pull_diagnostics_enabled().await {
+            self.client
+                .publish_diagnostics(uri, diagnostics, None)
+                .await;
+        }
+    }
+
+    /// Ask a pull-diagnostics client to re-request diagnostics.
+    async fn refresh_pulled_diagnostics(&self) {
+        if self.pull_diagnostics_enabled().await {
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let _ = client.workspace_diagnostic_refresh().await;
+            });
+        }
+    }
+
+    /// "Implement missing functions" for the contract containing `byte`.
+    ///
+    /// With `base`, only functions inherited through that base are stubbed.
+    /// Returns `None` when nothing is missing.
+    async fn implement_missing_functions_action(
+        &self,
+        uri: &Url,
+        source: &str,
+        byte: usize,
+        base: Option<&str>,
+    ) -> Option<CodeAction> {
+        let build = self.buffer_build(uri).await?;
+        let path = uri.to_file_path().ok()?;
+        let path_str = path.to_str()?;
+        let abs_path = build
+            .path_to_abs
+            .get(path_str)
+            .map(|p| p.as_str().to_string())
+            .unwrap_or_else(|| path_str.to_string());
+
+        let edit = crate::missing_functions::implement(&build, &abs_path, source, byte, base)?;
+        let title = match base {
+            Some(base) => format!("Implement missing functions from {base}"),
+            None => "Implement missing functions".to_string(),
+        };
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone(), vec![edit]);
+        Some(CodeAction {
+            title,
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// A build that sees the current buffer of `uri`, for quick fixes whose
+    /// diagnostics come from a build that never reached `ast_cache`.
+    ///
+    /// Returns the cached build when it is up to date. Otherwise the buffer
+    /// is compiled on demand and the output kept despite errors, as long as
+    /// solc still produced an AST for the file; that build is not cached.
+    /// Falls back to the stale cached build when neither works.
+    async fn buffer_build(&self, uri: &Url) -> Option<Arc<goto::CachedBuild>> {
+        if let Some(build) = self.current_build(uri).await {
+            return Some(build);
+        }
+        let key = uri.to_string();
+        let cached = self.ast_cache.read().await.get(&key).cloned();
+        if !self.use_solc {
+            return cached;
+        }
+        let Some((version, text)) = self.text_cache.read().await.get(&key).cloned() else {
+            return cached;
+        };
+        let Some(path) = uri.to_file_path().ok() else {
+            return cached;
+        };
+        let Some(path_str) = path.to_str() else {
+            return cached;
+        };
+        let config = self.foundry_config_for_file(&path).await;
+        match crate::solc::solc_ast(path_str, &config, Some(&self.client), Some(&text)).await {
+            Ok(output) => goto::CachedBuild::from_partial_output(output, path_str, version)
+                .map(Arc::new)
+                .or(cached),
+            Err(_) => cached,
+        }
+    }
+
+    /// Diagnostics of `text`, the current buffer of `uri`, for fix all on save.
+    ///
+    /// The last published diagnostics are reused when they were computed for
+    /// this version of the buffer; otherwise the buffer is compiled again.
+    /// `None` when neither is possible.
+    async fn fix_all_diagnostics(
+        &self,
+        uri: &Url,
+        file_path: &std::path::Path,
+        text: &str,
+    ) -> Option<Vec<Diagnostic>> {
+        let key = uri.to_string();
+        let text_version = self
+            .text_cache
+            .read()
+            .await
+            .get(&key)
+            .map(|(v, _)| *v)
+            .unwrap_or(0);
+        let diagnosed = self.diagnostics_versions.read().await.get(&key).copied();
+        if diagnosed.is_some_and(|v| v >= text_version) {
+            let diagnostics = self.diagnostics.read().await.get(&key).cloned();
+            return Some(diagnostics.unwrap_or_default());
+        }
+        if !self.use_solc {
+            return None;
+        }
+        let config = self.foundry_config_for_file(file_path).await;
+        let output =
+            crate::solc::solc_ast(file_path.to_str()?, &config, Some(&self.client), Some(text))
+                .await
+                .ok()?;
+        Some(crate::build::build_output_to_diagnostics(
+            &output,
+            file_path,
+            text,
+            &config.ignored_error_codes,
+        ))
+    }
+
+    /// The cached build for `uri`, if it was made from the current buffer.
+    async fn current_build(&self, uri: &Url) -> Option<Arc<goto::CachedBuild>> {
+        let key = uri.to_string();
+        let build = self.ast_cache.read().await.get(&key).cloned()?;
+        let text_version = self
+            .text_cache
+            .read()
+            .await
+            .get(&key)
+            .map(|(v, _)| *v)
+            .unwrap_or(0);
+        (build.build_version >= text_version).then_some(build)
+    }
+
+    /// Organize-imports edits for `uri`.
+    ///
+    /// Unused imports are only dropped when the cached build was made from
+    /// the current buffer; otherwise imports are just sorted and merged.
+    async fn organize_imports_edits(&self, uri: &Url, source: &str) -> Option<Vec<TextEdit>> {
+        let build = self.current_build(uri).await;
+        let config = match uri.to_file_path() {
+            Ok(path) => self.foundry_config_for_file(&path).await,
+            Err(_) => self.foundry_config.read().await.clone(),
+        };
+        let remappings = crate::solc::resolve_remappings(&config).await;
+        let text_cache = self.text_cache.read().await;
+        crate::organize_imports::organize_imports(
+            source,
+            uri,
+            &remappings,
+            build.as_deref(),
+            |path| {
+                let key = Url::from_file_path(path).ok()?.to_string();
+                match text_cache.get(&key) {
+                    Some((_, text)) => Some(text.clone()),
+                    None => std::fs::read_to_string(path).ok(),
+                }
+            },
+        )
+    }
+
+    /// How string reverts in `path` can become custom errors: `None` when the
+    /// resolved solc predates custom errors, otherwise whether `require`
+    /// accepts them. A solc whose version can't be read gets the
+    /// `if (!cond) revert` rewrite.
+    async fn custom_error_support(&self, path: &Path, source: &str) -> Option<bool> {
+        use crate::custom_errors::{MIN_CUSTOM_ERRORS, MIN_REQUIRE_ERRORS};
+
+        let config = self.foundry_config_for_file(path).await;
+        let pragma = crate::solc::parse_pragma(source);
+        let binary = crate::solc::resolve_solc_binary(&config, pragma.as_ref(), None).await;
+        match crate::solc::solc_version(&binary).await {
+            Some(version) if version < MIN_CUSTOM_ERRORS => None,
+            Some(version) => Some(version >= MIN_REQUIRE_ERRORS),
+            None => Some(false),
+        }
+    }
+
+    /// Under `fileOperations.renameFileWithContract`, extend the rename of a
+    /// contract declared in a same-named file with a `RenameFile` of that
+    /// file and the import rewrites the move needs, as one `documentChanges`
+    /// edit. Returns `edit` unchanged otherwise.
+    #[allow(clippy::too_many_arguments)]
+    async fn with_contract_file_rename(
+        &self,
+        build: &goto::CachedBuild,
+        uri: &Url,
+        position: Position,
+        source_bytes: &[u8],
+        new_name: &str,
+        text_buffers: &HashMap<String, Vec<u8>>,
+        edit: WorkspaceEdit,
+    ) -> WorkspaceEdit {
+        if !self
+            .settings
+            .read()
+            .await
+            .file_operations
+            .rename_file_with_contract
+        {
+            return edit;
+        }
+        let supports_rename = self
+            .client_capabilities
+            .read()
+            .await
+            .as_ref()
+            .and_then(|caps| caps.workspace.as_ref())
+            .and_then(|ws| ws.workspace_edit.as_ref())
+            .is_some_and(|we| {
+                we.document_changes == Some(true)
+                    && we
+                        .resource_operations
+                        .as_ref()
+                        .is_some_and(|ops| ops.contains(&ResourceOperationKind::Rename))
+            });
+        if !supports_rename {
+            return edit;
+        }
+        let Some(old_path) = rename::contract_file_at(build, uri, position, source_bytes) else {
+            return edit;
+        };
+        let new_path = old_path.with_file_name(format!("{new_name}.sol"));
+        if new_path.exists() {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!(
+                        "rename: {} already exists, keeping file name",
+                        new_path.display()
+                    ),
+                )
+                .await;
+            return edit;
+        }
+        let (Ok(old_uri), Ok(new_uri)) = (
+            Url::from_file_path(&old_path),
+            Url::from_file_path(&new_path),
+        ) else {
+            return edit;
+        };
+
+        let config = self.foundry_config_for_file(&old_path).await;
+        let buffers = text_buffers.clone();
+        let renames = vec![file_operations::FileRename { old_path, new_path }];
+        let import_edits = tokio::task::spawn_blocking(move || {
+            let source_files: Vec<String> = crate::solc::discover_source_files(&config)
+                .into_iter()
+                .filter_map(|p| p.to_str().map(String::from))
+                .collect();
+            let get_source_bytes = |fs_path: &str| -> Option<Vec<u8>> {
+                let uri = Url::from_file_path(fs_path).ok()?;
+                buffers
+                    .get(uri.as_str())
+                    .cloned()
+                    .or_else(|| std::fs::read(fs_path).ok())
+            };
+            file_operations::rename_imports(
+                &source_files,
+                &renames,
+                &config.root,
+                &get_source_bytes,
+            )
+            .edits
+        })
+        .await
+        .unwrap_or_default();
+
+        file_operations::rename_file_edit(
+            edit.changes.unwrap_or_default(),
+            import_edits,
+            old_uri,
+            new_uri,
+        )
+    }
+
+    /// Per-file diagnostics from a project-wide solc compile, cached until
+    /// the next save or on-disk `.sol` change.
+    async fn project_diagnostics(&self) -> ProjectDiagnostics {
+        let cache = &self.project_diagnostics;
+        if let Some(diags) = cache.get().await {
+            return diags;
+        }
+        // Pulls arriving during a compile wait for it instead of compiling
+        // again.
+        let _compile = cache.compile.lock().await;
+        if let Some(diags) = cache.get().await {
+            return diags;
+        }
+        let generation = cache.generation.load(Ordering::Acquire);
+
+        let foundry_config = self.foundry_config.read().await.clone();
+        // Only this project's open buffers take part in the compile.
+        let buffers: HashMap<DocumentUri, (i32, String)> = self
+            .text_cache
+            .read()
+            .await
+            .iter()
+            .filter(|(uri, _)| {
+                Url::parse(uri)
+                    .ok()
+                    .and_then(|u| u.to_file_path().ok())
+                    .is_some_and(|p| p.starts_with(&foundry_config.root))
+            })
+            .map(|(uri, entry)| (uri.clone(), entry.clone()))
+            .collect();
+        let output =
+            crate::solc::solc_project_index(&foundry_config, Some(&self.client), Some(&buffers))
+                .await;
+        let diags = match output {
+            Ok(output) => crate::build::project_diagnostics(
+                &output,
+                &foundry_config.root,
+                &foundry_config.ignored_error_codes,
+            ),
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("workspace diagnostics: project compile failed: {e}"),
+                    )
+                    .await;
+                return HashMap::new();
+            }
+        };
+        let versions = buffers
+            .into_iter()
+            .map(|(uri, (version, _))| (uri, version))
+            .collect();
+        cache.store(generation, diags.clone(), versions).await;
+        diags
+    }
+
+    /// Tree-sitter tree for the document at `uri`, parsed from `source`.
+    ///
+    /// Reuses the tree maintained by `didChange` when it was parsed from the
+    /// same text; otherwise parses `source` and caches the result.
+    async fn syntax_tree(&self, uri: &Url, source: &str) -> Option<tree_sitter::Tree> {
+        let key: DocumentUri = uri.to_string().into();
+        if let Some(doc) = self.syntax_trees.read().await.get(&key)
+            && doc.matches(source)
+        {
+            return Some(doc.tree.clone());
         }
+        let doc = crate::syntax::DocumentTree::parse(source)?;
+        let tree = doc.tree.clone();
+        self.syntax_trees.write().await.insert(key, doc);
+        Some(tree)
     }
 
     /// Resolve the foundry configuration for a specific file.
@@ -773,17 +1435,15 @@ impl ForgeLsp {
         // Clear stale diagnostics immediately so the user sees instant feedback
         // while solc is compiling.  Fresh diagnostics (if any) are published
         // below once the build finishes.
-        self.client
-            .publish_diagnostics(uri.clone(), vec![], None)
-            .await;
+        self.publish_diagnostics(uri.clone(), vec![]).await;
 
         // Check if linting should be skipped based on foundry.toml + editor settings.
-        let (should_lint, lint_settings) = {
+        let (should_lint, lint_settings, natspec_settings) = {
             let lint_cfg = self.lint_config.read().await;
             let settings = self.settings.read().await;
             let enabled = lint_cfg.should_lint(&file_path) && settings.lint.enabled;
             let ls = settings.lint.clone();
-            (enabled, ls)
+            (enabled, ls, settings.natspec.clone())
         };
 
         // When use_solc is enabled, run solc once for both AST and diagnostics.
@@ -992,6 +1652,23 @@ impl ForgeLsp {
             self.client.log_message(MessageType::INFO, reason).await;
         }
 
+        // NatSpec validation runs on the live buffer, independent of solc.
+        let mut natspec_diags = if natspec_settings.enabled {
+            crate::natspec::natspec_diagnostics(&params.text, &file_path, &natspec_settings)
+        } else {
+            vec![]
+        };
+
+        // Typehash checks read AST ranges, so only run them on a fresh build.
+        let mut typehash_diags = if build_succeeded {
+            let build = self.ast_cache.read().await.get(&uri.to_string()).cloned();
+            build.map_or_else(Vec::new, |build| {
+                crate::eip712::typehash_diagnostics(&build, &uri, &params.text)
+            })
+        } else {
+            vec![]
+        };
+
         // cache text — only if no newer version exists (e.g. from formatting/did_change)
         {
             let mut text_cache = self.text_cache.write().await;
@@ -1060,6 +1737,17 @@ impl ForgeLsp {
             }
         }
 
+        if !natspec_diags.is_empty() {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("found {} natspec diagnostics", natspec_diags.len()),
+                )
+                .await;
+            all_diagnostics.append(&mut natspec_diags);
+        }
+        all_diagnostics.append(&mut typehash_diags);
+
         // Sanitize: some LSP clients (e.g. trunk.io) crash on diagnostics with
         // empty message fields. Replace any empty message with a safe fallback
         // before publishing regardless of which diagnostic source produced it.
@@ -1070,9 +1758,11 @@ impl ForgeLsp {
         }
 
         // Publish diagnostics immediately — don't block on project indexing.
-        self.client
-            .publish_diagnostics(uri, all_diagnostics, None)
-            .await;
+        self.diagnostics_versions
+            .write()
+            .await
+            .insert(uri.to_string().into(), version);
+        self.publish_diagnostics(uri, all_diagnostics).await;
 
         // Cross-file diagnostics: publish errors to affected files and clear
         // stale errors from files that no longer have cross-file issues.
@@ -1082,8 +1772,7 @@ impl ForgeLsp {
 
             for (abs_path, diags) in &cross_file_diags {
                 if let Ok(file_uri) = Url::from_file_path(abs_path) {
-                    self.client
-                        .publish_diagnostics(file_uri.clone(), diags.clone(), None)
+                    self.publish_diagnostics(file_uri.clone(), diags.clone())
                         .await;
                     new_uris.insert(file_uri);
                 }
@@ -1092,19 +1781,19 @@ impl ForgeLsp {
             // Clear diagnostics from files that previously had cross-file
             // errors but no longer do (e.g. the error was fixed).
             for stale_uri in prev_uris.difference(&new_uris) {
-                self.client
-                    .publish_diagnostics(stale_uri.clone(), vec![], None)
-                    .await;
+                self.publish_diagnostics(stale_uri.clone(), vec![]).await;
             }
 
             *prev_uris = new_uris;
         }
+        self.refresh_pulled_diagnostics().await;
 
         // Refresh inlay hints after everything is updated
         if build_succeeded {
             let client = self.client.clone();
             tokio::spawn(async move {
                 let _ = client.inlay_hint_refresh().await;
+                let _ = client.code_lens_refresh().await;
             });
         }
 
@@ -1428,6 +2117,174 @@ impl ForgeLsp {
         }
     }
 
+    /// Collect implementation locations for the declaration at `position`
+    /// across the file build, the project build, and lib sub-caches.
+    ///
+    /// Returns `None` when no AST node is found at the cursor.
+    async fn collect_implementations(
+        &self,
+        cached_build: &goto::CachedBuild,
+        uri: &Url,
+        position: Position,
+        source_bytes: &[u8],
+    ) -> Option<Vec<Location>> {
+        let byte_position = goto::pos_to_bytes(source_bytes, position);
+        let abs_path = uri.as_ref().strip_prefix("file://").unwrap_or(uri.as_ref());
+
+        // Resolve node ID at cursor and follow referencedDeclaration.
+        // Also resolve the target's declaration abs_path + byte_offset for
+        // cross-build re-resolution (node IDs differ across builds).
+        let id = references::byte_to_id(&cached_build.nodes, abs_path, byte_position)?;
+        let target_id = cached_build
+            .nodes
+            .get(abs_path)
+            .and_then(|f| f.get(&id))
+            .and_then(|info| info.referenced_declaration)
+            .unwrap_or(id);
+
+        // Find the declaration's file and name_location byte offset.
+        let (target_decl_abs, target_decl_offset) =
+            references::resolve_target_location(cached_build, uri, position, source_bytes)
+                .unwrap_or_else(|| (abs_path.to_string(), byte_position));
+
+        // Collect all builds to search.
+        let project_build = self.ensure_project_cached_build().await;
+        let sub_caches = self.sub_caches.read().await;
+
+        let mut builds: Vec<&goto::CachedBuild> = vec![cached_build];
+        if let Some(ref pb) = project_build {
+            builds.push(pb);
+        }
+        for sc in sub_caches.iter() {
+            builds.push(sc);
+        }
+
+        // For each build, re-resolve the target by byte offset (stable across
+        // compilations), then look up base_function_implementation with the
+        // build-local node ID.  Collect (impl_id, build_ref) pairs so we can
+        // resolve locations within the same build that produced the ID.
+        let mut locations: Vec<Location> = Vec::new();
+        let mut seen_positions: Vec<(String, u32, u32)> = Vec::new(); // (uri, line, char)
+
+        for build in &builds {
+            // Re-resolve target in this build's node-ID space.
+            let local_target =
+                references::byte_to_id(&build.nodes, &target_decl_abs, target_decl_offset).or_else(
+                    || {
+                        // If the declaration file isn't in this build, try the original ID.
+                        if build.nodes.values().any(|f| f.contains_key(&target_id)) {
+                            Some(target_id)
+                        } else {
+                            None
+                        }
+                    },
+                );
+
+            let Some(local_id) = local_target else {
+                continue;
+            };
+
+            // Look up equivalents in this build.
+            let Some(impls) = build.base_function_implementation.get(&local_id) else {
+                continue;
+            };
+
+            for &impl_id in impls {
+                if let Some(loc) =
+                    references::id_to_location(&build.nodes, &build.id_to_path_map, impl_id)
+                {
+                    // Dedup by source position.
+                    let key = (
+                        loc.uri.to_string(),
+                        loc.range.start.line,
+                        loc.range.start.character,
+                    );
+                    if !seen_positions.contains(&key) {
+                        seen_positions.push(key);
+                        locations.push(loc);
+                    }
+                }
+            }
+        }
+
+        Some(locations)
+    }
+
+    /// Collect references for the declaration at `position` from the file
+    /// build, then expand the search into the project build and lib
+    /// sub-caches by the declaration's file path + byte offset.
+    ///
+    /// The current file is excluded from the project-cache scan — the
+    /// file-level build already covers it with freshly compiled offsets.
+    async fn collect_references(
+        &self,
+        file_build: &goto::CachedBuild,
+        project_build: Option<&goto::CachedBuild>,
+        uri: &Url,
+        position: Position,
+        source_bytes: &[u8],
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let current_abs = uri
+            .to_file_path()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // Always resolve target/local references from the current file build.
+        // This avoids stale/partial project-cache misses immediately after edits.
+        let mut locations = references::goto_references_cached(
+            file_build,
+            uri,
+            position,
+            source_bytes,
+            None,
+            include_declaration,
+        );
+
+        // Cross-file: resolve target from current file, then expand in project cache.
+        // Exclude the current file from the project-cache scan — the file-level
+        // build already covers it with correct (freshly compiled) byte offsets.
+        // The project cache may have stale offsets if the file was edited since
+        // the last full index, which would produce duplicate bogus locations.
+        if let Some((def_abs_path, def_byte_offset)) =
+            references::resolve_target_location(file_build, uri, position, source_bytes)
+        {
+            if let Some(project_build) = project_build {
+                let other_locations = references::goto_references_for_target(
+                    project_build,
+                    &def_abs_path,
+                    def_byte_offset,
+                    None,
+                    include_declaration,
+                    Some(&current_abs),
+                );
+                locations.extend(other_locations);
+            }
+
+            // Search sub-project caches for references in lib test files.
+            // Each sub-cache has its own node ID space; byte_to_id matches
+            // the target declaration by absolute file path + byte offset.
+            // No exclusion needed — sub-caches don't contain the current file.
+            let sub_caches = self.sub_caches.read().await;
+            for sub_cache in sub_caches.iter() {
+                let sub_locations = references::goto_references_for_target(
+                    sub_cache,
+                    &def_abs_path,
+                    def_byte_offset,
+                    None,
+                    include_declaration,
+                    None,
+                );
+                locations.extend(sub_locations);
+            }
+        }
+
+        // Deduplicate across all caches — removes exact duplicates and
+        // contained-range duplicates (e.g., UserDefinedTypeName full-span
+        // vs IdentifierPath name-only span for qualified type paths).
+        references::dedup_locations(locations)
+    }
+
     /// Get the source bytes for a file, preferring the in-memory text cache
     /// (which reflects unsaved editor changes) over reading from disk.
     async fn get_source_bytes(&self, uri: &Url, file_path: &std::path::Path) -> Option<Vec<u8>> {
@@ -2322,6 +3179,25 @@ impl LanguageServer for ForgeLsp {
             *fc = foundry_cfg;
         }
 
+        // Register the root project, then every Foundry project found in the
+        // workspace folders so requests can be routed by file path.
+        self.projects.write().await.push(self.project_state());
+        let folders: Vec<PathBuf> = match params.workspace_folders.as_ref() {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|f| f.uri.to_file_path().ok())
+                .collect(),
+            None => params
+                .root_uri
+                .as_ref()
+                .and_then(|uri| uri.to_file_path().ok())
+                .into_iter()
+                .collect(),
+        };
+        for folder in &folders {
+            self.add_workspace_folder(folder).await;
+        }
+
         // Negotiate position encoding with the client (once, for the session).
         let client_encodings = params
             .capabilities
@@ -2362,6 +3238,7 @@ impl LanguageServer for ForgeLsp {
                 definition_provider: Some(OneOf::Left(true)),
                 declaration_provider: Some(DeclarationCapability::Simple(true)),
                 implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
+                type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Right(RenameOptions {
                     prepare_provider: Some(true),
@@ -2380,9 +3257,21 @@ impl LanguageServer for ForgeLsp {
                     },
                 }),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: "\n".to_string(),
+                    more_trigger_character: Some(vec!["}".to_string()]),
+                }),
                 code_action_provider: Some(CodeActionProviderCapability::Options(
                     CodeActionOptions {
-                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR,
+                            CodeActionKind::REFACTOR_EXTRACT,
+                            CodeActionKind::REFACTOR_INLINE,
+                            CodeActionKind::REFACTOR_REWRITE,
+                            CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                        ]),
                         resolve_provider: Some(false),
                         work_done_progress_options: WorkDoneProgressOptions {
                             work_done_progress: None,
@@ -2390,7 +3279,19 @@ impl LanguageServer for ForgeLsp {
                     },
                 )),
                 call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
-                code_lens_provider: None,
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some(
+                            crate::pull_diagnostics::DIAGNOSTIC_IDENTIFIER.to_string(),
+                        ),
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        work_done_progress_options: WorkDoneProgressOptions::default(),
+                    },
+                )),
                 folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
                 inlay_hint_provider: Some(OneOf::Right(InlayHintServerCapabilities::Options(
@@ -2416,16 +3317,19 @@ impl LanguageServer for ForgeLsp {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         will_save: Some(true),
-                        will_save_wait_until: None,
+                        will_save_wait_until: Some(true),
                         open_close: Some(true),
                         save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
                             include_text: Some(true),
                         })),
-                        change: Some(TextDocumentSyncKind::FULL),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
                     },
                 )),
                 workspace: Some(WorkspaceServerCapabilities {
-                    workspace_folders: None,
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
                     file_operations: Some(WorkspaceFileOperationsServerCapabilities {
                         will_rename: Some(FileOperationRegistrationOptions {
                             filters: vec![
@@ -2536,6 +3440,7 @@ impl LanguageServer for ForgeLsp {
                     commands: vec![
                         "solidity.clearCache".to_string(),
                         "solidity.reindex".to_string(),
+                        "solidity.changeSignature".to_string(),
                     ],
                     work_done_progress_options: WorkDoneProgressOptions {
                         work_done_progress: None,
@@ -2577,6 +3482,10 @@ impl LanguageServer for ForgeLsp {
                                 glob_pattern: GlobPattern::String("**/remappings.txt".to_string()),
                                 kind: Some(WatchKind::all()),
                             },
+                            FileSystemWatcher {
+                                glob_pattern: GlobPattern::String("**/*.sol".to_string()),
+                                kind: Some(WatchKind::all()),
+                            },
                         ],
                     })
                     .unwrap(),
@@ -2597,6 +3506,48 @@ impl LanguageServer for ForgeLsp {
             }
         }
 
+        // lsp-types has no static `typeHierarchyProvider` capability, so
+        // type hierarchy is registered dynamically when the client allows it.
+        let supports_type_hierarchy = self
+            .client_capabilities
+            .read()
+            .await
+            .as_ref()
+            .and_then(|caps| caps.text_document.as_ref())
+            .and_then(|td| td.type_hierarchy.as_ref())
+            .and_then(|th| th.dynamic_registration)
+            .unwrap_or(false);
+
+        if supports_type_hierarchy {
+            let registration = Registration {
+                id: "type-hierarchy".to_string(),
+                method: "textDocument/prepareTypeHierarchy".to_string(),
+                register_options: Some(
+                    serde_json::to_value(TypeHierarchyRegistrationOptions {
+                        text_document_registration_options: TextDocumentRegistrationOptions {
+                            document_selector: Some(vec![DocumentFilter {
+                                language: None,
+                                scheme: Some("file".to_string()),
+                                pattern: Some("**/*.sol".to_string()),
+                            }]),
+                        },
+                        type_hierarchy_options: TypeHierarchyOptions::default(),
+                        static_registration_options: StaticRegistrationOptions::default(),
+                    })
+                    .unwrap(),
+                ),
+            };
+
+            if let Err(e) = self.client.register_capability(vec![registration]).await {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("failed to register type hierarchy: {e}"),
+                    )
+                    .await;
+            }
+        }
+
         // Pull settings from the client via workspace/configuration.
         // Neovim (and other editors) expose user settings through this
         // request rather than initializationOptions, so we need to pull
@@ -3101,6 +4052,18 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: ExecuteCommandParams,
     ) -> tower_lsp::jsonrpc::Result<Option<serde_json::Value>> {
+        // In a multi-root workspace, commands act on the project containing
+        // the file URI passed as the first argument, if any.
+        if let Some(uri) = params
+            .arguments
+            .first()
+            .and_then(|arg| arg.as_str())
+            .and_then(|arg| Url::parse(arg).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.execute_command(params).await;
+        }
+
         match params.command.as_str() {
             // ----------------------------------------------------------------
             // solidity.clearCache
@@ -3352,6 +4315,137 @@ impl LanguageServer for ForgeLsp {
                 Ok(Some(serde_json::json!({ "success": true })))
             }
 
+            // ----------------------------------------------------------------
+            // solidity.changeSignature
+            //
+            // Arguments: the file URI, the position of the function (its
+            // declaration or a call), and the new parameter list. Each entry
+            // is either `{ "from": <current index> }` or a new parameter
+            // `{ "type": "uint256", "name": "fee", "default": "0" }`, whose
+            // `default` is passed at existing call sites. The declaration,
+            // overrides, call sites and `@param` lines are rewritten through
+            // `workspace/applyEdit`.
+            //
+            // Usage (nvim):
+            //   vim.lsp.buf.execute_command({
+            //     command = "solidity.changeSignature",
+            //     arguments = { vim.uri_from_bufnr(0), pos, { { from = 1 }, { from = 0 } } },
+            //   })
+            // ----------------------------------------------------------------
+            "solidity.changeSignature" => {
+                let uri = params
+                    .arguments
+                    .first()
+                    .and_then(|arg| arg.as_str())
+                    .and_then(|arg| Url::parse(arg).ok());
+                let position = params
+                    .arguments
+                    .get(1)
+                    .and_then(|arg| serde_json::from_value::<Position>(arg.clone()).ok());
+                let parameters = params.arguments.get(2).and_then(|arg| {
+                    serde_json::from_value::<Vec<crate::change_signature::ParameterSpec>>(
+                        arg.clone(),
+                    )
+                    .ok()
+                });
+                let (Some(uri), Some(position), Some(parameters)) = (uri, position, parameters)
+                else {
+                    return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                        "expected [uri, position, parameters]",
+                    ));
+                };
+                let Ok(file_path) = uri.to_file_path() else {
+                    return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                        "invalid file uri",
+                    ));
+                };
+                let Some(source) = self.get_source_bytes(&uri, &file_path).await else {
+                    return Ok(None);
+                };
+                let source = String::from_utf8_lossy(&source).into_owned();
+                let byte = utils::position_to_byte_offset(&source, position);
+
+                let file_build = self.get_or_fetch_build(&uri, &file_path, false).await;
+                let project_build = self.ensure_project_cached_build().await;
+                let builds: Vec<&goto::CachedBuild> = file_build
+                    .iter()
+                    .chain(project_build.iter())
+                    .map(|b| b.as_ref())
+                    .collect();
+                let mut changes = {
+                    let text_cache = self.text_cache.read().await;
+                    crate::change_signature::change_signature(
+                        &builds,
+                        &file_path.to_string_lossy(),
+                        byte,
+                        &parameters,
+                        |path| {
+                            let key = Url::from_file_path(path).ok()?.to_string();
+                            match text_cache.get(&key) {
+                                Some((_, text)) => Some(text.clone()),
+                                None => std::fs::read_to_string(path).ok(),
+                            }
+                        },
+                    )
+                };
+                // AST offsets are applied to the buffers, so every open file
+                // touched must have been built from its current text.
+                if let Ok(edits) = &changes {
+                    let open: Vec<Url> = {
+                        let text_cache = self.text_cache.read().await;
+                        std::iter::once(&uri)
+                            .chain(edits.keys())
+                            .filter(|target| text_cache.contains_key(&target.to_string()))
+                            .cloned()
+                            .collect()
+                    };
+                    for target in &open {
+                        if self.current_build(target).await.is_none() {
+                            changes = Err(format!(
+                                "{} has changes that are not built yet; save it and retry",
+                                target.path()
+                            ));
+                            break;
+                        }
+                    }
+                }
+                let changes = match changes {
+                    Ok(changes) => changes,
+                    Err(e) => {
+                        self.client
+                            .show_message(
+                                MessageType::WARNING,
+                                format!("solidity.changeSignature: {e}"),
+                            )
+                            .await;
+                        return Ok(Some(serde_json::json!({ "success": false, "error": e })));
+                    }
+                };
+
+                let edit_count: usize = changes.values().map(|v| v.len()).sum();
+                let file_count = changes.len();
+                let edit = WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                };
+                let applied = self
+                    .client
+                    .apply_edit(edit)
+                    .await
+                    .as_ref()
+                    .is_ok_and(|r| r.applied);
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!(
+                            "solidity.changeSignature: {edit_count} edit(s) in {file_count} file(s), applied={applied}"
+                        ),
+                    )
+                    .await;
+                Ok(Some(serde_json::json!({ "success": applied })))
+            }
+
             _ => Err(tower_lsp::jsonrpc::Error::method_not_found()),
         }
     }
@@ -3365,6 +4459,10 @@ impl LanguageServer for ForgeLsp {
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.did_open(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "file opened")
             .await;
@@ -3467,32 +4565,72 @@ impl LanguageServer for ForgeLsp {
             .log_message(MessageType::INFO, "file changed")
             .await;
 
-        // update text cache
-        if let Some(change) = params.content_changes.into_iter().next() {
-            let has_substantive_content = change.text.chars().any(|ch| !ch.is_whitespace());
-            let mut text_cache = self.text_cache.write().await;
-            text_cache.insert(
-                params.text_document.uri.to_string().into(),
-                (params.text_document.version, change.text),
+        if params.content_changes.is_empty() {
+            return;
+        }
+
+        // Apply the changes in order to the cached text, mirroring each edit
+        // onto the cached tree so the reparse below is incremental.
+        let key: DocumentUri = params.text_document.uri.to_string().into();
+        let mut text_cache = self.text_cache.write().await;
+        let mut syntax_trees = self.syntax_trees.write().await;
+        let mut text = text_cache
+            .get(&key)
+            .map(|(_, text)| text.clone())
+            .unwrap_or_default();
+        let mut tree = syntax_trees
+            .remove(&key)
+            .filter(|doc| doc.matches(&text))
+            .map(|doc| doc.tree);
+        for change in params.content_changes {
+            crate::syntax::apply_content_change(&mut text, &mut tree, change);
+        }
+
+        if let Some(tree) = crate::syntax::reparse(&text, tree.as_ref()) {
+            syntax_trees.insert(
+                key.clone(),
+                crate::syntax::DocumentTree {
+                    tree,
+                    text_hash: crate::syntax::text_hash(&text),
+                },
             );
-            drop(text_cache);
+        }
+        drop(syntax_trees);
 
-            if has_substantive_content {
-                self.pending_create_scaffold
-                    .write()
-                    .await
-                    .remove(params.text_document.uri.as_str());
-            }
+        let has_substantive_content = text.chars().any(|ch| !ch.is_whitespace());
+        text_cache.insert(key, (params.text_document.version, text));
+        drop(text_cache);
+
+        if has_substantive_content {
+            self.pending_create_scaffold
+                .write()
+                .await
+                .remove(params.text_document.uri.as_str());
         }
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.did_save(params).await;
+        }
+
         // did_save is a notification — return to the editor immediately.
         // We route each URI through a dedicated watch channel so that rapid
         // saves collapse: the worker always picks up the *latest* params via
         // `borrow_and_update`, avoiding stale-result races.
         let uri_key = params.text_document.uri.to_string();
 
+        // Saved content may change errors in other files.
+        let saved_version = self.text_cache.read().await.get(&uri_key).map(|(v, _)| *v);
+        match saved_version {
+            Some(version) => {
+                self.project_diagnostics
+                    .invalidate_saved(&uri_key, version)
+                    .await
+            }
+            None => self.project_diagnostics.invalidate().await,
+        }
+
         // Fast path: worker already running for this URI — just send new params.
         {
             let workers = self.did_save_workers.read().await;
@@ -3548,32 +4686,106 @@ impl LanguageServer for ForgeLsp {
             .await;
     }
 
-    async fn formatting(
+    async fn will_save_wait_until(
         &self,
-        params: DocumentFormattingParams,
+        params: WillSaveTextDocumentParams,
     ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
-        self.client
-            .log_message(MessageType::INFO, "formatting request")
-            .await;
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.will_save_wait_until(params).await;
+        }
 
+        let on_save = self.settings.read().await.on_save.clone();
+        if !(on_save.fix_all || on_save.organize_imports || on_save.format) {
+            return Ok(None);
+        }
         let uri = params.text_document.uri;
-        let file_path = match uri.to_file_path() {
-            Ok(path) => path,
-            Err(_) => {
-                self.client
-                    .log_message(MessageType::ERROR, "Invalid file URI for formatting")
-                    .await;
-                return Ok(None);
-            }
-        };
-        let path_str = match file_path.to_str() {
-            Some(s) => s,
-            None => {
-                self.client
-                    .log_message(MessageType::ERROR, "Invalid file path for formatting")
-                    .await;
-                return Ok(None);
-            }
+        let Ok(file_path) = uri.to_file_path() else {
+            return Ok(None);
+        };
+        let Some(original) = self
+            .get_source_bytes(&uri, &file_path)
+            .await
+            .map(|b| String::from_utf8_lossy(&b).into_owned())
+        else {
+            return Ok(None);
+        };
+        let mut text = original.clone();
+
+        // Fixes first: diagnostic ranges refer to the unedited buffer.
+        if on_save.fix_all {
+            match self.fix_all_diagnostics(&uri, &file_path, &text).await {
+                Some(diagnostics) => {
+                    let edits = crate::code_actions::fix_all_edits(
+                        &self.code_action_db,
+                        &text,
+                        &diagnostics,
+                    );
+                    text = crate::file_operations::apply_text_edits(&text, &edits);
+                }
+                None => {
+                    self.client
+                        .log_message(
+                            MessageType::INFO,
+                            "Skipping fix all on save: no diagnostics for the current buffer",
+                        )
+                        .await;
+                }
+            }
+        }
+
+        if on_save.organize_imports
+            && let Some(edits) = self.organize_imports_edits(&uri, &text).await
+        {
+            text = crate::file_operations::apply_text_edits(&text, &edits);
+        }
+
+        if on_save.format
+            && let Some(path_str) = file_path.to_str()
+        {
+            match self.compiler.format(path_str, &text).await {
+                Ok(formatted) => text = formatted,
+                Err(e) => {
+                    self.client
+                        .log_message(MessageType::WARNING, format!("Formatting failed: {e}"))
+                        .await;
+                }
+            }
+        }
+
+        let edits = crate::formatting::minimal_edits(&original, &text);
+        Ok((!edits.is_empty()).then_some(edits))
+    }
+
+    async fn formatting(
+        &self,
+        params: DocumentFormattingParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.formatting(params).await;
+        }
+
+        self.client
+            .log_message(MessageType::INFO, "formatting request")
+            .await;
+
+        let uri = params.text_document.uri;
+        let file_path = match uri.to_file_path() {
+            Ok(path) => path,
+            Err(_) => {
+                self.client
+                    .log_message(MessageType::ERROR, "Invalid file URI for formatting")
+                    .await;
+                return Ok(None);
+            }
+        };
+        let path_str = match file_path.to_str() {
+            Some(s) => s,
+            None => {
+                self.client
+                    .log_message(MessageType::ERROR, "Invalid file path for formatting")
+                    .await;
+                return Ok(None);
+            }
         };
 
         // Get original content
@@ -3595,8 +4807,8 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        // Get formatted content
-        let formatted_content = match self.compiler.format(path_str).await {
+        // Format the buffer, not the file on disk, so unsaved edits survive.
+        let formatted_content = match self.compiler.format(path_str, &original_content).await {
             Ok(content) => content,
             Err(e) => {
                 self.client
@@ -3606,38 +4818,86 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        // If changed, update text_cache with formatted content and return edit
-        if original_content != formatted_content {
-            let end = utils::byte_offset_to_position(&original_content, original_content.len());
+        // The client applies the edits and reports them back through
+        // `didChange`, which updates the text cache.
+        let edits = crate::formatting::minimal_edits(&original_content, &formatted_content);
+        Ok((!edits.is_empty()).then_some(edits))
+    }
 
-            // Update text_cache immediately so goto/hover use the formatted text
-            {
-                let mut text_cache = self.text_cache.write().await;
-                let version = text_cache
-                    .get(&uri.to_string())
-                    .map(|(v, _)| *v)
-                    .unwrap_or(0);
-                text_cache.insert(uri.to_string().into(), (version, formatted_content.clone()));
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.range_formatting(params).await;
+        }
+
+        self.client
+            .log_message(MessageType::INFO, "range formatting request")
+            .await;
+
+        let uri = params.text_document.uri;
+        let Ok(file_path) = uri.to_file_path() else {
+            return Ok(None);
+        };
+        let Some(path_str) = file_path.to_str() else {
+            return Ok(None);
+        };
+        let Some(original) = self
+            .get_source_bytes(&uri, &file_path)
+            .await
+            .map(|b| String::from_utf8_lossy(&b).into_owned())
+        else {
+            return Ok(None);
+        };
+
+        let formatted = match self.compiler.format(path_str, &original).await {
+            Ok(content) => content,
+            Err(e) => {
+                self.client
+                    .log_message(MessageType::WARNING, format!("Formatting failed: {e}"))
+                    .await;
+                return Ok(None);
             }
+        };
 
-            let edit = TextEdit {
-                range: Range {
-                    start: Position::default(),
-                    end,
-                },
-                new_text: formatted_content,
-            };
-            Ok(Some(vec![edit]))
-        } else {
-            Ok(None)
-        }
+        let edits = crate::formatting::range_edits(&original, &formatted, params.range);
+        Ok((!edits.is_empty()).then_some(edits))
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let Some(source) = self
+            .text_cache
+            .read()
+            .await
+            .get(&uri.to_string())
+            .map(|(_, text)| text.clone())
+        else {
+            return Ok(None);
+        };
+
+        let tree = self.syntax_tree(&uri, &source).await;
+        let edits = crate::formatting::on_type_edits(&source, tree.as_ref(), position, &params.ch);
+        Ok((!edits.is_empty()).then_some(edits))
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.did_close(params).await;
+        }
+
         self.flush_project_cache_to_disk("didClose").await;
         let uri = params.text_document.uri.to_string();
         self.ast_cache.write().await.remove(&uri);
         self.text_cache.write().await.remove(&uri);
+        self.syntax_trees.write().await.remove(&uri);
+        self.diagnostics.write().await.remove(&uri);
+        self.diagnostics_versions.write().await.remove(&uri);
         self.completion_cache.write().await.remove(&uri);
         self.client
             .log_message(MessageType::INFO, "file closed, caches cleared.")
@@ -3664,10 +4924,28 @@ impl LanguageServer for ForgeLsp {
             let _ = client.inlay_hint_refresh().await;
         });
     }
-    async fn did_change_workspace_folders(&self, _: DidChangeWorkspaceFoldersParams) {
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
         self.client
-            .log_message(MessageType::INFO, "workdspace folders changed.")
+            .log_message(
+                MessageType::INFO,
+                format!(
+                    "workspace folders changed: {} added, {} removed",
+                    params.event.added.len(),
+                    params.event.removed.len()
+                ),
+            )
             .await;
+
+        for folder in &params.event.removed {
+            if let Ok(path) = folder.uri.to_file_path() {
+                self.remove_workspace_folder(&path).await;
+            }
+        }
+        for folder in &params.event.added {
+            if let Ok(path) = folder.uri.to_file_path() {
+                self.add_workspace_folder(&path).await;
+            }
+        }
     }
 
     async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
@@ -3675,6 +4953,22 @@ impl LanguageServer for ForgeLsp {
             .log_message(MessageType::INFO, "watched files have changed.")
             .await;
 
+        // Solidity files changed on disk (e.g. a branch switch): project-wide
+        // diagnostics are stale.
+        let sol_changed = params.changes.iter().any(|change| {
+            change
+                .uri
+                .to_file_path()
+                .is_ok_and(|p| p.extension().is_some_and(|ext| ext == "sol"))
+        });
+        if sol_changed {
+            for project in self.projects.read().await.iter() {
+                project.project_diagnostics.invalidate().await;
+            }
+            self.project_diagnostics.invalidate().await;
+            self.refresh_pulled_diagnostics().await;
+        }
+
         // Reload configs if foundry.toml or remappings.txt changed.
         for change in &params.changes {
             let path = match change.uri.to_file_path() {
@@ -3685,6 +4979,16 @@ impl LanguageServer for ForgeLsp {
             let filename = path.file_name().and_then(|n| n.to_str());
 
             if filename == Some("foundry.toml") {
+                // Reload the project this foundry.toml belongs to; nested
+                // lib projects are not workspace projects and are skipped.
+                let root = path.parent().unwrap_or(Path::new(""));
+                let Some(project) = self.project_for_path(&path).await else {
+                    continue;
+                };
+                if project.foundry_config.read().await.root != root {
+                    continue;
+                }
+
                 let lint_cfg = config::load_lint_config_from_toml(&path);
                 self.client
                     .log_message(
@@ -3696,7 +5000,7 @@ impl LanguageServer for ForgeLsp {
                         ),
                     )
                     .await;
-                let mut lc = self.lint_config.write().await;
+                let mut lc = project.lint_config.write().await;
                 *lc = lint_cfg;
 
                 let foundry_cfg = config::load_foundry_config_from_toml(&path);
@@ -3718,9 +5022,9 @@ impl LanguageServer for ForgeLsp {
                         )
                         .await;
                 }
-                let mut fc = self.foundry_config.write().await;
+                let mut fc = project.foundry_config.write().await;
                 *fc = foundry_cfg;
-                break;
+                continue;
             }
 
             if filename == Some("remappings.txt") {
@@ -3740,6 +5044,13 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: CompletionParams,
     ) -> tower_lsp::jsonrpc::Result<Option<CompletionResponse>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position.text_document.uri)
+            .await
+        {
+            return project.completion(params).await;
+        }
+
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
 
@@ -3943,6 +5254,13 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: GotoDefinitionParams,
     ) -> tower_lsp::jsonrpc::Result<Option<GotoDefinitionResponse>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.goto_definition(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/definition request")
             .await;
@@ -4194,6 +5512,13 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: request::GotoDeclarationParams,
     ) -> tower_lsp::jsonrpc::Result<Option<request::GotoDeclarationResponse>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.goto_declaration(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/declaration request")
             .await;
@@ -4271,10 +5596,77 @@ impl LanguageServer for ForgeLsp {
         }
     }
 
+    async fn goto_type_definition(
+        &self,
+        params: request::GotoTypeDefinitionParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<request::GotoTypeDefinitionResponse>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.goto_type_definition(params).await;
+        }
+
+        self.client
+            .log_message(MessageType::INFO, "got textDocument/typeDefinition request")
+            .await;
+
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let file_path = match uri.to_file_path() {
+            Ok(path) => path,
+            Err(_) => {
+                self.client
+                    .log_message(MessageType::ERROR, "invalid file uri")
+                    .await;
+                return Ok(None);
+            }
+        };
+
+        let source_bytes = match self.get_source_bytes(&uri, &file_path).await {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        let cached_build = self.get_or_fetch_build(&uri, &file_path, false).await;
+        let cached_build = match cached_build {
+            Some(cb) => cb,
+            None => return Ok(None),
+        };
+
+        if let Some(location) =
+            goto::goto_type_definition_cached(&cached_build, &uri, position, &source_bytes)
+        {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "found type definition at {}:{}",
+                        location.uri, location.range.start.line
+                    ),
+                )
+                .await;
+            Ok(Some(request::GotoTypeDefinitionResponse::Scalar(location)))
+        } else {
+            self.client
+                .log_message(MessageType::INFO, "no type definition found")
+                .await;
+            Ok(None)
+        }
+    }
+
     async fn goto_implementation(
         &self,
         params: request::GotoImplementationParams,
     ) -> tower_lsp::jsonrpc::Result<Option<request::GotoImplementationResponse>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.goto_implementation(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/implementation request")
             .await;
@@ -4366,137 +5758,62 @@ impl LanguageServer for ForgeLsp {
             None => return Ok(None),
         };
 
-        let byte_position = goto::pos_to_bytes(&source_bytes, position);
-        let abs_path = uri.as_ref().strip_prefix("file://").unwrap_or(uri.as_ref());
+        let locations = match self
+            .collect_implementations(&cached_build, &uri, position, &source_bytes)
+            .await
+        {
+            Some(locations) => locations,
+            None => return Ok(None),
+        };
 
-        // Resolve node ID at cursor and follow referencedDeclaration.
-        // Also resolve the target's declaration abs_path + byte_offset for
-        // cross-build re-resolution (node IDs differ across builds).
-        let (target_id, target_decl_abs, target_decl_offset) =
-            match references::byte_to_id(&cached_build.nodes, abs_path, byte_position) {
-                Some(id) => {
-                    let resolved = cached_build
-                        .nodes
-                        .get(abs_path)
-                        .and_then(|f| f.get(&id))
-                        .and_then(|info| info.referenced_declaration)
-                        .unwrap_or(id);
-
-                    // Find the declaration's file and name_location byte offset.
-                    let (decl_abs, decl_offset) = references::resolve_target_location(
-                        &cached_build,
-                        &uri,
-                        position,
-                        &source_bytes,
+        // Fallback: when no baseFunctions/inheritance implementations are found,
+        // behave like goto-definition so the user always lands somewhere useful.
+        if locations.is_empty() {
+            if let Some(location) =
+                goto::goto_declaration_cached(&cached_build, &uri, position, &source_bytes)
+            {
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        "no implementations found, falling back to definition",
                     )
-                    .unwrap_or_else(|| (abs_path.to_string(), byte_position));
+                    .await;
+                return Ok(Some(request::GotoImplementationResponse::Scalar(location)));
+            }
 
-                    (resolved, decl_abs, decl_offset)
-                }
-                None => return Ok(None),
-            };
+            self.client
+                .log_message(MessageType::INFO, "no implementations found")
+                .await;
+            return Ok(None);
+        }
 
-        // Collect all builds to search.
-        let project_build = self.ensure_project_cached_build().await;
-        let sub_caches = self.sub_caches.read().await;
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("found {} implementation(s)", locations.len()),
+            )
+            .await;
 
-        let mut builds: Vec<&goto::CachedBuild> = vec![&cached_build];
-        if let Some(ref pb) = project_build {
-            builds.push(pb);
-        }
-        for sc in sub_caches.iter() {
-            builds.push(sc);
+        if locations.len() == 1 {
+            Ok(Some(request::GotoImplementationResponse::Scalar(
+                locations.into_iter().next().unwrap(),
+            )))
+        } else {
+            Ok(Some(request::GotoImplementationResponse::Array(locations)))
         }
+    }
 
-        // For each build, re-resolve the target by byte offset (stable across
-        // compilations), then look up base_function_implementation with the
-        // build-local node ID.  Collect (impl_id, build_ref) pairs so we can
-        // resolve locations within the same build that produced the ID.
-        let mut locations: Vec<Location> = Vec::new();
-        let mut seen_positions: Vec<(String, u32, u32)> = Vec::new(); // (uri, line, char)
+    async fn references(
+        &self,
+        params: ReferenceParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<Location>>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position.text_document.uri)
+            .await
+        {
+            return project.references(params).await;
+        }
 
-        for build in &builds {
-            // Re-resolve target in this build's node-ID space.
-            let local_target =
-                references::byte_to_id(&build.nodes, &target_decl_abs, target_decl_offset).or_else(
-                    || {
-                        // If the declaration file isn't in this build, try the original ID.
-                        if build.nodes.values().any(|f| f.contains_key(&target_id)) {
-                            Some(target_id)
-                        } else {
-                            None
-                        }
-                    },
-                );
-
-            let Some(local_id) = local_target else {
-                continue;
-            };
-
-            // Look up equivalents in this build.
-            let Some(impls) = build.base_function_implementation.get(&local_id) else {
-                continue;
-            };
-
-            for &impl_id in impls {
-                if let Some(loc) =
-                    references::id_to_location(&build.nodes, &build.id_to_path_map, impl_id)
-                {
-                    // Dedup by source position.
-                    let key = (
-                        loc.uri.to_string(),
-                        loc.range.start.line,
-                        loc.range.start.character,
-                    );
-                    if !seen_positions.contains(&key) {
-                        seen_positions.push(key);
-                        locations.push(loc);
-                    }
-                }
-            }
-        }
-
-        // Fallback: when no baseFunctions/inheritance implementations are found,
-        // behave like goto-definition so the user always lands somewhere useful.
-        if locations.is_empty() {
-            if let Some(location) =
-                goto::goto_declaration_cached(&cached_build, &uri, position, &source_bytes)
-            {
-                self.client
-                    .log_message(
-                        MessageType::INFO,
-                        "no implementations found, falling back to definition",
-                    )
-                    .await;
-                return Ok(Some(request::GotoImplementationResponse::Scalar(location)));
-            }
-
-            self.client
-                .log_message(MessageType::INFO, "no implementations found")
-                .await;
-            return Ok(None);
-        }
-
-        self.client
-            .log_message(
-                MessageType::INFO,
-                format!("found {} implementation(s)", locations.len()),
-            )
-            .await;
-
-        if locations.len() == 1 {
-            Ok(Some(request::GotoImplementationResponse::Scalar(
-                locations.into_iter().next().unwrap(),
-            )))
-        } else {
-            Ok(Some(request::GotoImplementationResponse::Array(locations)))
-        }
-    }
-
-    async fn references(
-        &self,
-        params: ReferenceParams,
-    ) -> tower_lsp::jsonrpc::Result<Option<Vec<Location>>> {
         self.client
             .log_message(MessageType::INFO, "Got a textDocument/references request")
             .await;
@@ -4656,59 +5973,16 @@ impl LanguageServer for ForgeLsp {
             }
         }
 
-        // Always resolve target/local references from the current file build.
-        // This avoids stale/partial project-cache misses immediately after edits.
-        let mut locations = references::goto_references_cached(
-            &file_build,
-            &uri,
-            position,
-            &source_bytes,
-            None,
-            params.context.include_declaration,
-        );
-
-        // Cross-file: resolve target from current file, then expand in project cache.
-        // Exclude the current file from the project-cache scan — the file-level
-        // build already covers it with correct (freshly compiled) byte offsets.
-        // The project cache may have stale offsets if the file was edited since
-        // the last full index, which would produce duplicate bogus locations.
-        if let Some((def_abs_path, def_byte_offset)) =
-            references::resolve_target_location(&file_build, &uri, position, &source_bytes)
-        {
-            if let Some(project_build) = project_build {
-                let other_locations = references::goto_references_for_target(
-                    &project_build,
-                    &def_abs_path,
-                    def_byte_offset,
-                    None,
-                    params.context.include_declaration,
-                    Some(&current_abs),
-                );
-                locations.extend(other_locations);
-            }
-
-            // Search sub-project caches for references in lib test files.
-            // Each sub-cache has its own node ID space; byte_to_id matches
-            // the target declaration by absolute file path + byte offset.
-            // No exclusion needed — sub-caches don't contain the current file.
-            let sub_caches = self.sub_caches.read().await;
-            for sub_cache in sub_caches.iter() {
-                let sub_locations = references::goto_references_for_target(
-                    sub_cache,
-                    &def_abs_path,
-                    def_byte_offset,
-                    None,
-                    params.context.include_declaration,
-                    None,
-                );
-                locations.extend(sub_locations);
-            }
-        }
-
-        // Deduplicate across all caches — removes exact duplicates and
-        // contained-range duplicates (e.g., UserDefinedTypeName full-span
-        // vs IdentifierPath name-only span for qualified type paths).
-        locations = references::dedup_locations(locations);
+        let locations = self
+            .collect_references(
+                &file_build,
+                project_build.as_deref(),
+                &uri,
+                position,
+                &source_bytes,
+                params.context.include_declaration,
+            )
+            .await;
 
         self.client
             .log_message(
@@ -4723,6 +5997,10 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: TextDocumentPositionParams,
     ) -> tower_lsp::jsonrpc::Result<Option<PrepareRenameResponse>> {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.prepare_rename(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/prepareRename request")
             .await;
@@ -4745,7 +6023,7 @@ impl LanguageServer for ForgeLsp {
             None => return Ok(None),
         };
 
-        if let Some(range) = rename::get_identifier_range(&source_bytes, position) {
+        if let Some(range) = rename::prepare_rename_range(&source_bytes, position) {
             self.client
                 .log_message(
                     MessageType::INFO,
@@ -4768,6 +6046,13 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: RenameParams,
     ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position.text_document.uri)
+            .await
+        {
+            return project.rename(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/rename request")
             .await;
@@ -4846,7 +6131,7 @@ impl LanguageServer for ForgeLsp {
             &uri,
             position,
             &source_bytes,
-            new_name,
+            new_name.clone(),
             &other_refs,
             &text_buffers,
         ) {
@@ -4870,6 +6155,18 @@ impl LanguageServer for ForgeLsp {
                     )
                     .await;
 
+                let workspace_edit = self
+                    .with_contract_file_rename(
+                        &cached_build,
+                        &uri,
+                        position,
+                        &source_bytes,
+                        &new_name,
+                        &text_buffers,
+                        workspace_edit,
+                    )
+                    .await;
+
                 // Return the full WorkspaceEdit to the client so the editor
                 // applies all changes (including cross-file renames) via the
                 // LSP protocol. This keeps undo working and avoids writing
@@ -4953,7 +6250,10 @@ impl LanguageServer for ForgeLsp {
             },
         };
 
-        let symbols = symbols::extract_document_symbols(&source);
+        let symbols = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => symbols::extract_document_symbols_with_tree(&tree, &source),
+            None => vec![],
+        };
         self.client
             .log_message(
                 MessageType::INFO,
@@ -4996,7 +6296,10 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        let highlights = highlight::document_highlights(&source, position);
+        let highlights = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => highlight::document_highlights_with_tree(&tree, &source, position),
+            None => vec![],
+        };
         self.client
             .log_message(
                 MessageType::INFO,
@@ -5007,6 +6310,13 @@ impl LanguageServer for ForgeLsp {
     }
 
     async fn hover(&self, params: HoverParams) -> tower_lsp::jsonrpc::Result<Option<Hover>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.hover(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/hover request")
             .await;
@@ -5054,6 +6364,13 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: SignatureHelpParams,
     ) -> tower_lsp::jsonrpc::Result<Option<SignatureHelp>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.signature_help(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/signatureHelp request")
             .await;
@@ -5159,7 +6476,10 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        let mut tokens = semantic_tokens::semantic_tokens_full(&source);
+        let mut tokens = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => semantic_tokens::semantic_tokens_full_with_tree(&tree, &source),
+            None => semantic_tokens::semantic_tokens_full(&source),
+        };
 
         // Generate a unique result_id and cache the tokens for delta requests
         let id = self.semantic_token_id.fetch_add(1, Ordering::Relaxed);
@@ -5206,8 +6526,17 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        let tokens =
-            semantic_tokens::semantic_tokens_range(&source, range.start.line, range.end.line);
+        let tokens = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => semantic_tokens::semantic_tokens_range_with_tree(
+                &tree,
+                &source,
+                range.start.line,
+                range.end.line,
+            ),
+            None => {
+                semantic_tokens::semantic_tokens_range(&source, range.start.line, range.end.line)
+            }
+        };
 
         Ok(Some(SemanticTokensRangeResult::Tokens(tokens)))
     }
@@ -5245,7 +6574,10 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        let mut new_tokens = semantic_tokens::semantic_tokens_full(&source);
+        let mut new_tokens = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => semantic_tokens::semantic_tokens_full_with_tree(&tree, &source),
+            None => semantic_tokens::semantic_tokens_full(&source),
+        };
 
         // Generate a new result_id
         let id = self.semantic_token_id.fetch_add(1, Ordering::Relaxed);
@@ -5319,7 +6651,10 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        let ranges = folding::folding_ranges(&source);
+        let ranges = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => folding::folding_ranges_with_tree(&tree, &source),
+            None => vec![],
+        };
         self.client
             .log_message(
                 MessageType::INFO,
@@ -5358,7 +6693,10 @@ impl LanguageServer for ForgeLsp {
             }
         };
 
-        let ranges = selection::selection_ranges(&source, &params.positions);
+        let ranges = match self.syntax_tree(&uri, &source).await {
+            Some(tree) => selection::selection_ranges_with_tree(&tree, &source, &params.positions),
+            None => selection::selection_ranges(&source, &params.positions),
+        };
         self.client
             .log_message(
                 MessageType::INFO,
@@ -5372,6 +6710,10 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: InlayHintParams,
     ) -> tower_lsp::jsonrpc::Result<Option<Vec<InlayHint>>> {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.inlay_hint(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got textDocument/inlayHint request")
             .await;
@@ -5400,13 +6742,29 @@ impl LanguageServer for ForgeLsp {
             None => return Ok(None),
         };
 
-        let mut hints = inlay_hints::inlay_hints(&cached_build, &uri, range, &source_bytes);
-
-        // Filter hints based on settings.
-        let settings = self.settings.read().await;
-        if !settings.inlay_hints.parameters {
-            hints.retain(|h| h.kind != Some(InlayHintKind::PARAMETER));
-        }
+        let live_source = String::from_utf8_lossy(&source_bytes);
+        let Some(tree) = self.syntax_tree(&uri, &live_source).await else {
+            return Ok(Some(vec![]));
+        };
+        let other_builds: Vec<Arc<goto::CachedBuild>> = {
+            let cache = self.ast_cache.read().await;
+            cache
+                .iter()
+                .filter(|(key, _)| key.as_str() != uri.as_str())
+                .map(|(_, v)| v.clone())
+                .collect()
+        };
+        let other_refs: Vec<&goto::CachedBuild> = other_builds.iter().map(|v| v.as_ref()).collect();
+        let settings = self.settings.read().await.inlay_hints.clone();
+        let hints = inlay_hints::inlay_hints(
+            &cached_build,
+            &other_refs,
+            &uri,
+            range,
+            &source_bytes,
+            &tree,
+            &settings,
+        );
         self.client
             .log_message(
                 MessageType::INFO,
@@ -5422,10 +6780,207 @@ impl LanguageServer for ForgeLsp {
         Ok(Some(hints))
     }
 
+    async fn code_lens(
+        &self,
+        params: CodeLensParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<CodeLens>>> {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.code_lens(params).await;
+        }
+
+        self.client
+            .log_message(MessageType::INFO, "got textDocument/codeLens request")
+            .await;
+
+        let uri = params.text_document.uri;
+        let file_path = match uri.to_file_path() {
+            Ok(path) => path,
+            Err(_) => {
+                self.client
+                    .log_message(MessageType::ERROR, "invalid file uri")
+                    .await;
+                return Ok(None);
+            }
+        };
+
+        let source_bytes = match self.get_source_bytes(&uri, &file_path).await {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        let cached_build = self.get_or_fetch_build(&uri, &file_path, false).await;
+        let cached_build = match cached_build {
+            Some(cb) => cb,
+            None => return Ok(Some(vec![])),
+        };
+
+        let lenses = crate::code_lens::code_lenses(&cached_build, &uri, &source_bytes);
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("found {} code lenses", lenses.len()),
+            )
+            .await;
+        Ok(Some(lenses))
+    }
+
+    async fn code_lens_resolve(&self, lens: CodeLens) -> tower_lsp::jsonrpc::Result<CodeLens> {
+        let Some(data) = lens
+            .data
+            .clone()
+            .and_then(|d| serde_json::from_value::<crate::code_lens::CodeLensData>(d).ok())
+        else {
+            return Ok(lens);
+        };
+        if let Some(project) = self.route_uri(&data.uri).await {
+            return project.code_lens_resolve(lens).await;
+        }
+
+        let Ok(file_path) = data.uri.to_file_path() else {
+            return Ok(lens);
+        };
+        let Some(source_bytes) = self.get_source_bytes(&data.uri, &file_path).await else {
+            return Ok(lens);
+        };
+        let Some(file_build) = self.get_or_fetch_build(&data.uri, &file_path, false).await else {
+            return Ok(lens);
+        };
+
+        let locations = match data.kind {
+            crate::code_lens::CodeLensKind::References => {
+                let project_build = self.ensure_project_cached_build().await;
+                self.collect_references(
+                    &file_build,
+                    project_build.as_deref(),
+                    &data.uri,
+                    data.position,
+                    &source_bytes,
+                    false,
+                )
+                .await
+            }
+            crate::code_lens::CodeLensKind::Implementations => self
+                .collect_implementations(&file_build, &data.uri, data.position, &source_bytes)
+                .await
+                .unwrap_or_default(),
+        };
+
+        Ok(crate::code_lens::resolve_code_lens(lens, &data, locations))
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> tower_lsp::jsonrpc::Result<DocumentDiagnosticReportResult> {
+        self.client
+            .log_message(MessageType::INFO, "got textDocument/diagnostic request")
+            .await;
+
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.diagnostic(params).await;
+        }
+
+        let uri = params.text_document.uri;
+        let recorded = self.diagnostics.read().await.get(&uri.to_string()).cloned();
+        let diagnostics = match recorded {
+            Some(diags) => diags,
+            // Not compiled on its own yet: fall back to the last project compile.
+            None => match uri.to_file_path() {
+                Ok(path) => self
+                    .project_diagnostics
+                    .get_file(&path)
+                    .await
+                    .unwrap_or_default(),
+                Err(_) => vec![],
+            },
+        };
+
+        Ok(DocumentDiagnosticReportResult::Report(
+            crate::pull_diagnostics::document_report(
+                diagnostics,
+                params.previous_result_id.as_deref(),
+            ),
+        ))
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> tower_lsp::jsonrpc::Result<WorkspaceDiagnosticReportResult> {
+        self.client
+            .log_message(MessageType::INFO, "got workspace/diagnostic request")
+            .await;
+
+        let previous: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|p| (p.uri, p.value))
+            .collect();
+
+        // Not routed: the report spans every registered project, each one
+        // compiled under its own scope.
+        let mut projects = self.projects.read().await.clone();
+        if projects.is_empty() {
+            projects.push(self.project_state());
+        }
+        let mut project = ProjectDiagnostics::new();
+        for state in &projects {
+            let this = self.with_project(state);
+            project.extend(this.project_diagnostics().await);
+            // Report every project source file so files that no longer have
+            // errors are cleared, not just the ones solc complained about.
+            let foundry_config = this.foundry_config.read().await.clone();
+            for path in crate::solc::discover_source_files(&foundry_config) {
+                project.entry(path).or_default();
+            }
+        }
+
+        let recorded = self.diagnostics.read().await.clone();
+        let versions: HashMap<DocumentUri, i32> = self
+            .text_cache
+            .read()
+            .await
+            .iter()
+            .map(|(uri, (version, _))| (uri.clone(), *version))
+            .collect();
+
+        let mut items = Vec::with_capacity(project.len());
+        for (path, project_diags) in project {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let key = uri.to_string();
+            // Open documents report what their own build produced, which
+            // also includes lint and NatSpec diagnostics.
+            let diagnostics = recorded.get(&key).cloned().unwrap_or(project_diags);
+            let version = versions.get(&key).map(|v| i64::from(*v));
+            items.push(crate::pull_diagnostics::workspace_report(
+                uri,
+                version,
+                diagnostics,
+                &previous,
+            ));
+        }
+
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("workspace diagnostics: {} files", items.len()),
+            )
+            .await;
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
+        ))
+    }
+
     async fn code_action(
         &self,
         params: CodeActionParams,
     ) -> tower_lsp::jsonrpc::Result<Option<CodeActionResponse>> {
+        if let Some(project) = self.route_uri(&params.text_document.uri).await {
+            return project.code_action(params).await;
+        }
+
         use crate::code_actions::FixKind;
 
         let uri = &params.text_document.uri;
@@ -5441,8 +6996,42 @@ impl LanguageServer for ForgeLsp {
 
         let db = &self.code_action_db;
         let mut actions: Vec<CodeActionOrCommand> = Vec::new();
+        let mut natspec_items: HashSet<(u32, u32)> = HashSet::new();
+        // Import candidates for 7576/7920, loaded on first use:
+        // (project completion cache, project root, remappings).
+        let mut auto_import: Option<AutoImportContext> = None;
+        // Build of the current buffer for cross-file fixes, made on first use.
+        let mut cross_file_build: Option<Option<Arc<goto::CachedBuild>>> = None;
 
         for diag in &params.context.diagnostics {
+            // ── NatSpec skeleton ──────────────────────────────────────────────
+            if diag.source.as_deref() == Some(crate::natspec::NATSPEC_SOURCE) {
+                let item_end = diag.data.clone().and_then(|d| {
+                    serde_json::from_value::<crate::natspec::NatspecDiagnosticData>(d).ok()
+                });
+                if let Some(item_end) = item_end.map(|d| d.item_end)
+                    && natspec_items.insert((item_end.line, item_end.character))
+                    && let Some((title, edit)) = source
+                        .as_deref()
+                        .and_then(|src| crate::natspec::skeleton_edit(src, item_end))
+                {
+                    let mut changes = HashMap::new();
+                    changes.insert(uri.clone(), vec![edit]);
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title,
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diag.clone()]),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        }),
+                        is_preferred: Some(true),
+                        ..Default::default()
+                    }));
+                }
+                continue;
+            }
+
             // ── forge-lint string codes ───────────────────────────────────────
             if let Some(NumberOrString::String(s)) = &diag.code {
                 if s == "unused-import" {
@@ -5486,139 +7075,338 @@ impl LanguageServer for ForgeLsp {
             if let Some(def) = db.get(&code) {
                 // Build the TextEdit from the fix kind.
                 let edit_opt: Option<TextEdit> = match &def.fix {
-                    FixKind::Insert { text, anchor: _ } => {
-                        // InsertAtFileStart is the only anchor for now.
-                        goto::code_action_edit(
-                            source.as_deref().unwrap_or(""),
-                            diag.range,
-                            goto::CodeActionKind::InsertAtFileStart { text },
-                        )
-                    }
-
-                    FixKind::ReplaceToken {
-                        replacement,
-                        walk_to,
-                    } => source.as_deref().and_then(|src| {
-                        goto::code_action_edit(
-                            src,
-                            diag.range,
-                            goto::CodeActionKind::ReplaceToken {
-                                replacement,
-                                walk_to: walk_to.as_deref(),
-                            },
-                        )
-                    }),
-
-                    FixKind::DeleteToken => source.as_deref().and_then(|src| {
-                        goto::code_action_edit(src, diag.range, goto::CodeActionKind::DeleteToken)
-                    }),
-
-                    FixKind::DeleteNode { node_kind } => {
-                        // Only variable_declaration_statement supported so far.
-                        if node_kind == "variable_declaration_statement" {
-                            source.as_deref().and_then(|src| {
-                                goto::code_action_edit(
+                    // Build-aware fixes may edit several files, so they emit
+                    // their own multi-file action.
+                    FixKind::CrossFile { fix } => {
+                        if cross_file_build.is_none() {
+                            cross_file_build = Some(self.buffer_build(uri).await);
+                        }
+                        let build = cross_file_build.clone().flatten();
+                        let changes = match (source.as_deref(), build) {
+                            (Some(src), Some(build)) => {
+                                let text_cache = self.text_cache.read().await;
+                                crate::code_actions::cross_file_edits(
+                                    *fix,
+                                    &build,
+                                    uri,
                                     src,
                                     diag.range,
-                                    goto::CodeActionKind::DeleteLocalVar,
+                                    |path| {
+                                        let key = Url::from_file_path(path).ok()?.to_string();
+                                        match text_cache.get(&key) {
+                                            Some((_, text)) => Some(text.clone()),
+                                            None => std::fs::read_to_string(path).ok(),
+                                        }
+                                    },
                                 )
-                            })
-                        } else {
-                            None
+                            }
+                            _ => None,
+                        };
+                        if let Some(changes) = changes {
+                            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: def.title.clone(),
+                                kind: Some(CodeActionKind::QUICKFIX),
+                                diagnostics: Some(vec![diag.clone()]),
+                                edit: Some(WorkspaceEdit {
+                                    changes: Some(changes),
+                                    ..Default::default()
+                                }),
+                                is_preferred: Some(true),
+                                ..Default::default()
+                            }));
                         }
+                        continue;
                     }
 
-                    FixKind::DeleteChildNode {
-                        walk_to,
-                        child_kinds,
-                    } => {
-                        let ck: Vec<&str> = child_kinds.iter().map(|s| s.as_str()).collect();
-                        source.as_deref().and_then(|src| {
-                            goto::code_action_edit(
-                                src,
-                                diag.range,
-                                goto::CodeActionKind::DeleteChildNode {
-                                    walk_to,
-                                    child_kinds: &ck,
-                                },
-                            )
-                        })
+                    // Custom fixes are handled below.
+                    FixKind::Custom => None,
+
+                    fix => crate::code_actions::fix_edit(
+                        fix,
+                        source.as_deref().unwrap_or(""),
+                        diag.range,
+                    ),
+                };
+
+                if let Some(edit) = edit_opt {
+                    let mut changes = HashMap::new();
+                    changes.insert(uri.clone(), vec![edit]);
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: def.title.clone(),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diag.clone()]),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        }),
+                        is_preferred: Some(true),
+                        ..Default::default()
+                    }));
+                    continue; // handled — skip custom fallback
+                }
+
+                // If it's not Custom and edit_opt was None, the TS lookup failed
+                // (e.g. file not parseable). Nothing to emit for this diagnostic.
+                if !matches!(def.fix, FixKind::Custom) {
+                    continue;
+                }
+            }
+
+            // ── Custom / hand-written fallback ────────────────────────────────
+            // Only reaches here for codes with kind=custom or codes not in the DB.
+            // Add bespoke arms here as needed.
+            match code.0 {
+                // 7576 — undeclared identifier.
+                // 7920 — identifier not found or not unique.
+                // Offer one import per file that declares the name at top level.
+                7576 | 7920 => {
+                    let (Some(src), Ok(current_file)) = (source.as_deref(), uri.to_file_path())
+                    else {
+                        continue;
+                    };
+                    let start = utils::position_to_byte_offset(src, diag.range.start);
+                    let end = utils::position_to_byte_offset(src, diag.range.end);
+                    // `Foo.Bar` paths are reported as a whole; import the head.
+                    let Some(name) = src
+                        .get(start..end)
+                        .and_then(|text| text.split('.').next())
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                    else {
+                        continue;
+                    };
+
+                    if auto_import.is_none() {
+                        let cache = match self.project_cache_key().await {
+                            Some(root_key) => self
+                                .ast_cache
+                                .read()
+                                .await
+                                .get(&root_key)
+                                .map(|build| build.completion_cache.clone()),
+                            None => None,
+                        };
+                        let cache = match cache {
+                            Some(c) => Some(c),
+                            None => self
+                                .completion_cache
+                                .read()
+                                .await
+                                .get(&uri.to_string())
+                                .cloned(),
+                        };
+                        let config = self.foundry_config_for_file(&current_file).await;
+                        let remappings = crate::solc::resolve_remappings(&config).await;
+                        auto_import = Some((cache, config.root, remappings));
                     }
+                    let Some((Some(cache), project_root, remappings)) = &auto_import else {
+                        continue;
+                    };
 
-                    FixKind::ReplaceChildNode {
-                        walk_to,
-                        child_kind,
-                        replacement,
-                    } => source.as_deref().and_then(|src| {
-                        goto::code_action_edit(
-                            src,
-                            diag.range,
-                            goto::CodeActionKind::ReplaceChildNode {
-                                walk_to,
-                                child_kind,
-                                replacement,
-                            },
-                        )
-                    }),
+                    let edits = completion::auto_import_edits(
+                        cache,
+                        name,
+                        &current_file,
+                        src,
+                        project_root,
+                        remappings,
+                    );
+                    let preferred = edits.len() == 1;
+                    for (import_path, edit) in edits {
+                        let mut changes = HashMap::new();
+                        changes.insert(uri.clone(), vec![edit]);
+                        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                            title: format!("Import {{{name}}} from \"{import_path}\""),
+                            kind: Some(CodeActionKind::QUICKFIX),
+                            diagnostics: Some(vec![diag.clone()]),
+                            edit: Some(WorkspaceEdit {
+                                changes: Some(changes),
+                                ..Default::default()
+                            }),
+                            is_preferred: Some(preferred),
+                            ..Default::default()
+                        }));
+                    }
+                }
+                // 3656 — contract should be marked as abstract.
+                // Insert stubs for every inherited function left unimplemented.
+                3656 => {
+                    let Some(src) = source.as_deref() else {
+                        continue;
+                    };
+                    let byte = utils::position_to_byte_offset(src, diag.range.start);
+                    if let Some(action) = self
+                        .implement_missing_functions_action(uri, src, byte, None)
+                        .await
+                    {
+                        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                            kind: Some(CodeActionKind::QUICKFIX),
+                            diagnostics: Some(vec![diag.clone()]),
+                            is_preferred: Some(true),
+                            ..action
+                        }));
+                    }
+                }
+                // 2018 — state mutability can be restricted to pure/view.
+                // The replacement (pure vs view) is embedded in the diagnostic message.
+                // 9456 — missing `override` specifier.
+                // Needs a TS walk to find the right insertion point in the modifier list.
+                _ => {}
+            }
+        }
 
-                    FixKind::InsertBeforeNode {
-                        walk_to,
-                        before_child,
-                        text,
-                    } => {
-                        let bc: Vec<&str> = before_child.iter().map(|s| s.as_str()).collect();
-                        source.as_deref().and_then(|src| {
-                            goto::code_action_edit(
-                                src,
-                                diag.range,
-                                goto::CodeActionKind::InsertBeforeNode {
-                                    walk_to,
-                                    before_child: &bc,
-                                    text,
-                                },
-                            )
-                        })
+        // ── Refactors at the cursor ───────────────────────────────────────────
+        let wants_refactor = params.context.only.as_ref().is_none_or(|only| {
+            only.iter()
+                .any(|kind| kind.as_str().starts_with(CodeActionKind::REFACTOR.as_str()))
+        });
+        if wants_refactor && let Some(src) = source.as_deref() {
+            let byte = utils::position_to_byte_offset(src, params.range.start);
+            if let Some((_, base)) = crate::missing_functions::inheritance_at(src, byte)
+                && let Some(action) = self
+                    .implement_missing_functions_action(uri, src, byte, Some(&base))
+                    .await
+            {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    kind: Some(CodeActionKind::REFACTOR),
+                    ..action
+                }));
+            }
+
+            // Extractions and inlining resolve identifiers through the AST,
+            // so they need a build of the current buffer.
+            let build = self.current_build(uri).await;
+            let tree = match build {
+                Some(_) => self.syntax_tree(uri, src).await,
+                None => None,
+            };
+            if let (Some(build), Some(tree)) = (&build, &tree)
+                && let Some(action) =
+                    crate::inline::inline_at(src, tree, build, uri, params.range.start)
+            {
+                let (edit, disabled) = match action.edits {
+                    Ok(edits) => {
+                        let mut changes = HashMap::new();
+                        changes.insert(uri.clone(), edits);
+                        let edit = WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        };
+                        (Some(edit), None)
                     }
-
-                    // Custom fixes are handled below.
-                    FixKind::Custom => None,
+                    Err(reason) => (None, Some(CodeActionDisabled { reason })),
                 };
-
-                if let Some(edit) = edit_opt {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: action.title,
+                    kind: Some(CodeActionKind::REFACTOR_INLINE),
+                    edit,
+                    disabled,
+                    ..Default::default()
+                }));
+            }
+            if params.range.start != params.range.end
+                && let (Some(build), Some(tree)) = (&build, &tree)
+                && let Ok(path) = uri.to_file_path()
+                && let Some(path_str) = path.to_str()
+                && let Some(file_nodes) = build.nodes.get(
+                    build
+                        .path_to_abs
+                        .get(path_str)
+                        .map(|p| p.as_str())
+                        .unwrap_or(path_str),
+                )
+            {
+                let extractions = [
+                    (
+                        "Extract to function",
+                        "refactor.extract.function",
+                        crate::extract::extract_function(src, tree, file_nodes, params.range),
+                    ),
+                    (
+                        "Extract to local variable",
+                        "refactor.extract.variable",
+                        crate::extract::extract_variable(src, tree, file_nodes, params.range),
+                    ),
+                ];
+                for (title, kind, edits) in extractions {
+                    let Some(edits) = edits else {
+                        continue;
+                    };
                     let mut changes = HashMap::new();
-                    changes.insert(uri.clone(), vec![edit]);
+                    changes.insert(uri.clone(), edits);
                     actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-                        title: def.title.clone(),
-                        kind: Some(CodeActionKind::QUICKFIX),
-                        diagnostics: Some(vec![diag.clone()]),
+                        title: title.to_string(),
+                        kind: Some(CodeActionKind::new(kind)),
                         edit: Some(WorkspaceEdit {
                             changes: Some(changes),
                             ..Default::default()
                         }),
-                        is_preferred: Some(true),
                         ..Default::default()
                     }));
-                    continue; // handled — skip custom fallback
                 }
+            }
 
-                // If it's not Custom and edit_opt was None, the TS lookup failed
-                // (e.g. file not parseable). Nothing to emit for this diagnostic.
-                if !matches!(def.fix, FixKind::Custom) {
-                    continue;
+            let tree = match tree {
+                Some(tree) => Some(tree),
+                None => self.syntax_tree(uri, src).await,
+            };
+            if let Some(tree) = &tree
+                && crate::custom_errors::has_string_errors(src, tree)
+                && let Ok(path) = uri.to_file_path()
+                && let Some(require_errors) = self.custom_error_support(&path, src).await
+            {
+                let byte = utils::position_to_byte_offset(src, params.range.start);
+                let path_str = path.to_str().unwrap_or_default();
+                let abs_path = build
+                    .as_ref()
+                    .and_then(|build| build.path_to_abs.get(path_str))
+                    .map_or(path_str, |p| p.as_str());
+                for conversion in crate::custom_errors::conversions_at(
+                    src,
+                    tree,
+                    byte,
+                    require_errors,
+                    build.as_deref(),
+                    abs_path,
+                ) {
+                    let mut changes = HashMap::new();
+                    changes.insert(uri.clone(), conversion.edits);
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: conversion.title,
+                        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }));
                 }
             }
+        }
 
-            // ── Custom / hand-written fallback ────────────────────────────────
-            // Only reaches here for codes with kind=custom or codes not in the DB.
-            // Add bespoke arms here as needed.
-            #[allow(clippy::match_single_binding)]
-            match code {
-                // 2018 — state mutability can be restricted to pure/view.
-                // The replacement (pure vs view) is embedded in the diagnostic message.
-                // 9456 — missing `override` specifier.
-                // Needs a TS walk to find the right insertion point in the modifier list.
-                _ => {}
-            }
+        // ── Source actions (only when asked for) ──────────────────────────────
+        let wants_organize = params.context.only.as_ref().is_some_and(|only| {
+            only.iter().any(|kind| {
+                CodeActionKind::SOURCE_ORGANIZE_IMPORTS
+                    .as_str()
+                    .starts_with(kind.as_str())
+            })
+        });
+        if wants_organize
+            && let Some(src) = source.as_deref()
+            && let Some(edits) = self.organize_imports_edits(uri, src).await
+        {
+            let mut changes = HashMap::new();
+            changes.insert(uri.clone(), edits);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Organize imports".to_string(),
+                kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
         }
 
         Ok(Some(actions))
@@ -5628,6 +7416,15 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: RenameFilesParams,
     ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        if let Some(uri) = params
+            .files
+            .first()
+            .and_then(|f| Url::parse(&f.old_uri).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.will_rename_files(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -5820,6 +7617,15 @@ impl LanguageServer for ForgeLsp {
     }
 
     async fn did_rename_files(&self, params: RenameFilesParams) {
+        if let Some(uri) = params
+            .files
+            .first()
+            .and_then(|f| Url::parse(&f.old_uri).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.did_rename_files(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -6006,6 +7812,12 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: DeleteFilesParams,
     ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        if let Some(uri) = params.files.first().and_then(|f| Url::parse(&f.uri).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.will_delete_files(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -6179,6 +7991,12 @@ impl LanguageServer for ForgeLsp {
     }
 
     async fn did_delete_files(&self, params: DeleteFilesParams) {
+        if let Some(uri) = params.files.first().and_then(|f| Url::parse(&f.uri).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.did_delete_files(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -6248,9 +8066,7 @@ impl LanguageServer for ForgeLsp {
             .await;
 
         for uri in &deleted_uris {
-            self.client
-                .publish_diagnostics(uri.clone(), vec![], None)
-                .await;
+            self.publish_diagnostics(uri.clone(), vec![]).await;
         }
 
         let mut removed_text = 0usize;
@@ -6379,6 +8195,12 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: CreateFilesParams,
     ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        if let Some(uri) = params.files.first().and_then(|f| Url::parse(&f.uri).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.will_create_files(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -6410,6 +8232,12 @@ impl LanguageServer for ForgeLsp {
     }
 
     async fn did_create_files(&self, params: CreateFilesParams) {
+        if let Some(uri) = params.files.first().and_then(|f| Url::parse(&f.uri).ok())
+            && let Some(project) = self.route_uri(&uri).await
+        {
+            return project.did_create_files(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -6675,6 +8503,13 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: CallHierarchyPrepareParams,
     ) -> tower_lsp::jsonrpc::Result<Option<Vec<CallHierarchyItem>>> {
+        if let Some(project) = self
+            .route_uri(&params.text_document_position_params.text_document.uri)
+            .await
+        {
+            return project.prepare_call_hierarchy(params).await;
+        }
+
         self.client
             .log_message(
                 MessageType::INFO,
@@ -6782,6 +8617,10 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: CallHierarchyIncomingCallsParams,
     ) -> tower_lsp::jsonrpc::Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        if let Some(project) = self.route_uri(&params.item.uri).await {
+            return project.incoming_calls(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got callHierarchy/incomingCalls request")
             .await;
@@ -6964,6 +8803,10 @@ impl LanguageServer for ForgeLsp {
         &self,
         params: CallHierarchyOutgoingCallsParams,
     ) -> tower_lsp::jsonrpc::Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        if let Some(project) = self.route_uri(&params.item.uri).await {
+            return project.outgoing_calls(params).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "got callHierarchy/outgoingCalls request")
             .await;
@@ -7143,17 +8986,375 @@ impl LanguageServer for ForgeLsp {
 
         Ok(Some(results))
     }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TypeHierarchyItem>>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        if let Some(project) = self.route_uri(&uri).await {
+            return project
+                .prepare_type_hierarchy(TypeHierarchyPrepareParams {
+                    text_document_position_params: TextDocumentPositionParams {
+                        text_document: TextDocumentIdentifier { uri },
+                        position: params.text_document_position_params.position,
+                    },
+                    work_done_progress_params: params.work_done_progress_params,
+                })
+                .await;
+        }
+
+        self.client
+            .log_message(
+                MessageType::INFO,
+                "got textDocument/prepareTypeHierarchy request",
+            )
+            .await;
+
+        let position = params.text_document_position_params.position;
+        let file_path = match uri.to_file_path() {
+            Ok(path) => path,
+            Err(_) => {
+                self.client
+                    .log_message(MessageType::ERROR, "invalid file uri")
+                    .await;
+                return Ok(None);
+            }
+        };
+
+        let source_bytes = match self.get_source_bytes(&uri, &file_path).await {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        let cached_build = match self.get_or_fetch_build(&uri, &file_path, true).await {
+            Some(cb) => cb,
+            None => return Ok(None),
+        };
+
+        let path_str = match file_path.to_str() {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        let abs_path = match cached_build.path_to_abs.get(path_str) {
+            Some(ap) => ap.clone(),
+            None => crate::types::AbsPath::new(path_str),
+        };
+
+        let byte_position = goto::pos_to_bytes(&source_bytes, position);
+        let item = crate::type_hierarchy::contract_at_position(
+            &cached_build,
+            abs_path.as_str(),
+            byte_position,
+        )
+        .and_then(|id| crate::type_hierarchy::type_hierarchy_item(&cached_build, id));
+
+        match item {
+            Some(it) => {
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!("prepared type hierarchy for: {}", it.name),
+                    )
+                    .await;
+                Ok(Some(vec![it]))
+            }
+            None => {
+                self.client
+                    .log_message(MessageType::INFO, "no contract found at cursor position")
+                    .await;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TypeHierarchyItem>>> {
+        if let Some(project) = self.route_uri(&params.item.uri).await {
+            return project.supertypes(params).await;
+        }
+
+        self.client
+            .log_message(MessageType::INFO, "got typeHierarchy/supertypes request")
+            .await;
+
+        let item = &params.item;
+        let Some((node_id, target_abs, target_name_offset)) =
+            crate::type_hierarchy::item_target(item)
+        else {
+            self.client
+                .log_message(
+                    MessageType::ERROR,
+                    "missing nodeId in TypeHierarchyItem data",
+                )
+                .await;
+            return Ok(None);
+        };
+
+        let file_path = match item.uri.to_file_path() {
+            Ok(p) => p,
+            Err(_) => return Ok(None),
+        };
+
+        // Every base of a contract is compiled with it, so the first build
+        // that contains the contract has all of its supertypes. Prefer the
+        // file build (it has a `decl_index` with `linearizedBaseContracts`).
+        let mut builds: Vec<Arc<goto::CachedBuild>> = Vec::new();
+        if let Some(fb) = self.get_or_fetch_build(&item.uri, &file_path, true).await {
+            builds.push(fb);
+        }
+        if let Some(pb) = self.ensure_project_cached_build().await {
+            builds.push(pb);
+        }
+
+        for build in &builds {
+            let Some(contract_id) = crate::type_hierarchy::resolve_contract_in_build(
+                build,
+                node_id,
+                &target_abs,
+                &item.name,
+                target_name_offset,
+            ) else {
+                continue;
+            };
+            let results: Vec<TypeHierarchyItem> =
+                crate::type_hierarchy::supertypes(build, contract_id)
+                    .into_iter()
+                    .filter_map(|id| crate::type_hierarchy::type_hierarchy_item(build, id))
+                    .collect();
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("found {} supertypes", results.len()),
+                )
+                .await;
+            return Ok(Some(results));
+        }
+
+        Ok(Some(vec![]))
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TypeHierarchyItem>>> {
+        if let Some(project) = self.route_uri(&params.item.uri).await {
+            return project.subtypes(params).await;
+        }
+
+        self.client
+            .log_message(MessageType::INFO, "got typeHierarchy/subtypes request")
+            .await;
+
+        let item = &params.item;
+        let Some((node_id, target_abs, target_name_offset)) =
+            crate::type_hierarchy::item_target(item)
+        else {
+            self.client
+                .log_message(
+                    MessageType::ERROR,
+                    "missing nodeId in TypeHierarchyItem data",
+                )
+                .await;
+            return Ok(None);
+        };
+
+        let file_path = match item.uri.to_file_path() {
+            Ok(p) => p,
+            Err(_) => return Ok(None),
+        };
+
+        // Subtypes can live anywhere that imports the contract: the file
+        // build, the project build (tests + scripts) or a lib sub-cache.
+        let mut builds: Vec<Arc<goto::CachedBuild>> = Vec::new();
+        if let Some(fb) = self.get_or_fetch_build(&item.uri, &file_path, true).await {
+            builds.push(fb);
+        }
+        if let Some(pb) = self.ensure_project_cached_build().await {
+            builds.push(pb);
+        }
+        builds.extend(self.sub_caches.read().await.iter().cloned());
+
+        // Node IDs differ between compilations, so resolve the target and
+        // build subtype items per build, then merge by source position.
+        let mut grouped: HashMap<(String, u32, u32), TypeHierarchyItem> = HashMap::new();
+        for build in &builds {
+            let Some(contract_id) = crate::type_hierarchy::resolve_contract_in_build(
+                build,
+                node_id,
+                &target_abs,
+                &item.name,
+                target_name_offset,
+            ) else {
+                continue;
+            };
+            for id in crate::type_hierarchy::subtypes(build, contract_id) {
+                let Some(sub) = crate::type_hierarchy::type_hierarchy_item(build, id) else {
+                    continue;
+                };
+                let key = (
+                    sub.uri.to_string(),
+                    sub.selection_range.start.line,
+                    sub.selection_range.start.character,
+                );
+                grouped.entry(key).or_insert(sub);
+            }
+        }
+
+        let mut results: Vec<((String, u32, u32), TypeHierarchyItem)> =
+            grouped.into_iter().collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        let results: Vec<TypeHierarchyItem> = results.into_iter().map(|(_, it)| it).collect();
+
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("found {} subtypes", results.len()),
+            )
+            .await;
+        Ok(Some(results))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        start_or_mark_project_cache_sync_pending, stop_project_cache_sync_worker_or_reclaim,
-        take_project_cache_sync_pending, try_claim_project_cache_dirty,
-        update_imports_on_delete_enabled,
+        ForgeLsp, ProjectDiagnosticsCache, ProjectState, start_or_mark_project_cache_sync_pending,
+        stop_project_cache_sync_worker_or_reclaim, take_project_cache_sync_pending,
+        try_claim_project_cache_dirty, update_imports_on_delete_enabled,
     };
     use std::sync::atomic::{AtomicBool, Ordering};
 
+    #[tokio::test]
+    async fn route_scopes_requests_to_workspace_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        for project in ["core", "periphery"] {
+            std::fs::create_dir_all(dir.path().join(project).join("src")).unwrap();
+            std::fs::write(
+                dir.path().join(project).join("foundry.toml"),
+                "[profile.default]",
+            )
+            .unwrap();
+        }
+        let (service, _socket) =
+            tower_lsp::LspService::new(|client| ForgeLsp::new(client, false, true));
+        let server = service.inner().clone();
+        for toml_path in crate::config::discover_foundry_projects(dir.path()) {
+            server
+                .projects
+                .write()
+                .await
+                .push(ProjectState::load(&toml_path));
+        }
+
+        let core_file = dir.path().join("core/src/A.sol");
+        let core = server.route(&core_file).await.expect("routed to core");
+        assert_eq!(
+            core.foundry_config.read().await.root,
+            dir.path().join("core")
+        );
+        // Already scoped: no further routing.
+        assert!(core.route(&core_file).await.is_none());
+
+        let periphery = core
+            .route(&dir.path().join("periphery/src/B.sol"))
+            .await
+            .expect("routed to periphery");
+        assert_eq!(
+            periphery.foundry_config.read().await.root,
+            dir.path().join("periphery")
+        );
+
+        assert!(
+            server
+                .route(&dir.path().join("other/C.sol"))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_diagnostic_reports_every_project() {
+        use tower_lsp::LanguageServer;
+        use tower_lsp::lsp_types::*;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (service, socket) =
+            tower_lsp::LspService::new(|client| ForgeLsp::new(client, false, true));
+        // Nothing reads the client's messages: drop the socket so logging
+        // does not wait on it.
+        drop(socket);
+        let server = service.inner().clone();
+        for project in ["core", "periphery"] {
+            let root = dir.path().join(project);
+            std::fs::create_dir_all(root.join("src")).unwrap();
+            std::fs::write(root.join("src/A.sol"), "contract A {}").unwrap();
+            std::fs::write(root.join("foundry.toml"), "[profile.default]").unwrap();
+            let state = ProjectState::load(&root.join("foundry.toml"));
+            // Seed the project's last compile so the test needs no solc.
+            let diagnostic = Diagnostic {
+                message: project.to_string(),
+                ..Default::default()
+            };
+            let diags =
+                std::collections::HashMap::from([(root.join("src/A.sol"), vec![diagnostic])]);
+            state
+                .project_diagnostics
+                .store(0, diags, Default::default())
+                .await;
+            server.projects.write().await.push(state);
+        }
+
+        let params = WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids: Vec::new(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let WorkspaceDiagnosticReportResult::Report(report) =
+            server.workspace_diagnostic(params).await.unwrap()
+        else {
+            panic!("expected a full report");
+        };
+        let mut messages: Vec<String> = report
+            .items
+            .into_iter()
+            .filter_map(|item| match item {
+                WorkspaceDocumentDiagnosticReport::Full(full) => Some(
+                    full.full_document_diagnostic_report.items[0]
+                        .message
+                        .clone(),
+                ),
+                _ => None,
+            })
+            .collect();
+        messages.sort();
+        assert_eq!(messages, vec!["core", "periphery"]);
+    }
+
+    #[tokio::test]
+    async fn project_diagnostics_cache_survives_unchanged_saves() {
+        let cache = ProjectDiagnosticsCache::default();
+        let versions = std::collections::HashMap::from([("file:///A.sol".into(), 3)]);
+        cache.store(0, Default::default(), versions).await;
+
+        // Saving the text the compile already saw keeps the result.
+        cache.invalidate_saved("file:///A.sol", 3).await;
+        assert!(cache.get().await.is_some());
+        cache.invalidate_saved("file:///A.sol", 4).await;
+        assert!(cache.get().await.is_none());
+
+        // A compile that started before an invalidation is not stored.
+        let generation = cache.generation.load(Ordering::Acquire);
+        cache.invalidate().await;
+        cache
+            .store(generation, Default::default(), Default::default())
+            .await;
+        assert!(cache.get().await.is_none());
+    }
+
     #[test]
     fn update_imports_on_delete_enabled_defaults_true() {
         let s = crate::config::Settings::default();
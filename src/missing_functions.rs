@@ -0,0 +1,695 @@
+//! "Implement missing functions" code action.
+//!
+//! Solc reports error 3656 ("Contract should be marked as abstract") when a
+//! contract inherits functions it does not implement. This module computes
+//! those functions from the inheritance chain and renders stubs for them:
+//!
+//! ```solidity
+//! function balanceOf(address owner) external view override returns (uint256) {
+//!     revert("unimplemented");
+//! }
+//! ```
+//!
+//! # Architecture
+//!
+//! The contract's linearization (`linearizedBaseContracts`, kept in
+//! `CompletionCache`) is walked from the most derived contract to the most
+//! base one. Every function is keyed by name and parameter types; a key is
+//! implemented if any definition along the chain implements it, and public
+//! state variables count as implementations of functions with the same name
+//! (their getters). Keys that stay unimplemented, and that the contract does
+//! not declare itself, are missing. Their declarations are deduplicated through
+//! `baseFunctions`, and when more than one base still declares the function
+//! the stub gets an `override(A, B)` list.
+//!
+//! Declarations come from `decl_index`, which needs `implemented` to survive
+//! `extract_decl_nodes` stripping. Where the stubs go is decided on the live
+//! buffer with tree-sitter: just before the contract's closing brace.
+
+use std::collections::HashSet;
+use tower_lsp::lsp_types::TextEdit;
+use tree_sitter::Node;
+
+use crate::goto::CachedBuild;
+use crate::solc_ast::{
+    ContractDefinition, ContractDefinitionNode, DeclNode, FunctionDefinition, FunctionKind,
+    ParameterList, StateMutability, Visibility,
+};
+use crate::types::NodeId;
+use crate::utils::byte_offset_to_position;
+
+/// A function the contract inherits but does not implement.
+#[derive(Debug, Clone)]
+pub struct MissingFunction {
+    /// The most derived declaration of the function.
+    pub function: FunctionDefinition,
+    /// Names of the bases that still declare it, for `override(...)`.
+    pub declared_in: Vec<String>,
+}
+
+// ── Query functions ────────────────────────────────────────────────────────
+
+/// Every function `contract_id` inherits without an implementation, ordered
+/// from the most base contract to the most derived one, in declaration order.
+pub fn missing_functions(build: &CachedBuild, contract_id: NodeId) -> Vec<MissingFunction> {
+    struct Entry<'a> {
+        implemented: bool,
+        /// `(position in linearization, contract name, declaration)`.
+        decls: Vec<(usize, &'a str, &'a FunctionDefinition)>,
+    }
+
+    let order = linearization(build, contract_id);
+    let mut entries: Vec<(String, Entry)> = Vec::new();
+    let mut getters: HashSet<&str> = HashSet::new();
+
+    for (pos, id) in order.iter().enumerate() {
+        let Some(contract) = contract_decl(build, *id) else {
+            continue;
+        };
+        for node in &contract.nodes {
+            match node {
+                ContractDefinitionNode::FunctionDefinition(f)
+                    if f.kind == FunctionKind::Function
+                        && f.visibility != Some(Visibility::Private) =>
+                {
+                    let key = signature_key(f);
+                    let implemented = f.implemented.unwrap_or(true);
+                    match entries.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, entry)) => {
+                            entry.implemented |= implemented;
+                            entry.decls.push((pos, &contract.name, f));
+                        }
+                        None => entries.push((
+                            key,
+                            Entry {
+                                implemented,
+                                decls: vec![(pos, &contract.name, f)],
+                            },
+                        )),
+                    }
+                }
+                ContractDefinitionNode::VariableDeclaration(v)
+                    if v.state_variable == Some(true)
+                        && v.visibility == Some(Visibility::Public) =>
+                {
+                    getters.insert(&v.name);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut missing: Vec<(usize, usize, MissingFunction)> = entries
+        .into_iter()
+        .filter(|(_, entry)| !entry.implemented && entry.decls[0].0 > 0)
+        .filter(|(_, entry)| !getters.contains(entry.decls[0].2.name.as_str()))
+        .map(|(_, entry)| {
+            // A declaration already overridden by another one in the list is
+            // covered by that override and must not be named again.
+            let overridden: HashSet<i64> = entry
+                .decls
+                .iter()
+                .flat_map(|(_, _, f)| f.base_functions.iter().flatten().copied())
+                .collect();
+            let mut declared_in: Vec<String> = entry
+                .decls
+                .iter()
+                .filter(|(_, _, f)| !overridden.contains(&f.id))
+                .map(|(_, name, _)| name.to_string())
+                .collect();
+            declared_in.sort();
+            declared_in.dedup();
+
+            let (pos, _, function) = entry.decls[0];
+            let offset = src_offset(&function.src);
+            (
+                pos,
+                offset,
+                MissingFunction {
+                    function: function.clone(),
+                    declared_in,
+                },
+            )
+        })
+        .collect();
+    missing.sort_by_key(|(pos, offset, _)| (std::cmp::Reverse(*pos), *offset));
+    missing.into_iter().map(|(_, _, m)| m).collect()
+}
+
+/// The subset of `missing` inherited through `base_id` (declared in the base
+/// itself or in one of its own bases).
+pub fn missing_from_base(
+    build: &CachedBuild,
+    base_id: NodeId,
+    missing: Vec<MissingFunction>,
+) -> Vec<MissingFunction> {
+    let names: HashSet<String> = linearization(build, base_id)
+        .into_iter()
+        .filter_map(|id| contract_decl(build, id).map(|c| c.name.clone()))
+        .collect();
+    missing
+        .into_iter()
+        .filter(|m| m.declared_in.iter().any(|name| names.contains(name)))
+        .collect()
+}
+
+/// The base of `contract_id` named `base` in its inheritance list, as
+/// written there (qualified names included).
+pub fn base_by_name(build: &CachedBuild, contract_id: NodeId, base: &str) -> Option<NodeId> {
+    let base: String = base.split_whitespace().collect();
+    contract_decl(build, contract_id)?
+        .base_contracts
+        .iter()
+        .find(|spec| spec.base_name.name == base)
+        .and_then(|spec| spec.base_name.referenced_declaration)
+        .map(NodeId)
+}
+
+/// Find a contract declared in `abs_path` by name.
+pub fn contract_by_name(build: &CachedBuild, abs_path: &str, name: &str) -> Option<NodeId> {
+    build.decl_index.iter().find_map(|(id, decl)| match decl {
+        DeclNode::ContractDefinition(c)
+            if c.name == name
+                && build
+                    .node_id_to_source_path
+                    .get(id)
+                    .is_some_and(|p| p.as_str() == abs_path) =>
+        {
+            Some(*id)
+        }
+        _ => None,
+    })
+}
+
+// ── Stub rendering ─────────────────────────────────────────────────────────
+
+/// Render a stub for `missing`, indented by `indent`.
+pub fn render_stub(missing: &MissingFunction, indent: &str) -> String {
+    let f = &missing.function;
+    let mut header = format!("function {}({})", f.name, format_params(&f.parameters));
+
+    let visibility = match f.visibility {
+        Some(Visibility::Public) => "public",
+        Some(Visibility::Internal) => "internal",
+        _ => "external",
+    };
+    header.push_str(&format!(" {visibility}"));
+    if f.state_mutability != StateMutability::Nonpayable {
+        header.push_str(&format!(" {}", f.state_mutability));
+    }
+    if missing.declared_in.len() > 1 {
+        header.push_str(&format!(" override({})", missing.declared_in.join(", ")));
+    } else {
+        header.push_str(" override");
+    }
+    let returns = format_params(&f.return_parameters);
+    if !returns.is_empty() {
+        header.push_str(&format!(" returns ({returns})"));
+    }
+
+    format!("{indent}{header} {{\n{indent}{indent}revert(\"unimplemented\");\n{indent}}}\n")
+}
+
+/// Edit inserting stubs for `missing` before the closing brace of the
+/// contract named `contract_name` in `source`.
+pub fn implement_edit(
+    source: &str,
+    contract_name: &str,
+    missing: &[MissingFunction],
+) -> Option<TextEdit> {
+    if missing.is_empty() {
+        return None;
+    }
+    let tree = crate::syntax::parse(source)?;
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let contract = root.children(&mut cursor).find(|node| {
+        node.kind() == "contract_declaration"
+            && node
+                .child_by_field_name("name")
+                .is_some_and(|n| &source[n.byte_range()] == contract_name)
+    })?;
+    let body = contract.child_by_field_name("body")?;
+    let close = body.end_byte().checked_sub(1)?;
+    if source.as_bytes().get(close) != Some(&b'}') {
+        return None;
+    }
+
+    let indent = member_indent(source, body).unwrap_or_else(|| "    ".to_string());
+    let stubs: Vec<String> = missing.iter().map(|m| render_stub(m, &indent)).collect();
+    let mut text = stubs.join("\n");
+
+    // Keep a blank line between existing members and the new stubs, and make
+    // sure the closing brace ends up on its own line.
+    let before = source[..close].trim_end_matches([' ', '\t']);
+    if body.named_child_count() > 0 {
+        text.insert(0, '\n');
+    }
+    if !before.ends_with('\n') {
+        text.insert(0, '\n');
+    }
+    let start = before.len();
+
+    Some(TextEdit {
+        range: tower_lsp::lsp_types::Range {
+            start: byte_offset_to_position(source, start),
+            end: byte_offset_to_position(source, close),
+        },
+        new_text: text,
+    })
+}
+
+/// Stubs for the contract containing `byte` in `source`, limited to the
+/// functions inherited through `base` when given.
+///
+/// `build` must already see the contract's inheritance list as it is in
+/// `source`; a build carrying the 3656 error that asks for this is fine.
+pub fn implement(
+    build: &CachedBuild,
+    abs_path: &str,
+    source: &str,
+    byte: usize,
+    base: Option<&str>,
+) -> Option<TextEdit> {
+    let contract_name = enclosing_contract_name(source, byte)?;
+    let contract_id = contract_by_name(build, abs_path, &contract_name)?;
+    let mut missing = missing_functions(build, contract_id);
+    if let Some(base) = base {
+        let base_id = base_by_name(build, contract_id, base)?;
+        missing = missing_from_base(build, base_id, missing);
+    }
+    implement_edit(source, &contract_name, &missing)
+}
+
+/// Name of the contract whose declaration contains `byte`.
+pub fn enclosing_contract_name(source: &str, byte: usize) -> Option<String> {
+    let tree = crate::syntax::parse(source)?;
+    let mut node = tree.root_node().descendant_for_byte_range(byte, byte)?;
+    loop {
+        if node.kind() == "contract_declaration" {
+            let name = node.child_by_field_name("name")?;
+            return Some(source[name.byte_range()].to_string());
+        }
+        node = node.parent()?;
+    }
+}
+
+/// If `byte` is on an `is Base` inheritance specifier, the names of the
+/// contract and of the base.
+pub fn inheritance_at(source: &str, byte: usize) -> Option<(String, String)> {
+    let tree = crate::syntax::parse(source)?;
+    let mut node = tree.root_node().descendant_for_byte_range(byte, byte)?;
+    while node.kind() != "inheritance_specifier" {
+        node = node.parent()?;
+    }
+    let ancestor = node.child_by_field_name("ancestor")?;
+    let base = source[ancestor.byte_range()].to_string();
+    let contract = node.parent()?;
+    let name = contract.child_by_field_name("name")?;
+    Some((source[name.byte_range()].to_string(), base))
+}
+
+/// Linearized bases of `contract_id`, most derived first (including itself).
+///
+/// Falls back to a depth-first walk of `baseContracts` when the completion
+/// cache has no linearization for the contract.
+pub fn linearization(build: &CachedBuild, contract_id: NodeId) -> Vec<NodeId> {
+    if let Some(linearized) = build
+        .completion_cache
+        .linearized_base_contracts
+        .get(&contract_id)
+    {
+        return linearized.clone();
+    }
+
+    let mut order = Vec::new();
+    let mut stack = vec![contract_id];
+    while let Some(id) = stack.pop() {
+        if order.contains(&id) {
+            continue;
+        }
+        order.push(id);
+        if let Some(contract) = contract_decl(build, id) {
+            stack.extend(
+                contract
+                    .base_contracts
+                    .iter()
+                    .filter_map(|spec| spec.base_name.referenced_declaration)
+                    .map(NodeId),
+            );
+        }
+    }
+    order
+}
+
+// ── Internal helpers ───────────────────────────────────────────────────────
+
+fn contract_decl(build: &CachedBuild, id: NodeId) -> Option<&ContractDefinition> {
+    match build.decl_index.get(&id)? {
+        DeclNode::ContractDefinition(c) => Some(c),
+        _ => None,
+    }
+}
+
+fn signature_key(f: &FunctionDefinition) -> String {
+    let types: Vec<String> = f
+        .parameters
+        .parameters
+        .iter()
+        .map(|p| source_type(p.type_descriptions.type_string.as_deref().unwrap_or("?")))
+        .collect();
+    format!("{}({})", f.name, types.join(","))
+}
+
+/// Turn a solc `typeString` into the type as written in source, e.g.
+/// `struct IPool.Key[] memory` → `IPool.Key[]`.
+fn source_type(type_string: &str) -> String {
+    let mut t = type_string;
+    for prefix in ["struct ", "enum ", "contract ", "interface ", "library "] {
+        if let Some(rest) = t.strip_prefix(prefix) {
+            t = rest;
+        }
+    }
+    for suffix in [" memory", " calldata", " storage", " pointer", " ref"] {
+        if let Some(rest) = t.strip_suffix(suffix) {
+            t = rest;
+        }
+    }
+    t.to_string()
+}
+
+fn format_params(params: &ParameterList) -> String {
+    params
+        .parameters
+        .iter()
+        .map(|p| {
+            let mut s = source_type(p.type_descriptions.type_string.as_deref().unwrap_or("?"));
+            if let Some(location) = &p.storage_location {
+                let location = location.to_string();
+                if location != "default" {
+                    s.push(' ');
+                    s.push_str(&location);
+                }
+            }
+            if !p.name.is_empty() {
+                s.push(' ');
+                s.push_str(&p.name);
+            }
+            s
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn src_offset(src: &str) -> usize {
+    src.split(':')
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Indentation of the first member of a contract body, if it has one.
+fn member_indent(source: &str, body: Node) -> Option<String> {
+    let first = body.named_child(0)?;
+    let line_start = source[..first.start_byte()]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let indent = &source[line_start..first.start_byte()];
+    (!indent.is_empty() && indent.chars().all(|c| c == ' ' || c == '\t'))
+        .then(|| indent.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params(id: i64, params: serde_json::Value) -> serde_json::Value {
+        json!({ "id": id, "nodeType": "ParameterList", "src": "0:0:0", "parameters": params })
+    }
+
+    fn param(id: i64, name: &str, type_string: &str, location: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "nodeType": "VariableDeclaration",
+            "src": "0:0:0",
+            "name": name,
+            "storageLocation": location,
+            "typeDescriptions": { "typeString": type_string }
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn function(
+        id: i64,
+        src: &str,
+        name: &str,
+        visibility: &str,
+        mutability: &str,
+        implemented: bool,
+        parameters: serde_json::Value,
+        returns: serde_json::Value,
+        base_functions: serde_json::Value,
+    ) -> serde_json::Value {
+        json!({
+            "id": id,
+            "nodeType": "FunctionDefinition",
+            "src": src,
+            "name": name,
+            "kind": "function",
+            "visibility": visibility,
+            "stateMutability": mutability,
+            "implemented": implemented,
+            "parameters": parameters,
+            "returnParameters": returns,
+            "baseFunctions": base_functions
+        })
+    }
+
+    fn contract(
+        id: i64,
+        name: &str,
+        kind: &str,
+        bases: &[(i64, i64, &str)],
+        linearized: &[i64],
+        nodes: serde_json::Value,
+    ) -> serde_json::Value {
+        let bases: Vec<serde_json::Value> = bases
+            .iter()
+            .map(|(spec_id, base, base_name)| {
+                json!({
+                    "id": spec_id,
+                    "nodeType": "InheritanceSpecifier",
+                    "src": "0:0:0",
+                    "baseName": {
+                        "id": spec_id + 1,
+                        "nodeType": "IdentifierPath",
+                        "name": base_name,
+                        "referencedDeclaration": base,
+                        "src": "0:0:0"
+                    }
+                })
+            })
+            .collect();
+        json!({
+            "id": id,
+            "nodeType": "ContractDefinition",
+            "src": "0:0:0",
+            "name": name,
+            "contractKind": kind,
+            "baseContracts": bases,
+            "linearizedBaseContracts": linearized,
+            "nodes": nodes
+        })
+    }
+
+    /// ```solidity
+    /// interface I { function f(uint256 a, string calldata s) external view returns (uint256);
+    ///               function g() external; function h() external; }
+    /// interface J { function f(uint256 a, string calldata s) external view returns (uint256); }
+    /// abstract contract A is I { function g() external override {} uint256 public h; }
+    /// contract C is A, J {}
+    /// ```
+    fn build() -> CachedBuild {
+        let f = |id: i64, src: &str| {
+            function(
+                id,
+                src,
+                "f",
+                "external",
+                "view",
+                false,
+                params(
+                    id + 1,
+                    json!([
+                        param(id + 2, "a", "uint256", "default"),
+                        param(id + 3, "s", "string", "calldata")
+                    ]),
+                ),
+                params(id + 4, json!([param(id + 5, "", "uint256", "default")])),
+                json!([]),
+            )
+        };
+        let unit = |id: i64, name: &str, implemented: bool, base: &[i64]| {
+            function(
+                id,
+                "20:1:0",
+                name,
+                "external",
+                "nonpayable",
+                implemented,
+                params(id + 1, json!([])),
+                params(id + 2, json!([])),
+                json!(base),
+            )
+        };
+        let nodes = json!([
+            contract(
+                2,
+                "I",
+                "interface",
+                &[],
+                &[2],
+                json!([
+                    f(10, "10:1:0"),
+                    unit(20, "g", false, &[]),
+                    unit(30, "h", false, &[])
+                ])
+            ),
+            contract(3, "J", "interface", &[], &[3], json!([f(40, "10:1:0")])),
+            contract(
+                4,
+                "A",
+                "contract",
+                &[(50, 2, "I")],
+                &[4, 2],
+                json!([
+                    unit(60, "g", true, &[20]),
+                    {
+                        "id": 70,
+                        "nodeType": "VariableDeclaration",
+                        "src": "0:0:0",
+                        "name": "h",
+                        "stateVariable": true,
+                        "visibility": "public",
+                        "typeDescriptions": { "typeString": "uint256" }
+                    }
+                ])
+            ),
+            contract(
+                5,
+                "C",
+                "contract",
+                &[(80, 4, "A"), (82, 3, "J")],
+                &[5, 3, 4, 2],
+                json!([])
+            )
+        ]);
+        let ast = crate::test_support::output("/tmp/C.sol", 0, nodes);
+        CachedBuild::new(ast, 0, None)
+    }
+
+    #[test]
+    fn test_missing_functions_follow_inheritance() {
+        let build = build();
+        let missing = missing_functions(&build, NodeId(5));
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].function.name, "f");
+        assert_eq!(missing[0].declared_in, vec!["I", "J"]);
+
+        let missing = missing_functions(&build, NodeId(4));
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].declared_in, vec!["I"]);
+
+        assert_eq!(contract_by_name(&build, "/tmp/C.sol", "C"), Some(NodeId(5)));
+        let from_a = missing_from_base(&build, NodeId(4), missing_functions(&build, NodeId(5)));
+        assert_eq!(from_a.len(), 1);
+    }
+
+    #[test]
+    fn test_render_stub() {
+        let build = build();
+        let missing = missing_functions(&build, NodeId(5));
+        assert_eq!(
+            render_stub(&missing[0], "    "),
+            "    function f(uint256 a, string calldata s) external view override(I, J) returns (uint256) {\n        revert(\"unimplemented\");\n    }\n"
+        );
+    }
+
+    #[test]
+    fn test_implement_edit_inserts_before_closing_brace() {
+        let build = build();
+        let missing = missing_functions(&build, NodeId(5));
+
+        let source = "contract C is A, J {}\n";
+        let edit = implement_edit(source, "C", &missing).unwrap();
+        let mut out = source.to_string();
+        let start = crate::utils::position_to_byte_offset(&out, edit.range.start);
+        let end = crate::utils::position_to_byte_offset(&out, edit.range.end);
+        out.replace_range(start..end, &edit.new_text);
+        assert!(out.starts_with("contract C is A, J {\n    function f("));
+        assert!(out.ends_with("    }\n}\n"));
+
+        let source = "contract C is A, J {\n\tuint256 x;\n}\n";
+        let edit = implement_edit(source, "C", &missing).unwrap();
+        assert_eq!(edit.range.start.line, 2);
+        assert!(edit.new_text.starts_with("\n\tfunction f("));
+    }
+
+    #[test]
+    fn test_implement_from_build_with_errors() {
+        // `contract D is J {}` was just typed: solc rejects it with 3656, so
+        // the stubs must come from that failed output, not the cached build.
+        let source = "interface J {}\ncontract D is J {}\n";
+        let nodes = json!([
+            contract(
+                3,
+                "J",
+                "interface",
+                &[],
+                &[3],
+                json!([function(
+                    40,
+                    "10:1:0",
+                    "f",
+                    "external",
+                    "nonpayable",
+                    false,
+                    params(41, json!([])),
+                    params(42, json!([])),
+                    json!([])
+                )])
+            ),
+            contract(6, "D", "contract", &[(90, 3, "J")], &[6, 3], json!([]))
+        ]);
+        let mut output = crate::test_support::output("/tmp/D.sol", 34, nodes);
+        output["errors"] = json!([{
+            "errorCode": "3656",
+            "severity": "error",
+            "message": "Contract \"D\" should be marked as abstract.",
+            "sourceLocation": { "file": "/tmp/D.sol", "start": 15, "end": 33 }
+        }]);
+        assert!(
+            CachedBuild::from_partial_output(json!({ "sources": {} }), "/tmp/D.sol", 2).is_none()
+        );
+        let build = CachedBuild::from_partial_output(output, "/tmp/D.sol", 2).unwrap();
+        assert_eq!(build.build_version, 2);
+
+        let edit = implement(&build, "/tmp/D.sol", source, 30, None).unwrap();
+        assert!(edit.new_text.contains("function f() external override {"));
+        assert_eq!(base_by_name(&build, NodeId(6), "J"), Some(NodeId(3)));
+        assert!(implement(&build, "/tmp/D.sol", source, 30, Some(" J ")).is_some());
+        assert!(implement(&build, "/tmp/D.sol", source, 30, Some("I")).is_none());
+    }
+
+    #[test]
+    fn test_inheritance_at_cursor() {
+        let source = "contract C is A, J {\n    uint256 x;\n}\n";
+        assert_eq!(
+            inheritance_at(source, 17),
+            Some(("C".to_string(), "J".to_string()))
+        );
+        assert_eq!(inheritance_at(source, 25), None);
+        assert_eq!(enclosing_contract_name(source, 25).as_deref(), Some("C"));
+    }
+}
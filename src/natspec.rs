@@ -0,0 +1,480 @@
+//! NatSpec validation diagnostics and skeleton code actions.
+//!
+//! The editor buffer is parsed with `lintspec-core`'s solar backend and every
+//! documentable item is validated against the editor's [`NatspecSettings`].
+//! The resulting problems are published together with the solc and
+//! forge-lint diagnostics (see `ForgeLsp::on_change`).
+//!
+//! Each diagnostic stores the end of its item header in `data`, so
+//! `textDocument/codeAction` can find the declaration again with tree-sitter
+//! and offer to insert a NatSpec skeleton (or the missing tags) above it.
+
+use crate::config::NatspecSettings;
+use crate::utils::{byte_offset_to_position, position_to_byte_offset};
+use lintspec_core::config::{
+    ContractRules, FunctionConfig, FunctionRules, NoticeDevRules, Req, VariableConfig,
+    WithParamsRules, WithReturnsRules,
+};
+use lintspec_core::definitions::ItemType;
+use lintspec_core::lint::{Validate, ValidationOptions};
+use lintspec_core::parser::Parse;
+use lintspec_core::parser::solar::SolarParser;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range, TextEdit};
+use tree_sitter::{Node, Parser};
+
+/// `Diagnostic.source` for NatSpec diagnostics.
+pub const NATSPEC_SOURCE: &str = "natspec";
+
+/// Payload stored in `Diagnostic.data` for NatSpec diagnostics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatspecDiagnosticData {
+    /// End of the documented item's header (before any body).
+    pub item_end: Position,
+}
+
+// ── Validation ─────────────────────────────────────────────────────────────
+
+/// Translate the editor settings into lintspec [`ValidationOptions`].
+///
+/// Every item kind listed in `settings.items` gets the tags in
+/// `settings.tags` as `Required`; everything else is `Ignored`. Unknown item
+/// or tag names are skipped.
+pub fn validation_options(settings: &NatspecSettings) -> ValidationOptions {
+    let items: Vec<ItemType> = settings
+        .items
+        .iter()
+        .filter_map(|item| ItemType::from_str(item).ok())
+        .collect();
+    let has_tag = |tag: &str| settings.tags.iter().any(|t| t == tag);
+    let rule = |item: ItemType, tag: &str| {
+        if items.contains(&item) && has_tag(tag) {
+            Req::Required
+        } else {
+            Req::Ignored
+        }
+    };
+
+    let contract_rules = |item: ItemType| {
+        ContractRules::builder()
+            .title(rule(item, "title"))
+            .author(rule(item, "author"))
+            .notice(rule(item, "notice"))
+            .dev(rule(item, "dev"))
+            .build()
+    };
+    let with_params_rules = |item: ItemType| {
+        WithParamsRules::builder()
+            .notice(rule(item, "notice"))
+            .dev(rule(item, "dev"))
+            .param(rule(item, "param"))
+            .build()
+    };
+    let function_rules = |item: ItemType| {
+        FunctionRules::builder()
+            .notice(rule(item, "notice"))
+            .dev(rule(item, "dev"))
+            .param(rule(item, "param"))
+            .returns(rule(item, "return"))
+            .build()
+    };
+    let notice_dev_rules = |item: ItemType| {
+        NoticeDevRules::builder()
+            .notice(rule(item, "notice"))
+            .dev(rule(item, "dev"))
+            .build()
+    };
+
+    let inheritdoc_items = [ItemType::PublicFunction, ItemType::ExternalFunction];
+    ValidationOptions::builder()
+        .inheritdoc(has_tag("inheritdoc") && inheritdoc_items.iter().any(|i| items.contains(i)))
+        .inheritdoc_override(false)
+        .notice_or_dev(false)
+        .contracts(contract_rules(ItemType::Contract))
+        .interfaces(contract_rules(ItemType::Interface))
+        .libraries(contract_rules(ItemType::Library))
+        .constructors(with_params_rules(ItemType::Constructor))
+        .enums(with_params_rules(ItemType::Enum))
+        .errors(with_params_rules(ItemType::Error))
+        .events(with_params_rules(ItemType::Event))
+        .modifiers(with_params_rules(ItemType::Modifier))
+        .structs(with_params_rules(ItemType::Struct))
+        .functions(
+            FunctionConfig::builder()
+                .private(function_rules(ItemType::PrivateFunction))
+                .internal(function_rules(ItemType::InternalFunction))
+                .public(function_rules(ItemType::PublicFunction))
+                .external(function_rules(ItemType::ExternalFunction))
+                .build(),
+        )
+        .variables(
+            VariableConfig::builder()
+                .private(notice_dev_rules(ItemType::PrivateVariable))
+                .internal(notice_dev_rules(ItemType::InternalVariable))
+                .public(
+                    WithReturnsRules::builder()
+                        .notice(rule(ItemType::PublicVariable, "notice"))
+                        .dev(rule(ItemType::PublicVariable, "dev"))
+                        .returns(rule(ItemType::PublicVariable, "return"))
+                        .build(),
+                )
+                .build(),
+        )
+        .build()
+}
+
+/// Validate the NatSpec of every item in `source`.
+///
+/// Returns an empty list when the buffer does not parse — solc already
+/// reports syntax errors, so there is nothing useful to add.
+pub fn natspec_diagnostics(
+    source: &str,
+    path: &Path,
+    settings: &NatspecSettings,
+) -> Vec<Diagnostic> {
+    // A fresh parser per call: the solar source map caches files by name, so
+    // reusing one would validate stale buffer contents.
+    let mut parser = SolarParser::new();
+    let Ok(document) = parser.parse_document(source.as_bytes(), Some(path), false) else {
+        return vec![];
+    };
+    let options = validation_options(settings);
+
+    let mut diagnostics = Vec::new();
+    for definition in &document.definitions {
+        let item = definition.validate(&options);
+        let data = (item.item_type != ItemType::ParsingError)
+            .then(|| NatspecDiagnosticData {
+                item_end: byte_offset_to_position(source, item.span.end.utf8),
+            })
+            .and_then(|d| serde_json::to_value(d).ok());
+        for diag in item.diags {
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: byte_offset_to_position(source, diag.span.start.utf8),
+                    end: byte_offset_to_position(source, diag.span.end.utf8),
+                },
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                code: None,
+                code_description: None,
+                source: Some(NATSPEC_SOURCE.to_string()),
+                message: format!("{} {}: {}", item.item_type, item.name, diag.message),
+                related_information: None,
+                tags: None,
+                data: data.clone(),
+            });
+        }
+    }
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+// ── Skeleton code action ───────────────────────────────────────────────────
+
+/// Declaration kinds that can carry NatSpec.
+const DOCUMENTABLE_KINDS: &[&str] = &[
+    "contract_declaration",
+    "interface_declaration",
+    "library_declaration",
+    "function_definition",
+    "modifier_definition",
+    "constructor_definition",
+    "event_definition",
+    "error_declaration",
+    "struct_declaration",
+    "enum_declaration",
+    "state_variable_declaration",
+];
+
+/// Build the quick-fix edit for a NatSpec diagnostic.
+///
+/// `item_end` is the position stored in [`NatspecDiagnosticData`]. When the
+/// declaration has no doc comment, a full skeleton is inserted above it;
+/// otherwise only the missing `@notice`/`@param`/`@return` lines are
+/// appended to the existing comment. Returns the action title and the edit,
+/// or `None` when nothing is missing (e.g. the item uses `@inheritdoc`).
+pub fn skeleton_edit(source: &str, item_end: Position) -> Option<(String, TextEdit)> {
+    let tree = parse(source)?;
+    let end_byte = position_to_byte_offset(source, item_end);
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(end_byte.saturating_sub(1), end_byte.saturating_sub(1))?;
+    while !DOCUMENTABLE_KINDS.contains(&node.kind()) {
+        node = node.parent()?;
+    }
+
+    let line_start = source[..node.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    let indent: String = source[line_start..node.start_byte()]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    let wanted = wanted_tags(node, source);
+
+    let docs = doc_comments(node, source);
+    let Some((first, last)) = docs.first().zip(docs.last()) else {
+        let text: String = wanted
+            .iter()
+            .map(|tag| format!("/// {tag}\n{indent}"))
+            .collect();
+        let pos = byte_offset_to_position(source, node.start_byte());
+        return Some((
+            "Add NatSpec".to_string(),
+            TextEdit {
+                range: Range {
+                    start: pos,
+                    end: pos,
+                },
+                new_text: text,
+            },
+        ));
+    };
+
+    let existing: String = docs.iter().map(|c| &source[c.byte_range()]).collect();
+    if existing.contains("@inheritdoc") {
+        return None;
+    }
+    // `@return` lines need not repeat the names, so they are matched by
+    // position: only the returns past the documented ones are missing.
+    let documented_returns = tag_count(&existing, "@return");
+    let mut returns = 0;
+    let missing: Vec<&String> = wanted
+        .iter()
+        .filter(|tag| {
+            if tag.starts_with("@return") {
+                returns += 1;
+                returns > documented_returns
+            } else {
+                tag_count(&existing, tag) == 0
+            }
+        })
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let last_text = &source[last.byte_range()];
+    let (offset, text) = if last_text.starts_with("///") {
+        let text: String = missing
+            .iter()
+            .map(|tag| format!("\n{indent}/// {tag}"))
+            .collect();
+        (last.end_byte(), text)
+    } else {
+        // `/** ... */` — insert ` * @tag` lines before a closing `*/` that
+        // sits on its own line.
+        let close = last.end_byte().checked_sub(2)?;
+        let close_line = source[..close].rfind('\n')? + 1;
+        if close_line <= first.start_byte() || !source[close_line..close].trim().is_empty() {
+            return None;
+        }
+        let text: String = missing
+            .iter()
+            .map(|tag| format!("{indent} * {tag}\n"))
+            .collect();
+        (close_line, text)
+    };
+    let pos = byte_offset_to_position(source, offset);
+    Some((
+        "Add missing NatSpec tags".to_string(),
+        TextEdit {
+            range: Range {
+                start: pos,
+                end: pos,
+            },
+            new_text: text,
+        },
+    ))
+}
+
+/// The tags a skeleton for `decl` should contain, e.g.
+/// `["@notice", "@param amount", "@return"]`.
+fn wanted_tags(decl: Node, source: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    if matches!(
+        decl.kind(),
+        "contract_declaration" | "interface_declaration" | "library_declaration"
+    ) && let Some(name) = decl.child_by_field_name("name")
+    {
+        tags.push(format!("@title {}", &source[name.byte_range()]));
+    }
+    tags.push("@notice".to_string());
+
+    let mut cursor = decl.walk();
+    for child in decl.named_children(&mut cursor) {
+        match child.kind() {
+            "parameter" | "event_parameter" | "error_parameter" => {
+                if let Some(name) = child.child_by_field_name("name") {
+                    tags.push(format!("@param {}", &source[name.byte_range()]));
+                }
+            }
+            "return_type_definition" => {
+                let mut rc = child.walk();
+                for ret in child.named_children(&mut rc) {
+                    if ret.kind() != "parameter" {
+                        continue;
+                    }
+                    match ret.child_by_field_name("name") {
+                        Some(name) => tags.push(format!("@return {}", &source[name.byte_range()])),
+                        None => tags.push("@return".to_string()),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    tags
+}
+
+/// How often `comment` contains `tag` (`"@param x"` matches `@param x`
+/// followed by whitespace or the end of the comment).
+fn tag_count(comment: &str, tag: &str) -> usize {
+    let tag = tag.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+    comment
+        .match_indices(&tag)
+        .filter(|(i, _)| {
+            comment[i + tag.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace())
+        })
+        .count()
+}
+
+/// NatSpec comments (`///` or `/** */`) directly above `decl`, in source order.
+fn doc_comments<'a>(decl: Node<'a>, source: &str) -> Vec<Node<'a>> {
+    let mut comments = Vec::new();
+    let mut next_start = decl.start_byte();
+    let mut sibling = decl.prev_sibling();
+    while let Some(node) = sibling {
+        let text = &source[node.byte_range()];
+        let adjacent = source[node.end_byte()..next_start]
+            .chars()
+            .all(char::is_whitespace);
+        if node.kind() != "comment"
+            || !adjacent
+            || !(text.starts_with("///") || text.starts_with("/**"))
+        {
+            break;
+        }
+        comments.push(node);
+        next_start = node.start_byte();
+        sibling = node.prev_sibling();
+    }
+    comments.reverse();
+    comments
+}
+
+fn parse(source: &str) -> Option<tree_sitter::Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_solidity::LANGUAGE.into())
+        .ok()?;
+    parser.parse(source, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(items: &[&str], tags: &[&str]) -> NatspecSettings {
+        NatspecSettings {
+            enabled: true,
+            items: items.iter().map(|s| s.to_string()).collect(),
+            tags: tags.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn messages(source: &str, settings: &NatspecSettings) -> Vec<String> {
+        natspec_diagnostics(source, Path::new("/tmp/C.sol"), settings)
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_missing_param_and_return() {
+        let source = "contract C {\n    /// @notice adds\n    function add(uint256 a) external returns (uint256 sum) {}\n}\n";
+        let settings = enabled(&["external-function"], &["notice", "param", "return"]);
+        assert_eq!(
+            messages(source, &settings),
+            vec![
+                "function add: @param a is missing",
+                "function add: @return sum is missing"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unlisted_items_are_ignored() {
+        let source =
+            "contract C {\n    function add(uint256 a) internal {}\n    event E(uint256 x);\n}\n";
+        let settings = enabled(&["event"], &["notice"]);
+        assert_eq!(
+            messages(source, &settings),
+            vec!["event E: @notice is missing"]
+        );
+    }
+
+    #[test]
+    fn test_inheritdoc_tag_requires_inheritdoc() {
+        let source = "contract C {\n    /// @notice f\n    function f() public {}\n}\n";
+        let settings = enabled(&["public-function"], &["inheritdoc"]);
+        assert_eq!(
+            messages(source, &settings),
+            vec!["function f: @inheritdoc is missing"]
+        );
+    }
+
+    #[test]
+    fn test_skeleton_for_undocumented_function() {
+        let source = "contract C {\n    function add(uint256 a, uint256 b) external returns (uint256) {}\n}\n";
+        let settings = enabled(&["external-function"], &["notice"]);
+        let diags = natspec_diagnostics(source, Path::new("/tmp/C.sol"), &settings);
+        let data: NatspecDiagnosticData =
+            serde_json::from_value(diags[0].data.clone().unwrap()).unwrap();
+
+        let (title, edit) = skeleton_edit(source, data.item_end).unwrap();
+        assert_eq!(title, "Add NatSpec");
+        assert_eq!(edit.range.start, Position::new(1, 4));
+        assert_eq!(
+            edit.new_text,
+            "/// @notice\n    /// @param a\n    /// @param b\n    /// @return\n    "
+        );
+    }
+
+    #[test]
+    fn test_skeleton_appends_missing_tags() {
+        let source = "contract C {\n    /// @notice adds\n    /// @param a first\n    function add(uint256 a, uint256 b) external {}\n}\n";
+        let end = Position::new(3, 47);
+        let (title, edit) = skeleton_edit(source, end).unwrap();
+        assert_eq!(title, "Add missing NatSpec tags");
+        assert_eq!(edit.range.start, Position::new(2, 22));
+        assert_eq!(edit.new_text, "\n    /// @param b");
+    }
+
+    #[test]
+    fn test_skeleton_counts_documented_returns() {
+        let source = "contract C {\n    /// @notice splits\n    /// @param a value\n    /// @return the high half\n    function f(uint256 a) external returns (uint128 hi, uint128) {}\n}\n";
+        let (_, edit) = skeleton_edit(source, Position::new(4, 67)).unwrap();
+        assert_eq!(edit.new_text, "\n    /// @return");
+
+        let documented = source.replace(
+            "/// @return the high half",
+            "/// @return hi the high half\n    /// @return the low half",
+        );
+        assert!(skeleton_edit(&documented, Position::new(5, 67)).is_none());
+    }
+
+    #[test]
+    fn test_skeleton_block_comment_and_inheritdoc() {
+        let source = "contract C {\n    /**\n     * @notice adds\n     */\n    event E(uint256 x);\n    /// @inheritdoc I\n    function f(uint256 y) external {}\n}\n";
+        let (_, edit) = skeleton_edit(source, Position::new(4, 23)).unwrap();
+        assert_eq!(edit.range.start, Position::new(3, 0));
+        assert_eq!(edit.new_text, "     * @param x\n");
+
+        assert!(skeleton_edit(source, Position::new(6, 34)).is_none());
+    }
+}
@@ -0,0 +1,676 @@
+//! Organize imports (`source.organizeImports`).
+//!
+//! Rewrites the import block of a file in one edit:
+//!
+//! - imports are sorted by remapping group (`forge-std/`, `@openzeppelin/`,
+//!   ...), with relative imports last and a blank line between groups;
+//! - `import {A} from "x.sol"; import {B} from "x.sol";` are merged into
+//!   `import {A, B} from "x.sol";` and duplicates are dropped;
+//! - plain `import "x.sol";` becomes a named import of the symbols the file
+//!   actually uses;
+//! - unused symbols and imports are removed.
+//!
+//! Directives are read from the live buffer with tree-sitter (paths via
+//! [`crate::links::ts_find_imports`]). Usage comes from the `nodes` reference
+//! index of the file's `CachedBuild`: a symbol is used when some node outside
+//! the import block references its declaration. Aliased symbols
+//! (`{A as B}`, `"x.sol" as X`) have no declaration of their own, so, as in
+//! rename, their usages are found by identifier text.
+//!
+//! Without a build that matches the buffer there is no usage information;
+//! imports are then only sorted, merged and deduplicated.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use tower_lsp::lsp_types::{Range, TextEdit, Url};
+
+use crate::goto::{CachedBuild, NodeInfo};
+use crate::types::{NodeId, SourceLoc};
+use crate::utils::{byte_offset_to_position, position_to_byte_offset};
+
+/// Top-level declarations that can be imported by name.
+const IMPORTABLE_KINDS: &[&str] = &[
+    "ContractDefinition",
+    "StructDefinition",
+    "EnumDefinition",
+    "ErrorDefinition",
+    "EventDefinition",
+    "UserDefinedValueTypeDefinition",
+    "FunctionDefinition",
+    "VariableDeclaration",
+];
+
+/// One imported symbol: `Name` or `Name as Alias`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Symbol {
+    name: String,
+    alias: Option<String>,
+}
+
+impl Symbol {
+    fn render(&self) -> String {
+        match &self.alias {
+            Some(alias) => format!("{} as {alias}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Clause {
+    /// `import "x.sol";`
+    Plain,
+    /// `import "x.sol" as X;` or `import * as X from "x.sol";`
+    Unit(String),
+    /// `import {A, B as C} from "x.sol";`, with the byte offset of each name.
+    Named(Vec<(Symbol, usize)>),
+}
+
+/// An `import_directive` in the live buffer.
+#[derive(Debug)]
+struct Directive {
+    start: usize,
+    end: usize,
+    path: String,
+    clause: Clause,
+}
+
+/// Edits that organize the imports of `source`.
+///
+/// `build` supplies the reference index; pass `None` when it does not match
+/// the buffer. `read_source` reads other files of the build (used to resolve
+/// the names of declarations brought in by plain imports).
+///
+/// Returns `None` when the imports are already organized or the import block
+/// does not parse.
+pub fn organize_imports(
+    source: &str,
+    uri: &Url,
+    remappings: &[String],
+    build: Option<&CachedBuild>,
+    read_source: impl Fn(&Path) -> Option<String>,
+) -> Option<Vec<TextEdit>> {
+    let directives = parse_directives(source)?;
+    let first = directives.first()?;
+    let last = directives.last()?;
+    let usage = build.and_then(|b| Usage::new(b, uri, source, &directives, &read_source));
+
+    // (group, path, line) for every import that survives.
+    let mut lines: BTreeSet<((bool, String), String, String)> = BTreeSet::new();
+    let mut named: HashMap<&str, BTreeSet<Symbol>> = HashMap::new();
+    for (i, directive) in directives.iter().enumerate() {
+        let path = directive.path.as_str();
+        match &directive.clause {
+            Clause::Plain => match usage.as_ref().map(|u| u.plain_symbols(i)) {
+                Some(Some(symbols)) => named.entry(path).or_default().extend(symbols),
+                _ if usage.as_ref().is_some_and(|u| u.plain_unused(i)) => {}
+                _ => {
+                    lines.insert((
+                        group_key(path, remappings),
+                        path.to_string(),
+                        format!("import \"{path}\";"),
+                    ));
+                }
+            },
+            Clause::Unit(alias) => {
+                if usage.as_ref().is_none_or(|u| u.unit_used(i, alias)) {
+                    let text = &source[directive.start..directive.end];
+                    lines.insert((
+                        group_key(path, remappings),
+                        path.to_string(),
+                        text.to_string(),
+                    ));
+                }
+            }
+            Clause::Named(symbols) => {
+                let kept = symbols
+                    .iter()
+                    .filter(|(symbol, byte)| {
+                        usage.as_ref().is_none_or(|u| u.symbol_used(symbol, *byte))
+                    })
+                    .map(|(symbol, _)| symbol.clone());
+                named.entry(path).or_default().extend(kept);
+            }
+        }
+    }
+    for (path, symbols) in named {
+        if symbols.is_empty() {
+            continue;
+        }
+        let list: Vec<String> = symbols.iter().map(Symbol::render).collect();
+        lines.insert((
+            group_key(path, remappings),
+            path.to_string(),
+            format!("import {{{}}} from \"{path}\";", list.join(", ")),
+        ));
+    }
+
+    let mut block = String::new();
+    let mut previous_group = None;
+    for (group, _, line) in &lines {
+        if let Some(previous) = previous_group
+            && previous != group
+        {
+            block.push('\n');
+        }
+        if !block.is_empty() {
+            block.push('\n');
+        }
+        block.push_str(line);
+        previous_group = Some(group);
+    }
+
+    let between_only_whitespace = directives
+        .windows(2)
+        .all(|pair| source[pair[0].end..pair[1].start].trim().is_empty());
+    let edits = if between_only_whitespace {
+        let mut end = last.end;
+        if block.is_empty() {
+            end += source[end..].len() - source[end..].trim_start().len();
+        }
+        if source[first.start..end] == block {
+            return None;
+        }
+        vec![edit(source, first.start, end, block)]
+    } else {
+        // Something other than imports sits between them (comments, pragmas):
+        // leave it in place, put the block where the first import was and
+        // delete the others.
+        let mut edits = vec![edit(source, first.start, first.end, block)];
+        for directive in &directives[1..] {
+            let (start, end) = line_span(source, directive.start, directive.end);
+            edits.push(edit(source, start, end, String::new()));
+        }
+        edits
+    };
+    Some(edits)
+}
+
+/// Import directives of `source`, in order.
+///
+/// Returns `None` if the file has syntax errors, so a half-typed import is
+/// never rewritten.
+fn parse_directives(source: &str) -> Option<Vec<Directive>> {
+    let tree = crate::syntax::parse(source)?;
+    let root = tree.root_node();
+    if root.has_error() {
+        return None;
+    }
+    let paths = crate::links::ts_find_imports(source.as_bytes());
+    let mut cursor = root.walk();
+    let nodes: Vec<_> = root
+        .children(&mut cursor)
+        .filter(|n| n.kind() == "import_directive")
+        .collect();
+    if nodes.len() != paths.len() {
+        return None;
+    }
+
+    let text = |n: tree_sitter::Node| source[n.byte_range()].to_string();
+    let mut directives = Vec::new();
+    for (node, import) in nodes.into_iter().zip(paths) {
+        let mut symbols: Vec<(Symbol, usize)> = Vec::new();
+        let mut braces = false;
+        let mut unit = None;
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            loop {
+                let child = cursor.node();
+                match (cursor.field_name(), child.kind()) {
+                    (_, "{") => braces = true,
+                    (Some("import_name"), _) if braces => symbols.push((
+                        Symbol {
+                            name: text(child),
+                            alias: None,
+                        },
+                        child.start_byte(),
+                    )),
+                    // `import A from "x.sol"` is not valid Solidity.
+                    (Some("import_name"), _) => return None,
+                    (Some("alias"), _) if braces => {
+                        symbols.last_mut()?.0.alias = Some(text(child));
+                    }
+                    (Some("alias"), _) => unit = Some(text(child)),
+                    _ => {}
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        let clause = match (braces, unit) {
+            (true, _) => Clause::Named(symbols),
+            (false, Some(alias)) => Clause::Unit(alias),
+            (false, None) => Clause::Plain,
+        };
+        directives.push(Directive {
+            start: node.start_byte(),
+            end: node.end_byte(),
+            path: import.path,
+            clause,
+        });
+    }
+    Some(directives)
+}
+
+/// Sort key for an import path: relative imports last, everything else by
+/// the longest matching remapping prefix (or its first path segment).
+fn group_key(path: &str, remappings: &[String]) -> (bool, String) {
+    if path.starts_with("./") || path.starts_with("../") {
+        return (true, String::new());
+    }
+    let remapped = remappings
+        .iter()
+        .filter_map(|r| {
+            let (prefix, _) = r.split_once('=')?;
+            // Drop the optional `context:` part.
+            let prefix = prefix.rsplit_once(':').map_or(prefix, |(_, p)| p);
+            (!prefix.is_empty() && path.starts_with(prefix)).then_some(prefix)
+        })
+        .max_by_key(|prefix| prefix.len());
+    let group = match remapped {
+        Some(prefix) => prefix.to_string(),
+        None => path.split('/').next().unwrap_or(path).to_string(),
+    };
+    (false, group)
+}
+
+fn edit(source: &str, start: usize, end: usize, new_text: String) -> TextEdit {
+    TextEdit {
+        range: Range {
+            start: byte_offset_to_position(source, start),
+            end: byte_offset_to_position(source, end),
+        },
+        new_text,
+    }
+}
+
+/// Widen `start..end` to whole lines when nothing else shares them.
+fn line_span(source: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..]
+        .find('\n')
+        .map_or(source.len(), |i| end + i + 1);
+    if source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+        (line_start, line_end)
+    } else {
+        (start, end)
+    }
+}
+
+/// What the file uses, according to the build's reference index.
+struct Usage<'a> {
+    file_nodes: &'a HashMap<NodeId, NodeInfo>,
+    source: &'a str,
+    uri: &'a Url,
+    import_ranges: Vec<(usize, usize)>,
+    /// `ImportDirective` node ID of each directive.
+    import_ids: Vec<NodeId>,
+    /// Declarations referenced outside the import block.
+    referenced: HashSet<NodeId>,
+    /// Symbols each plain import provides; `None` when some could not be
+    /// named and the import must stay as it is.
+    plain: HashMap<usize, Option<BTreeSet<Symbol>>>,
+}
+
+impl<'a> Usage<'a> {
+    /// Returns `None` if the build does not line up with `directives`.
+    fn new(
+        build: &'a CachedBuild,
+        uri: &'a Url,
+        source: &'a str,
+        directives: &[Directive],
+        read_source: &impl Fn(&Path) -> Option<String>,
+    ) -> Option<Self> {
+        let path = uri.to_file_path().ok()?;
+        let path_str = path.to_str()?;
+        let abs_path = build
+            .path_to_abs
+            .get(path_str)
+            .map(|p| p.as_str())
+            .unwrap_or(path_str);
+        let file_nodes = build.nodes.get(abs_path)?;
+
+        let mut import_ids = Vec::new();
+        let mut targets = Vec::new();
+        for directive in directives {
+            let (id, info) = file_nodes.iter().find(|(_, info)| {
+                info.node_type.as_deref() == Some("ImportDirective")
+                    && SourceLoc::parse(info.src.as_str()).is_some_and(|loc| {
+                        loc.offset == directive.start && loc.offset + loc.length == directive.end
+                    })
+            })?;
+            import_ids.push(*id);
+            targets.push(info.absolute_path.clone()?);
+        }
+
+        let import_ranges: Vec<(usize, usize)> =
+            directives.iter().map(|d| (d.start, d.end)).collect();
+        let in_imports = |offset: usize| {
+            import_ranges
+                .iter()
+                .any(|&(s, e)| s <= offset && offset < e)
+        };
+        let referenced: HashSet<NodeId> = file_nodes
+            .values()
+            .filter(|info| {
+                SourceLoc::parse(info.src.as_str()).is_some_and(|loc| !in_imports(loc.offset))
+            })
+            .filter_map(|info| info.referenced_declaration)
+            .collect();
+
+        // Declarations already imported by name.
+        let named: HashSet<NodeId> = directives
+            .iter()
+            .filter_map(|d| match &d.clause {
+                Clause::Named(symbols) => Some(symbols),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|(_, byte)| foreign_declaration(file_nodes, *byte))
+            .collect();
+
+        // Hand every used top-level declaration from another file to the
+        // plain import that declares it, else to the first that reaches it.
+        let plain_imports: Vec<usize> = directives
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| matches!(d.clause, Clause::Plain))
+            .map(|(i, _)| i)
+            .collect();
+        let reach: HashMap<usize, HashSet<String>> = plain_imports
+            .iter()
+            .map(|&i| (i, reachable_files(build, &targets[i])))
+            .collect();
+        let mut plain: HashMap<usize, Option<BTreeSet<Symbol>>> = plain_imports
+            .iter()
+            .map(|&i| (i, Some(BTreeSet::new())))
+            .collect();
+        let mut sources: HashMap<String, Option<String>> = HashMap::new();
+        let mut used: Vec<NodeId> = referenced.difference(&named).copied().collect();
+        used.sort();
+        for id in used {
+            let Some((file, info)) = top_level_declaration(build, id) else {
+                continue;
+            };
+            if file == abs_path {
+                continue;
+            }
+            let owner = plain_imports
+                .iter()
+                .find(|&&i| targets[i] == file)
+                .or_else(|| plain_imports.iter().find(|&&i| reach[&i].contains(file)));
+            let Some(&owner) = owner else {
+                continue;
+            };
+            let text = sources.entry(file.to_string()).or_insert_with(|| {
+                crate::call_hierarchy::find_file_path(file, &build.id_to_path_map)
+                    .and_then(|p| read_source(&p))
+            });
+            let name = text
+                .as_deref()
+                .and_then(|text| declaration_name(info, text));
+            match (plain.get_mut(&owner), name) {
+                (Some(Some(symbols)), Some(name)) => {
+                    symbols.insert(Symbol { name, alias: None });
+                }
+                (Some(slot), None) => *slot = None,
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            file_nodes,
+            source,
+            uri,
+            import_ranges,
+            import_ids,
+            referenced,
+            plain,
+        })
+    }
+
+    /// Symbols to import by name instead of plain import `i`, or `None` to
+    /// keep it as is. An empty set means the import is unused.
+    fn plain_symbols(&self, i: usize) -> Option<BTreeSet<Symbol>> {
+        self.plain
+            .get(&i)
+            .cloned()
+            .flatten()
+            .filter(|s| !s.is_empty())
+    }
+
+    fn plain_unused(&self, i: usize) -> bool {
+        matches!(self.plain.get(&i), Some(Some(symbols)) if symbols.is_empty())
+    }
+
+    fn unit_used(&self, i: usize, alias: &str) -> bool {
+        self.referenced.contains(&self.import_ids[i]) || self.identifier_used(alias)
+    }
+
+    fn symbol_used(&self, symbol: &Symbol, byte: usize) -> bool {
+        if let Some(alias) = &symbol.alias {
+            return self.identifier_used(alias);
+        }
+        // Keep what the build cannot resolve.
+        foreign_declaration(self.file_nodes, byte).is_none_or(|id| self.referenced.contains(&id))
+    }
+
+    /// Whether `name` appears as an identifier outside the import block.
+    fn identifier_used(&self, name: &str) -> bool {
+        crate::rename::ts_collect_identifier_locations(self.source.as_bytes(), self.uri, name)
+            .iter()
+            .map(|loc| position_to_byte_offset(self.source, loc.range.start))
+            .any(|offset| {
+                !self
+                    .import_ranges
+                    .iter()
+                    .any(|&(s, e)| s <= offset && offset < e)
+            })
+    }
+}
+
+/// Declaration referenced by the `foreign` identifier of a symbol alias.
+fn foreign_declaration(file_nodes: &HashMap<NodeId, NodeInfo>, byte: usize) -> Option<NodeId> {
+    file_nodes.values().find_map(|info| {
+        let loc = SourceLoc::parse(info.src.as_str())?;
+        if loc.offset == byte && info.node_type.as_deref() == Some("Identifier") {
+            info.referenced_declaration
+        } else {
+            None
+        }
+    })
+}
+
+/// File and node of `id` if it is an importable top-level declaration.
+fn top_level_declaration(build: &CachedBuild, id: NodeId) -> Option<(&str, &NodeInfo)> {
+    let (file, nodes) = build
+        .nodes
+        .iter()
+        .find(|(_, nodes)| nodes.contains_key(&id))?;
+    let info = nodes.get(&id)?;
+    if !IMPORTABLE_KINDS.contains(&info.node_type.as_deref()?) {
+        return None;
+    }
+    let scope = nodes.get(&info.scope?)?;
+    (scope.node_type.as_deref() == Some("SourceUnit")).then_some((file.as_str(), info))
+}
+
+/// `file` and every file it imports, transitively.
+fn reachable_files(build: &CachedBuild, file: &str) -> HashSet<String> {
+    let mut seen = HashSet::from([file.to_string()]);
+    let mut stack = vec![file.to_string()];
+    while let Some(current) = stack.pop() {
+        let Some(nodes) = build.nodes.get(current.as_str()) else {
+            continue;
+        };
+        for info in nodes.values() {
+            if info.node_type.as_deref() == Some("ImportDirective")
+                && let Some(target) = &info.absolute_path
+                && seen.insert(target.clone())
+            {
+                stack.push(target.clone());
+            }
+        }
+    }
+    seen
+}
+
+fn declaration_name(info: &NodeInfo, source: &str) -> Option<String> {
+    let loc = SourceLoc::parse(info.name_location.as_deref()?)?;
+    let name = source.get(loc.offset..loc.offset + loc.length)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn apply(source: &str, edits: &[TextEdit]) -> String {
+        let mut out = source.to_string();
+        let mut edits = edits.to_vec();
+        edits.sort_by_key(|e| std::cmp::Reverse(position_to_byte_offset(source, e.range.start)));
+        for e in edits {
+            let start = position_to_byte_offset(source, e.range.start);
+            let end = position_to_byte_offset(source, e.range.end);
+            out.replace_range(start..end, &e.new_text);
+        }
+        out
+    }
+
+    fn uri() -> Url {
+        Url::from_file_path("/tmp/org/C.sol").unwrap()
+    }
+
+    #[test]
+    fn test_sorts_merges_and_dedupes_without_build() {
+        let source = "pragma solidity ^0.8.0;\n\nimport {B} from \"./B.sol\";\nimport \"forge-std/Test.sol\";\nimport {A} from \"./B.sol\";\nimport {A} from \"./B.sol\";\nimport {Foo} from \"@oz/Foo.sol\";\n\ncontract C {}\n";
+        let remappings = vec![
+            "forge-std/=lib/forge-std/src/".to_string(),
+            "@oz/=lib/oz/".to_string(),
+        ];
+        let edits = organize_imports(source, &uri(), &remappings, None, |_| None).unwrap();
+        assert_eq!(
+            apply(source, &edits),
+            "pragma solidity ^0.8.0;\n\nimport {Foo} from \"@oz/Foo.sol\";\n\nimport \"forge-std/Test.sol\";\n\nimport {A, B} from \"./B.sol\";\n\ncontract C {}\n"
+        );
+
+        let organized = apply(source, &edits);
+        assert!(organize_imports(&organized, &uri(), &remappings, None, |_| None).is_none());
+    }
+
+    #[test]
+    fn test_keeps_code_between_imports() {
+        let source = "import \"./B.sol\";\n// keep me\nimport \"./A.sol\";\n\ncontract C {}\n";
+        let edits = organize_imports(source, &uri(), &[], None, |_| None).unwrap();
+        assert_eq!(
+            apply(source, &edits),
+            "import \"./A.sol\";\nimport \"./B.sol\";\n// keep me\n\ncontract C {}\n"
+        );
+    }
+
+    #[test]
+    fn test_skips_files_with_syntax_errors() {
+        let source = "import {A} from \"./A.sol\"\nimport \"./B.sol\";\n";
+        assert!(organize_imports(source, &uri(), &[], None, |_| None).is_none());
+    }
+
+    const A_SOL: &str = "contract A {}\ncontract Z {}\n";
+    const C_SOL: &str =
+        "import \"./A.sol\";\nimport {Unused} from \"./B.sol\";\ncontract C is A {}\n";
+
+    /// `C.sol` plainly imports `A.sol` (using only `A`) and imports `Unused`
+    /// from `B.sol` without using it.
+    fn build() -> CachedBuild {
+        let contract = |id: i64, src: &str, name: &str, name_loc: &str, scope: i64| {
+            json!({
+                "id": id, "nodeType": "ContractDefinition", "src": src, "name": name,
+                "nameLocation": name_loc, "scope": scope, "contractKind": "contract",
+                "baseContracts": [], "nodes": []
+            })
+        };
+        let a = json!([
+            contract(10, "0:13:0", "A", "9:1:0", 1),
+            contract(11, "14:13:0", "Z", "23:1:0", 1),
+        ]);
+        let b = json!([contract(20, "0:18:1", "Unused", "9:6:1", 2)]);
+        let c = json!([
+            {
+                "id": 30, "nodeType": "ImportDirective", "src": "0:17:2",
+                "absolutePath": "/tmp/org/A.sol", "file": "./A.sol",
+                "scope": 3, "symbolAliases": [], "unitAlias": ""
+            },
+            {
+                "id": 31, "nodeType": "ImportDirective", "src": "18:31:2",
+                "absolutePath": "/tmp/org/B.sol", "file": "./B.sol",
+                "scope": 3, "unitAlias": "",
+                "symbolAliases": [{ "foreign": {
+                    "id": 32, "nodeType": "Identifier", "src": "26:6:2",
+                    "name": "Unused", "referencedDeclaration": 20
+                }}]
+            },
+            {
+                "id": 33, "nodeType": "ContractDefinition", "src": "50:18:2",
+                "name": "C", "nameLocation": "59:1:2", "scope": 3,
+                "contractKind": "contract", "nodes": [],
+                "baseContracts": [{
+                    "id": 34, "nodeType": "InheritanceSpecifier", "src": "64:1:2",
+                    "baseName": {
+                        "id": 35, "nodeType": "IdentifierPath", "src": "64:1:2",
+                        "name": "A", "referencedDeclaration": 10
+                    }
+                }]
+            }
+        ]);
+        let ast = crate::test_support::output_files(vec![
+            ("/tmp/org/A.sol", 28, a),
+            ("/tmp/org/B.sol", 19, b),
+            ("/tmp/org/C.sol", 69, c),
+        ]);
+        CachedBuild::new(ast, 0, None)
+    }
+
+    #[test]
+    fn test_names_plain_imports_and_drops_unused() {
+        let build = build();
+        let read = |path: &Path| (path == Path::new("/tmp/org/A.sol")).then(|| A_SOL.to_string());
+        let edits = organize_imports(C_SOL, &uri(), &[], Some(&build), read).unwrap();
+        assert_eq!(
+            apply(C_SOL, &edits),
+            "import {A} from \"./A.sol\";\ncontract C is A {}\n"
+        );
+    }
+
+    #[test]
+    fn test_keeps_plain_import_when_names_are_unknown() {
+        let build = build();
+        let edits = organize_imports(C_SOL, &uri(), &[], Some(&build), |_| None).unwrap();
+        assert_eq!(
+            apply(C_SOL, &edits),
+            "import \"./A.sol\";\ncontract C is A {}\n"
+        );
+    }
+
+    #[test]
+    fn test_group_key() {
+        let remappings = vec![
+            "ctx:@oz/=lib/oz/".to_string(),
+            "@oz/utils/=lib/u/".to_string(),
+        ];
+        assert_eq!(group_key("./A.sol", &remappings), (true, String::new()));
+        assert_eq!(
+            group_key("@oz/utils/X.sol", &remappings),
+            (false, "@oz/utils/".to_string())
+        );
+        assert_eq!(
+            group_key("@oz/Y.sol", &remappings),
+            (false, "@oz/".to_string())
+        );
+        assert_eq!(
+            group_key("src/Z.sol", &remappings),
+            (false, "src".to_string())
+        );
+    }
+}
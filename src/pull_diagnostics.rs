@@ -0,0 +1,185 @@
+//! Pull diagnostics (`textDocument/diagnostic` + `workspace/diagnostic`).
+//!
+//! Every diagnostic set the server computes is recorded per file. When the
+//! client supports pull diagnostics (and the matching refresh request), those
+//! records are served on demand instead of being pushed with
+//! `textDocument/publishDiagnostics`.
+//!
+//! Each report carries a result ID derived from its diagnostics, so a client
+//! that sends back the ID it already has gets an `unchanged` report instead of
+//! the full list.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use tower_lsp::lsp_types::{
+    ClientCapabilities, Diagnostic, DocumentDiagnosticReport, FullDocumentDiagnosticReport,
+    RelatedFullDocumentDiagnosticReport, RelatedUnchangedDocumentDiagnosticReport,
+    UnchangedDocumentDiagnosticReport, Url, WorkspaceDocumentDiagnosticReport,
+    WorkspaceFullDocumentDiagnosticReport, WorkspaceUnchangedDocumentDiagnosticReport,
+};
+
+/// Identifier advertised in `DiagnosticOptions`.
+pub const DIAGNOSTIC_IDENTIFIER: &str = "solidity";
+
+/// Whether the client pulls diagnostics instead of receiving pushes.
+///
+/// Requires both `textDocument.diagnostic` and
+/// `workspace.diagnostic.refreshSupport`: diagnostics are computed
+/// asynchronously after a save, so the server must be able to ask the client
+/// to pull again once they are ready.
+pub fn client_supports_pull(caps: &ClientCapabilities) -> bool {
+    let document = caps
+        .text_document
+        .as_ref()
+        .is_some_and(|td| td.diagnostic.is_some());
+    let refresh = caps
+        .workspace
+        .as_ref()
+        .and_then(|ws| ws.diagnostic.as_ref())
+        .and_then(|d| d.refresh_support)
+        .unwrap_or(false);
+    document && refresh
+}
+
+/// Stable result ID for a set of diagnostics.
+pub fn result_id(diagnostics: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(diagnostics)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Report for a single `textDocument/diagnostic` request.
+pub fn document_report(
+    diagnostics: Vec<Diagnostic>,
+    previous_result_id: Option<&str>,
+) -> DocumentDiagnosticReport {
+    let id = result_id(&diagnostics);
+    if previous_result_id == Some(id.as_str()) {
+        return DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+            related_documents: None,
+            unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                result_id: id,
+            },
+        });
+    }
+    DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+        related_documents: None,
+        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+            result_id: Some(id),
+            items: diagnostics,
+        },
+    })
+}
+
+/// Report for one file in a `workspace/diagnostic` response.
+///
+/// `previous_result_ids` maps each URI to the result ID the client already
+/// holds; `version` is the open document version, if any.
+pub fn workspace_report(
+    uri: Url,
+    version: Option<i64>,
+    diagnostics: Vec<Diagnostic>,
+    previous_result_ids: &HashMap<Url, String>,
+) -> WorkspaceDocumentDiagnosticReport {
+    let id = result_id(&diagnostics);
+    if previous_result_ids.get(&uri) == Some(&id) {
+        return WorkspaceDocumentDiagnosticReport::Unchanged(
+            WorkspaceUnchangedDocumentDiagnosticReport {
+                uri,
+                version,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id: id,
+                },
+            },
+        );
+    }
+    WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+        uri,
+        version,
+        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+            result_id: Some(id),
+            items: diagnostics,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{
+        DiagnosticClientCapabilities, DiagnosticWorkspaceClientCapabilities, Position, Range,
+        TextDocumentClientCapabilities, WorkspaceClientCapabilities,
+    };
+
+    fn diagnostic(message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_document_report_unchanged_when_result_id_matches() {
+        let diags = vec![diagnostic("a")];
+        let id = result_id(&diags);
+
+        assert!(matches!(
+            document_report(diags.clone(), Some(&id)),
+            DocumentDiagnosticReport::Unchanged(_)
+        ));
+        match document_report(diags, Some("stale")) {
+            DocumentDiagnosticReport::Full(report) => {
+                let full = report.full_document_diagnostic_report;
+                assert_eq!(full.result_id, Some(id));
+                assert_eq!(full.items.len(), 1);
+            }
+            other => panic!("expected full report, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_result_id_depends_on_diagnostics() {
+        assert_eq!(result_id(&[]), result_id(&[]));
+        assert_ne!(result_id(&[diagnostic("a")]), result_id(&[diagnostic("b")]));
+    }
+
+    #[test]
+    fn test_workspace_report_uses_previous_result_ids() {
+        let uri = Url::parse("file:///project/src/A.sol").unwrap();
+        let diags = vec![diagnostic("a")];
+        let previous = HashMap::from([(uri.clone(), result_id(&diags))]);
+
+        assert!(matches!(
+            workspace_report(uri.clone(), None, diags.clone(), &previous),
+            WorkspaceDocumentDiagnosticReport::Unchanged(_)
+        ));
+        assert!(matches!(
+            workspace_report(uri, Some(3), vec![], &previous),
+            WorkspaceDocumentDiagnosticReport::Full(_)
+        ));
+    }
+
+    #[test]
+    fn test_client_supports_pull_requires_refresh() {
+        let mut caps = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                diagnostic: Some(DiagnosticClientCapabilities::default()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(!client_supports_pull(&caps));
+
+        caps.workspace = Some(WorkspaceClientCapabilities {
+            diagnostic: Some(DiagnosticWorkspaceClientCapabilities {
+                refresh_support: Some(true),
+            }),
+            ..Default::default()
+        });
+        assert!(client_supports_pull(&caps));
+    }
+}
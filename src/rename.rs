@@ -3,6 +3,7 @@ use crate::goto::CachedBuild;
 use crate::references;
 use crate::types::SourceLoc;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tower_lsp::lsp_types::{Location, Position, Range, TextEdit, Url, WorkspaceEdit};
 
 /// Search a specific line for an identifier and return its exact range.
@@ -172,6 +173,226 @@ pub fn get_identifier_range(source_bytes: &[u8], position: Position) -> Option<R
     Some(Range { start, end })
 }
 
+/// NatSpec tags followed by the name of a declaration.
+const NATSPEC_NAME_TAGS: &[&str] = &["@param", "@return", "@inheritdoc"];
+
+/// The doc comment, tag and name byte range of the NatSpec tag reference at
+/// `cursor_byte`: the name after `@param`, `@return` or `@inheritdoc`.
+fn natspec_reference_at<'a>(
+    source: &str,
+    tree: &'a tree_sitter::Tree,
+    cursor_byte: usize,
+) -> Option<(tree_sitter::Node<'a>, &'static str, usize, usize)> {
+    let comment = tree
+        .root_node()
+        .descendant_for_byte_range(cursor_byte, cursor_byte)?;
+    let text = &source[comment.byte_range()];
+    if comment.kind() != "comment" || !(text.starts_with("///") || text.starts_with("/**")) {
+        return None;
+    }
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let bytes = source.as_bytes();
+    let mut start = cursor_byte;
+    while start > comment.start_byte() && is_ident(bytes[start - 1]) {
+        start -= 1;
+    }
+    let mut end = cursor_byte;
+    while end < comment.end_byte() && is_ident(bytes[end]) {
+        end += 1;
+    }
+    if start == end || bytes[start].is_ascii_digit() {
+        return None;
+    }
+    let before = &source[comment.start_byte()..start];
+    let tag_end = before.trim_end().len();
+    if tag_end == before.len() {
+        return None;
+    }
+    let tag = NATSPEC_NAME_TAGS
+        .iter()
+        .find(|tag| before[..tag_end].ends_with(*tag))?;
+    Some((comment, tag, start, end))
+}
+
+/// Whether `position` is inside a comment.
+fn in_comment(source_bytes: &[u8], position: Position) -> bool {
+    let text = String::from_utf8_lossy(source_bytes);
+    let cursor_byte = crate::utils::position_to_byte_offset(&text, position);
+    crate::syntax::parse(&text).is_some_and(|tree| {
+        tree.root_node()
+            .descendant_for_byte_range(cursor_byte, cursor_byte)
+            .is_some_and(|n| n.kind() == "comment")
+    })
+}
+
+/// The range to rename at `position`: the identifier under the cursor, or
+/// inside a comment only a NatSpec tag reference (`@param amount`) that
+/// names a declaration.
+pub fn prepare_rename_range(source_bytes: &[u8], position: Position) -> Option<Range> {
+    if in_comment(source_bytes, position) {
+        natspec_reference_target(source_bytes, position)?;
+    }
+    get_identifier_range(source_bytes, position)
+}
+
+/// Position of the declaration named by the NatSpec tag reference at
+/// `position`: the parameter or return variable of the documented item for
+/// `@param` / `@return`, the base contract in the inheritance list for
+/// `@inheritdoc`.
+pub fn natspec_reference_target(source_bytes: &[u8], position: Position) -> Option<Position> {
+    let text = String::from_utf8_lossy(source_bytes);
+    let cursor_byte = crate::utils::position_to_byte_offset(&text, position);
+    let tree = crate::syntax::parse(&text)?;
+    let (comment, tag, start, end) = natspec_reference_at(&text, &tree, cursor_byte)?;
+    let name = &text[start..end];
+
+    let mut documented = comment.next_named_sibling()?;
+    while documented.kind() == "comment" {
+        documented = documented.next_named_sibling()?;
+    }
+
+    let mut stack = match tag {
+        "@inheritdoc" => {
+            let mut contract = documented.parent();
+            while let Some(c) = contract.filter(|c| c.kind() != "contract_declaration") {
+                contract = c.parent();
+            }
+            let contract = contract?;
+            let mut cursor = contract.walk();
+            contract
+                .children(&mut cursor)
+                .filter(|c| c.kind() == "inheritance_specifier")
+                .collect()
+        }
+        _ => vec![documented],
+    };
+    while let Some(node) = stack.pop() {
+        let name_node = match tag {
+            "@inheritdoc" => Some(node).filter(|n| n.kind() == "identifier"),
+            _ if node.kind().ends_with("parameter") => node.child_by_field_name("name"),
+            _ => None,
+        };
+        if let Some(n) = name_node.filter(|n| &text[n.byte_range()] == name) {
+            return Some(crate::utils::byte_offset_to_position(&text, n.start_byte()));
+        }
+        if node.kind() == "function_body" {
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    None
+}
+
+/// NatSpec tag references to the declaration at `def_abs_path` /
+/// `def_byte_offset` in `build`. Tags aren't AST references, so they are
+/// found through the `StructuredDocumentation` of the documented items:
+///
+/// - a parameter or return variable is named by `@param` / `@return` in the
+///   documentation of the function, event, error or modifier declaring it,
+/// - a contract is named by `@inheritdoc` in the documentation of items in
+///   files that reference it.
+fn natspec_locations(
+    build: &CachedBuild,
+    def_abs_path: &str,
+    def_byte_offset: usize,
+    name: &str,
+    text_buffers: &HashMap<String, Vec<u8>>,
+) -> Vec<Location> {
+    let Some(id) = references::byte_to_id(&build.nodes, def_abs_path, def_byte_offset) else {
+        return vec![];
+    };
+    let target = build
+        .nodes
+        .get(def_abs_path)
+        .and_then(|nodes| nodes.get(&id))
+        .and_then(|info| info.referenced_declaration)
+        .unwrap_or(id);
+    let find = |id| {
+        build
+            .nodes
+            .iter()
+            .find_map(|(path, nodes)| nodes.get(&id).map(|info| (path.as_str(), info)))
+    };
+    let Some((_, info)) = find(target) else {
+        return vec![];
+    };
+
+    let (tags, docs): (&[&str], Vec<(&str, &str)>) = match info.node_type.as_deref() {
+        Some("VariableDeclaration") => {
+            let docs = info
+                .scope
+                .and_then(find)
+                .and_then(|(path, scope)| Some((path, scope.documentation.as_deref()?)))
+                .into_iter()
+                .collect();
+            (&["@param", "@return"], docs)
+        }
+        Some("ContractDefinition") => {
+            let docs = build
+                .nodes
+                .iter()
+                .filter(|(path, nodes)| {
+                    path.as_str() == def_abs_path
+                        || nodes
+                            .values()
+                            .any(|n| n.referenced_declaration == Some(target))
+                })
+                .flat_map(|(path, nodes)| {
+                    nodes
+                        .values()
+                        .filter_map(move |n| Some((path.as_str(), n.documentation.as_deref()?)))
+                })
+                .collect();
+            (&["@inheritdoc"], docs)
+        }
+        _ => return vec![],
+    };
+
+    let mut locations = Vec::new();
+    for (path, doc_src) in docs {
+        let Ok(uri) = Url::from_file_path(path) else {
+            continue;
+        };
+        let text = match text_buffers.get(uri.as_str()) {
+            Some(buf) => String::from_utf8_lossy(buf).into_owned(),
+            None => match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+        };
+        let Some(loc) = SourceLoc::parse(doc_src) else {
+            continue;
+        };
+        let Some(doc) = text.get(loc.offset..loc.end()) else {
+            continue;
+        };
+        for tag in tags {
+            for (at, _) in doc.match_indices(tag) {
+                let rest = &doc[at + tag.len()..];
+                let word = rest.trim_start();
+                if word.len() == rest.len() {
+                    continue;
+                }
+                let start = loc.offset + at + tag.len() + (rest.len() - word.len());
+                let len = word
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(word.len());
+                if &word[..len] == name {
+                    locations.push(Location {
+                        uri: uri.clone(),
+                        range: Range {
+                            start: crate::utils::byte_offset_to_position(&text, start),
+                            end: crate::utils::byte_offset_to_position(&text, start + len),
+                        },
+                    });
+                }
+            }
+        }
+    }
+    locations
+}
+
 /// Check whether `cursor_byte` falls on an alias local name inside an
 /// `import_directive` in the tree-sitter parse tree, e.g. `MyTest` in
 /// `import {Test as MyTest} from "./A.sol"` or `AFile` in
@@ -486,6 +707,20 @@ pub fn rename_symbol(
     other_builds: &[&CachedBuild],
     text_buffers: &HashMap<String, Vec<u8>>,
 ) -> Option<WorkspaceEdit> {
+    // On a NatSpec tag (`@param amount`), rename the declaration it names.
+    if in_comment(source_bytes, position) {
+        let target = natspec_reference_target(source_bytes, position)?;
+        return rename_symbol(
+            build,
+            file_uri,
+            target,
+            source_bytes,
+            new_name,
+            other_builds,
+            text_buffers,
+        );
+    }
+
     let original_identifier = get_identifier_at_position(source_bytes, position)?;
 
     // Check whether the cursor is on an import alias name — either at the
@@ -569,6 +804,16 @@ pub fn rename_symbol(
             );
             locations.extend(other_locations);
         }
+
+        for b in std::iter::once(build).chain(other_builds.iter().copied()) {
+            locations.extend(natspec_locations(
+                b,
+                &def_abs_path,
+                def_byte_offset,
+                &original_identifier,
+                text_buffers,
+            ));
+        }
     }
 
     build_workspace_edit(
@@ -581,6 +826,35 @@ pub fn rename_symbol(
     )
 }
 
+/// When the symbol at `position` is a contract declared in a file named after
+/// it (`contract Vault` in `Vault.sol`), return the path of that file.
+pub fn contract_file_at(
+    build: &CachedBuild,
+    file_uri: &Url,
+    position: Position,
+    source_bytes: &[u8],
+) -> Option<PathBuf> {
+    let position = if in_comment(source_bytes, position) {
+        natspec_reference_target(source_bytes, position)?
+    } else {
+        position
+    };
+    let name = get_identifier_at_position(source_bytes, position)?;
+    let (def_abs_path, def_byte_offset) =
+        references::resolve_target_location(build, file_uri, position, source_bytes)?;
+    let id = references::byte_to_id(&build.nodes, &def_abs_path, def_byte_offset)?;
+    let info = build.nodes.get(def_abs_path.as_str())?.get(&id)?;
+    if info.node_type.as_deref() != Some("ContractDefinition") {
+        return None;
+    }
+    let path = build
+        .path_to_abs
+        .iter()
+        .find(|(_, abs)| abs.as_str() == def_abs_path)
+        .map(|(path, _)| PathBuf::from(path.as_str()))?;
+    (path.file_stem()? == name.as_str()).then_some(path)
+}
+
 /// Deduplicate `locations`, build `TextEdit`s replacing `original_identifier`
 /// with `new_name`, and return a `WorkspaceEdit`.  Returns `None` if no edits
 /// can be produced.
@@ -944,4 +1218,209 @@ import \"./A.sol\";
             );
         }
     }
+
+    // =========================================================================
+    // NatSpec tag references
+    // =========================================================================
+
+    const NATSPEC_SOL: &str = "contract Base {
+    /// @param amount The amount.
+    /// @return total The total.
+    function f(uint amount) public virtual returns (uint total) {
+        total = amount;
+    }
+}
+
+contract Child is Base {
+    /// @inheritdoc Base
+    function f(uint amount) public override returns (uint total) {
+        total = amount + 1;
+    }
+}
+";
+
+    /// Position of the `nth` occurrence of `needle` in `NATSPEC_SOL`.
+    fn natspec_pos(needle: &str, nth: usize) -> Position {
+        let byte = NATSPEC_SOL.match_indices(needle).nth(nth).unwrap().0;
+        crate::utils::byte_offset_to_position(NATSPEC_SOL, byte)
+    }
+
+    /// A build of `NATSPEC_SOL` written to `dir`, with the nodes the NatSpec
+    /// rename needs.
+    fn natspec_build(dir: &std::path::Path) -> (Url, CachedBuild) {
+        let path = dir.join("Base.sol");
+        std::fs::write(&path, NATSPEC_SOL).unwrap();
+        let path = path.to_str().unwrap();
+        let at = |needle: &str, nth: usize| NATSPEC_SOL.match_indices(needle).nth(nth).unwrap().0;
+        let src = |offset: usize, len: usize| format!("{offset}:{len}:0");
+        let base_doc_end = at("The total.", 0) + "The total.".len();
+        let base_f = at("function f", 0);
+        let child_doc = at("/// @inheritdoc", 0);
+        let child_f = at("function f", 1);
+        let nodes = serde_json::json!([
+            {
+                "id": 2, "nodeType": "ContractDefinition",
+                "src": src(0, at("contract Child", 0) - 2),
+                "nameLocation": src(at("Base", 0), 4),
+                "nodes": [{
+                    "id": 10, "nodeType": "FunctionDefinition",
+                    "src": src(base_f, at("}\n}", 0) + 1 - base_f),
+                    "nameLocation": src(base_f + 9, 1),
+                    "documentation": {
+                        "id": 3, "nodeType": "StructuredDocumentation",
+                        "src": src(at("/// @param", 0), base_doc_end - at("/// @param", 0)),
+                    },
+                    "parameters": {
+                        "id": 4, "nodeType": "ParameterList",
+                        "src": src(base_f + 10, 13),
+                        "parameters": [{
+                            "id": 11, "nodeType": "VariableDeclaration", "scope": 10,
+                            "src": src(base_f + 11, 11),
+                            "nameLocation": src(at("amount)", 0), 6),
+                        }],
+                    },
+                    "body": {
+                        "id": 5, "nodeType": "Block",
+                        "src": src(at("{\n        total", 0), 31),
+                        "statements": [{
+                            "id": 12, "nodeType": "Identifier",
+                            "src": src(at("amount;", 0), 6),
+                            "referencedDeclaration": 11,
+                        }],
+                    },
+                }],
+            },
+            {
+                "id": 20, "nodeType": "ContractDefinition",
+                "src": src(at("contract Child", 0), NATSPEC_SOL.len() - 1 - at("contract Child", 0)),
+                "nameLocation": src(at("Child", 0), 5),
+                "baseContracts": [{
+                    "id": 21, "nodeType": "IdentifierPath",
+                    "src": src(at("Base", 1), 4),
+                    "referencedDeclaration": 2,
+                }],
+                "nodes": [{
+                    "id": 30, "nodeType": "FunctionDefinition",
+                    "src": src(child_f, NATSPEC_SOL.len() - 3 - child_f),
+                    "documentation": {
+                        "id": 31, "nodeType": "StructuredDocumentation",
+                        "src": src(child_doc, "/// @inheritdoc Base".len()),
+                    },
+                }],
+            },
+        ]);
+        let ast = crate::test_support::output(path, NATSPEC_SOL.len(), nodes);
+        (
+            Url::from_file_path(path).unwrap(),
+            CachedBuild::new(ast, 0, None),
+        )
+    }
+
+    /// The text each edit of `edit` replaces, sorted.
+    fn edited_texts(edit: &WorkspaceEdit) -> Vec<(u32, String)> {
+        let mut texts: Vec<(u32, String)> = edit
+            .changes
+            .as_ref()
+            .unwrap()
+            .values()
+            .flatten()
+            .map(|e| {
+                let text = get_text_at_range(NATSPEC_SOL.as_bytes(), &e.range).unwrap();
+                (e.range.start.line, text)
+            })
+            .collect();
+        texts.sort();
+        texts
+    }
+
+    #[test]
+    fn test_contract_file_at_requires_matching_file_stem() {
+        let dir = tempfile::tempdir().unwrap();
+        let (uri, build) = natspec_build(dir.path());
+        let source = NATSPEC_SOL.as_bytes();
+        let path = uri.to_file_path().unwrap();
+        let at = |pos| contract_file_at(&build, &uri, pos, source);
+        assert_eq!(at(natspec_pos("Base", 0)), Some(path.clone()));
+        assert_eq!(at(natspec_pos("Base", 1)), Some(path.clone()));
+        assert_eq!(at(natspec_pos("Base\n    function", 0)), Some(path));
+        assert_eq!(at(natspec_pos("Child", 0)), None);
+        assert_eq!(at(natspec_pos("amount)", 0)), None);
+    }
+
+    #[test]
+    fn test_prepare_rename_range_only_accepts_natspec_references_in_comments() {
+        let source = NATSPEC_SOL.as_bytes();
+        let range = prepare_rename_range(source, natspec_pos("amount The", 0)).unwrap();
+        assert_eq!(get_text_at_range(source, &range).as_deref(), Some("amount"));
+        assert!(prepare_rename_range(source, natspec_pos("The amount", 0)).is_none());
+        // `@inheritdoc` names a base in the inheritance list.
+        assert!(prepare_rename_range(source, natspec_pos("Base\n    function", 0)).is_some());
+        assert!(prepare_rename_range(source, natspec_pos("total =", 0)).is_some());
+    }
+
+    #[test]
+    fn test_natspec_reference_target() {
+        let source = NATSPEC_SOL.as_bytes();
+        assert_eq!(
+            natspec_reference_target(source, natspec_pos("amount The", 0)),
+            Some(natspec_pos("amount)", 0))
+        );
+        assert_eq!(
+            natspec_reference_target(source, natspec_pos("total The", 0)),
+            Some(natspec_pos("total)", 0))
+        );
+        assert_eq!(
+            natspec_reference_target(source, natspec_pos("Base\n    function", 0)),
+            Some(natspec_pos("Base {", 1))
+        );
+    }
+
+    #[test]
+    fn test_rename_parameter_updates_param_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let (uri, build) = natspec_build(dir.path());
+        // Renaming from the `@param` tag renames the parameter too.
+        let edit = rename_symbol(
+            &build,
+            &uri,
+            natspec_pos("amount The", 0),
+            NATSPEC_SOL.as_bytes(),
+            "value".to_string(),
+            &[],
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            edited_texts(&edit),
+            [
+                (1, "amount".to_string()),
+                (3, "amount".to_string()),
+                (4, "amount".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rename_contract_updates_inheritdoc() {
+        let dir = tempfile::tempdir().unwrap();
+        let (uri, build) = natspec_build(dir.path());
+        let edit = rename_symbol(
+            &build,
+            &uri,
+            natspec_pos("Base", 0),
+            NATSPEC_SOL.as_bytes(),
+            "Parent".to_string(),
+            &[],
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            edited_texts(&edit),
+            [
+                (0, "Base".to_string()),
+                (8, "Base".to_string()),
+                (9, "Base".to_string()),
+            ]
+        );
+    }
 }
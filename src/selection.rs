@@ -1,5 +1,5 @@
 use tower_lsp::lsp_types::{Position, Range, SelectionRange};
-use tree_sitter::{Node, Parser, Point};
+use tree_sitter::{Node, Point, Tree};
 
 /// Compute selection ranges for each requested position.
 ///
@@ -7,11 +7,18 @@ use tree_sitter::{Node, Parser, Point};
 /// deepest (leaf) node to the root, producing a linked list of
 /// `SelectionRange` values that editors use for expand/shrink selection.
 pub fn selection_ranges(source: &str, positions: &[Position]) -> Vec<SelectionRange> {
-    let tree = match parse(source) {
-        Some(t) => t,
-        None => return positions.iter().map(|_| empty_selection_range()).collect(),
-    };
+    match crate::syntax::parse(source) {
+        Some(tree) => selection_ranges_with_tree(&tree, source, positions),
+        None => positions.iter().map(|_| empty_selection_range()).collect(),
+    }
+}
 
+/// Selection ranges from an already-parsed `tree` of `source`.
+pub fn selection_ranges_with_tree(
+    tree: &Tree,
+    source: &str,
+    positions: &[Position],
+) -> Vec<SelectionRange> {
     let root = tree.root_node();
 
     positions
@@ -101,14 +108,6 @@ fn empty_selection_range() -> SelectionRange {
     }
 }
 
-fn parse(source: &str) -> Option<tree_sitter::Tree> {
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_solidity::LANGUAGE.into())
-        .ok()?;
-    parser.parse(source, None)
-}
-
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
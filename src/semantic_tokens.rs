@@ -2,7 +2,7 @@ use tower_lsp::lsp_types::{
     SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensEdit,
     SemanticTokensLegend,
 };
-use tree_sitter::{Node, Parser};
+use tree_sitter::{Node, Tree};
 
 /// Inclusive line range for filtering (0-based).
 #[derive(Clone, Copy, Debug)]
@@ -75,7 +75,18 @@ pub fn legend() -> SemanticTokensLegend {
 
 /// Parse `source` with tree-sitter and return semantic tokens for the entire file.
 pub fn semantic_tokens_full(source: &str) -> SemanticTokens {
-    semantic_tokens_impl(source, None)
+    match crate::syntax::parse(source) {
+        Some(tree) => semantic_tokens_full_with_tree(&tree, source),
+        None => SemanticTokens {
+            result_id: None,
+            data: vec![],
+        },
+    }
+}
+
+/// Semantic tokens for the entire file from an already-parsed `tree`.
+pub fn semantic_tokens_full_with_tree(tree: &Tree, source: &str) -> SemanticTokens {
+    semantic_tokens_impl(tree, source, None)
 }
 
 /// Parse `source` with tree-sitter and return semantic tokens only for
@@ -83,7 +94,25 @@ pub fn semantic_tokens_full(source: &str) -> SemanticTokens {
 /// pruned during the tree walk so we avoid visiting most of the AST on
 /// large files.
 pub fn semantic_tokens_range(source: &str, start_line: u32, end_line: u32) -> SemanticTokens {
+    match crate::syntax::parse(source) {
+        Some(tree) => semantic_tokens_range_with_tree(&tree, source, start_line, end_line),
+        None => SemanticTokens {
+            result_id: None,
+            data: vec![],
+        },
+    }
+}
+
+/// Semantic tokens for lines `start_line..=end_line` from an already-parsed
+/// `tree`.
+pub fn semantic_tokens_range_with_tree(
+    tree: &Tree,
+    source: &str,
+    start_line: u32,
+    end_line: u32,
+) -> SemanticTokens {
     semantic_tokens_impl(
+        tree,
         source,
         Some(LineRange {
             start: start_line,
@@ -92,22 +121,7 @@ pub fn semantic_tokens_range(source: &str, start_line: u32, end_line: u32) -> Se
     )
 }
 
-fn semantic_tokens_impl(source: &str, range: Option<LineRange>) -> SemanticTokens {
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_solidity::LANGUAGE.into())
-        .expect("failed to load Solidity grammar");
-
-    let tree = match parser.parse(source, None) {
-        Some(t) => t,
-        None => {
-            return SemanticTokens {
-                result_id: None,
-                data: vec![],
-            };
-        }
-    };
-
+fn semantic_tokens_impl(tree: &Tree, source: &str, range: Option<LineRange>) -> SemanticTokens {
     let mut tokens: Vec<RawToken> = Vec::new();
     collect(tree.root_node(), source, range, &mut tokens);
     tokens.sort_by(|a, b| a.line.cmp(&b.line).then(a.col.cmp(&b.col)));
@@ -99,11 +99,9 @@ impl Runner for SolarRunner {
         Ok(serde_json::Value::Array(Vec::new()))
     }
 
-    async fn format(&self, file: &str) -> Result<String, RunnerError> {
+    async fn format(&self, _file: &str, source: &str) -> Result<String, RunnerError> {
         // Solar does not have formatting, return the original content
-        tokio::fs::read_to_string(file)
-            .await
-            .map_err(|_| RunnerError::ReadError)
+        Ok(source.to_string())
     }
 
     async fn ast(&self, file: &str) -> Result<serde_json::Value, RunnerError> {
@@ -193,6 +193,25 @@ fn find_solc_binary(version: &str) -> Option<PathBuf> {
     None
 }
 
+/// The version of a solc binary returned by [`resolve_solc_binary`].
+///
+/// svm binaries are named `solc-<version>`; anything else (the `$PATH`
+/// fallback) is asked with `solc --version`.
+pub async fn solc_version(binary: &Path) -> Option<SemVer> {
+    if let Some(version) = binary
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("solc-"))
+        .and_then(SemVer::parse)
+    {
+        return Some(version);
+    }
+    let output = Command::new(binary).arg("--version").output().await.ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout.lines().find_map(|l| l.strip_prefix("Version: "))?;
+    SemVer::parse(version.split(['+', '-']).next()?)
+}
+
 // ── Pragma parsing ────────────────────────────────────────────────────────
 
 /// A parsed semver version (major.minor.patch).
@@ -2084,6 +2103,19 @@ mod tests {
         assert_eq!(binary, PathBuf::from("solc"));
     }
 
+    #[tokio::test]
+    async fn test_solc_version_from_svm_binary_name() {
+        let binary = PathBuf::from("/home/u/.svm/0.8.26/solc-0.8.26");
+        assert_eq!(
+            solc_version(&binary).await,
+            Some(SemVer {
+                major: 0,
+                minor: 8,
+                patch: 26
+            })
+        );
+    }
+
     #[test]
     fn test_parse_pragma_exact() {
         let source = "// SPDX\npragma solidity 0.8.26;\n";
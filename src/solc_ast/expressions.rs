@@ -29,6 +29,28 @@ pub enum Expression {
     UnaryOperation(UnaryOperation),
 }
 
+impl Expression {
+    /// Type descriptions of the expression's value.
+    pub fn type_descriptions(&self) -> &TypeDescriptions {
+        match self {
+            Self::Assignment(n) => &n.type_descriptions,
+            Self::BinaryOperation(n) => &n.type_descriptions,
+            Self::Conditional(n) => &n.type_descriptions,
+            Self::ElementaryTypeNameExpression(n) => &n.type_descriptions,
+            Self::FunctionCall(n) => &n.type_descriptions,
+            Self::FunctionCallOptions(n) => &n.type_descriptions,
+            Self::Identifier(n) => &n.type_descriptions,
+            Self::IndexAccess(n) => &n.type_descriptions,
+            Self::IndexRangeAccess(n) => &n.type_descriptions,
+            Self::Literal(n) => &n.type_descriptions,
+            Self::MemberAccess(n) => &n.type_descriptions,
+            Self::NewExpression(n) => &n.type_descriptions,
+            Self::TupleExpression(n) => &n.type_descriptions,
+            Self::UnaryOperation(n) => &n.type_descriptions,
+        }
+    }
+}
+
 // ── Individual expression types ────────────────────────────────────────────
 
 /// An assignment expression (`a = b`, `a += b`, etc.).
@@ -525,14 +525,14 @@ const DECL_NODE_TYPES: &[&str] = &[
 
 /// Fields to strip from declaration nodes before deserializing.
 /// These contain large AST subtrees (function bodies, expressions, etc.)
-/// that are never read through `DeclNode`.
+/// that are never read through `DeclNode`. `implemented` is kept: it is
+/// cheap and "Implement missing functions" needs it.
 const STRIP_FIELDS: &[&str] = &[
     "body",
     "modifiers",
     "value",
     "overrides",
     "nameLocation",
-    "implemented",
     "isVirtual",
     "abstract",
     "contractDependencies",
@@ -553,7 +553,6 @@ const STRIP_CHILD_FIELDS: &[&str] = &[
     "value",
     "overrides",
     "nameLocation",
-    "implemented",
     "isVirtual",
     "constant",
     "indexed",
@@ -652,8 +651,20 @@ fn walk_and_extract(
             build_filtered_decl(obj)
         };
 
-        // Deserialize the filtered node into the typed struct
-        if let Some(decl) = deserialize_decl_node(node_type, node_value) {
+        // Deserialize the filtered node into the typed struct. A constant's
+        // initializer is kept for hover evaluation; should it not fit the
+        // typed expression model, fall back to the declaration without it.
+        let decl = match deserialize_decl_node(node_type, node_value) {
+            None if is_constant(obj) => {
+                let mut stripped = build_filtered_decl(obj);
+                if let Some(map) = stripped.as_object_mut() {
+                    map.remove("value");
+                }
+                deserialize_decl_node(node_type, stripped)
+            }
+            decl => decl,
+        };
+        if let Some(decl) = decl {
             decl_index.insert(id, decl);
         }
     }
@@ -692,14 +703,21 @@ fn walk_and_extract(
     }
 }
 
+/// Whether a declaration node is a `constant` variable.
+fn is_constant(obj: &serde_json::Map<String, serde_json::Value>) -> bool {
+    obj.get("mutability").and_then(|v| v.as_str()) == Some("constant")
+}
+
 /// Build a filtered Value from a borrowed declaration node, cloning only the
 /// fields needed for deserialization. Heavy fields (body, modifiers, value, etc.)
-/// are never copied. This replaces the old clone-then-strip pattern that caused
-/// ~117 MB of transient allocation churn.
+/// are never copied, except the `value` of a constant, which hover evaluates.
+/// This replaces the old clone-then-strip pattern that caused ~117 MB of
+/// transient allocation churn.
 fn build_filtered_decl(obj: &serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
+    let keep_value = is_constant(obj);
     let mut filtered = serde_json::Map::with_capacity(obj.len());
     for (key, value) in obj {
-        if !STRIP_FIELDS.contains(&key.as_str()) {
+        if !STRIP_FIELDS.contains(&key.as_str()) || (keep_value && key == "value") {
             filtered.insert(key.clone(), value.clone());
         }
     }
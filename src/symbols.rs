@@ -3,16 +3,20 @@
 use tower_lsp::lsp_types::{
     DocumentSymbol, Location, Position, Range, SymbolInformation, SymbolKind, Url,
 };
-use tree_sitter::{Node, Parser};
+use tree_sitter::{Node, Parser, Tree};
 
 // ── Document symbols (hierarchical, single file) ───────────────────────────
 
 /// Extract hierarchical document symbols from Solidity source using tree-sitter.
 pub fn extract_document_symbols(source: &str) -> Vec<DocumentSymbol> {
-    let tree = match parse(source) {
-        Some(t) => t,
-        None => return vec![],
-    };
+    match crate::syntax::parse(source) {
+        Some(tree) => extract_document_symbols_with_tree(&tree, source),
+        None => vec![],
+    }
+}
+
+/// Hierarchical document symbols from an already-parsed `tree` of `source`.
+pub fn extract_document_symbols_with_tree(tree: &Tree, source: &str) -> Vec<DocumentSymbol> {
     collect_top_level(tree.root_node(), source)
 }
 
@@ -341,14 +345,6 @@ fn push_info(
 
 // ── Helpers ────────────────────────────────────────────────────────────────
 
-fn parse(source: &str) -> Option<tree_sitter::Tree> {
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_solidity::LANGUAGE.into())
-        .expect("failed to load Solidity grammar");
-    parser.parse(source, None)
-}
-
 fn range(node: Node) -> Range {
     let s = node.start_position();
     let e = node.end_position();
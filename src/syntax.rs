@@ -0,0 +1,183 @@
+//! Tree-sitter parse trees shared across features.
+//!
+//! With incremental `textDocument/didChange`, every content change is applied
+//! to the cached buffer text and mirrored onto the document's cached
+//! [`Tree`] with [`Tree::edit`], so the next parse only re-lexes the edited
+//! region. Features that walk the live buffer (semantic tokens, folding,
+//! selection, symbols, highlights, inlay hints) ask `ForgeLsp` for the
+//! cached tree instead of parsing the full source themselves.
+//!
+//! A cached tree is only valid for the exact text it was parsed from, so
+//! each [`DocumentTree`] records a hash of that text. Anything that rewrites
+//! `text_cache` without going through [`apply_content_change`] (formatting,
+//! file operations, reloads from disk) simply causes a fresh parse on the
+//! next lookup.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use tower_lsp::lsp_types::TextDocumentContentChangeEvent;
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+use crate::utils::position_to_byte_offset;
+
+/// A parse tree together with the hash of the text it was parsed from.
+#[derive(Debug, Clone)]
+pub struct DocumentTree {
+    pub tree: Tree,
+    pub text_hash: u64,
+}
+
+impl DocumentTree {
+    /// Parse `source` from scratch.
+    pub fn parse(source: &str) -> Option<Self> {
+        Some(Self {
+            tree: parse(source)?,
+            text_hash: text_hash(source),
+        })
+    }
+
+    /// Whether this tree was parsed from exactly `source`.
+    pub fn matches(&self, source: &str) -> bool {
+        self.text_hash == text_hash(source)
+    }
+}
+
+/// Parse Solidity source with tree-sitter.
+pub fn parse(source: &str) -> Option<Tree> {
+    reparse(source, None)
+}
+
+/// Parse `source`, reusing the unchanged parts of `old_tree`.
+///
+/// `old_tree` must already have been edited (via [`Tree::edit`]) to match
+/// `source`; pass `None` to parse from scratch.
+pub fn reparse(source: &str, old_tree: Option<&Tree>) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_solidity::LANGUAGE.into())
+        .expect("failed to load Solidity grammar");
+    parser.parse(source, old_tree)
+}
+
+/// Hash of a buffer, used to check that a cached tree belongs to it.
+pub fn text_hash(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Apply one `didChange` content change to `text`, keeping `tree` in sync.
+///
+/// Ranged changes are spliced into `text` and recorded on the tree with
+/// [`Tree::edit`]. A change without a range replaces the whole document, so
+/// the old tree is discarded.
+pub fn apply_content_change(
+    text: &mut String,
+    tree: &mut Option<Tree>,
+    change: TextDocumentContentChangeEvent,
+) {
+    let Some(range) = change.range else {
+        *text = change.text;
+        *tree = None;
+        return;
+    };
+
+    let start_byte = position_to_byte_offset(text, range.start);
+    let old_end_byte = position_to_byte_offset(text, range.end).max(start_byte);
+    let start_position = point_at(text, start_byte);
+    let old_end_position = point_at(text, old_end_byte);
+
+    text.replace_range(start_byte..old_end_byte, &change.text);
+
+    let new_end_byte = start_byte + change.text.len();
+    if let Some(tree) = tree {
+        tree.edit(&InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position: point_at(text, new_end_byte),
+        });
+    }
+}
+
+/// Tree-sitter point (row, byte column) of `byte` in `text`.
+fn point_at(text: &str, byte: usize) -> Point {
+    let before = &text[..byte];
+    let row = before.matches('\n').count();
+    let column = before.rfind('\n').map_or(byte, |nl| byte - nl - 1);
+    Point { row, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_incremental_edits_match_full_parse() {
+        let mut text = "contract A {\n    uint256 x;\n}\n".to_string();
+        let mut tree = parse(&text);
+
+        for c in [
+            change((1, 12), (1, 13), "total"),
+            change((1, 17), (1, 17), " = 1"),
+            change((2, 0), (2, 0), "    function f() public {}\n"),
+        ] {
+            apply_content_change(&mut text, &mut tree, c);
+            tree = reparse(&text, tree.as_ref());
+        }
+
+        assert_eq!(
+            text,
+            "contract A {\n    uint256 total = 1;\n    function f() public {}\n}\n"
+        );
+        let incremental = tree.unwrap().root_node().to_sexp();
+        let full = parse(&text).unwrap().root_node().to_sexp();
+        assert_eq!(incremental, full);
+    }
+
+    #[test]
+    fn test_full_change_drops_tree() {
+        let mut text = "contract A {}".to_string();
+        let mut tree = parse(&text);
+        apply_content_change(
+            &mut text,
+            &mut tree,
+            TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "contract B {}".to_string(),
+            },
+        );
+        assert_eq!(text, "contract B {}");
+        assert!(tree.is_none());
+    }
+
+    #[test]
+    fn test_document_tree_matches_source() {
+        let doc = DocumentTree::parse("contract A {}").unwrap();
+        assert!(doc.matches("contract A {}"));
+        assert!(!doc.matches("contract A { }"));
+    }
+
+    #[test]
+    fn test_point_at_counts_bytes() {
+        let text = "ab\nc\u{e9}d";
+        assert_eq!(point_at(text, 0), Point { row: 0, column: 0 });
+        assert_eq!(point_at(text, 3), Point { row: 1, column: 0 });
+        assert_eq!(point_at(text, 6), Point { row: 1, column: 3 });
+    }
+}
@@ -0,0 +1,113 @@
+//! Fixtures shared by unit tests.
+
+use crate::goto::CachedBuild;
+use serde_json::Value;
+
+/// A minimal AST node for [`build`]: an id, a `nodeType` and a source span,
+/// plus the fields the features under test read.
+#[derive(Clone, Copy)]
+pub struct AstNode<'a> {
+    id: i64,
+    node_type: &'a str,
+    offset: usize,
+    len: usize,
+    name_location: Option<(usize, usize)>,
+    referenced: Option<i64>,
+    base_functions: &'a [i64],
+}
+
+/// A node of `node_type` spanning `len` bytes at `offset`.
+pub fn node(id: i64, node_type: &str, offset: usize, len: usize) -> AstNode<'_> {
+    AstNode {
+        id,
+        node_type,
+        offset,
+        len,
+        name_location: None,
+        referenced: None,
+        base_functions: &[],
+    }
+}
+
+impl<'a> AstNode<'a> {
+    /// Set `nameLocation`.
+    pub fn name(self, offset: usize, len: usize) -> Self {
+        Self {
+            name_location: Some((offset, len)),
+            ..self
+        }
+    }
+
+    /// Set `referencedDeclaration`.
+    pub fn references(self, id: i64) -> Self {
+        Self {
+            referenced: Some(id),
+            ..self
+        }
+    }
+
+    /// Set `baseFunctions`.
+    pub fn bases(self, ids: &'a [i64]) -> Self {
+        Self {
+            base_functions: ids,
+            ..self
+        }
+    }
+}
+
+/// A build of `source` at `path` whose source unit holds `nodes`.
+pub fn build_at(path: &str, source: &str, nodes: &[AstNode]) -> CachedBuild {
+    let nodes: Vec<Value> = nodes
+        .iter()
+        .map(|n| {
+            serde_json::json!({
+                "id": n.id,
+                "nodeType": n.node_type,
+                "src": format!("{}:{}:0", n.offset, n.len),
+                "nameLocation": n.name_location.map(|(o, l)| format!("{o}:{l}:0")),
+                "referencedDeclaration": n.referenced,
+                "baseFunctions": n.base_functions,
+            })
+        })
+        .collect();
+    CachedBuild::new(output(path, source.len(), nodes.into()), 0, None)
+}
+
+/// Solc output with a single source unit at `path`, `len` bytes long,
+/// holding the raw AST `nodes` (an array). Add `contracts` or `errors` as needed.
+pub fn output(path: &str, len: usize, nodes: Value) -> Value {
+    output_files(vec![(path, len, nodes)])
+}
+
+/// [`output`] for several files; each one's index is its source id, and its
+/// source unit gets the next id (`1`, `2`, ...).
+pub fn output_files(files: Vec<(&str, usize, Value)>) -> Value {
+    let mut sources = serde_json::Map::new();
+    let mut source_id_to_path = serde_json::Map::new();
+    for (i, (path, len, nodes)) in files.into_iter().enumerate() {
+        sources.insert(
+            path.to_string(),
+            serde_json::json!({
+                "id": i,
+                "ast": {
+                    "id": i + 1, "nodeType": "SourceUnit",
+                    "src": format!("0:{len}:{i}"),
+                    "absolutePath": path,
+                    "nodes": nodes
+                }
+            }),
+        );
+        source_id_to_path.insert(i.to_string(), Value::from(path));
+    }
+    serde_json::json!({ "sources": sources, "source_id_to_path": source_id_to_path })
+}
+
+/// Like [`build_at`], with `source` written to `A.sol` in a temporary
+/// directory for features that read reference locations from disk.
+pub fn build(source: &str, nodes: &[AstNode]) -> (tempfile::TempDir, CachedBuild) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("A.sol");
+    std::fs::write(&path, source).unwrap();
+    let build = build_at(path.to_str().unwrap(), source, nodes);
+    (dir, build)
+}
@@ -0,0 +1,449 @@
+//! Type hierarchy support for the Solidity language server.
+//!
+//! This module provides the query logic and LSP conversion helpers for
+//! the Type Hierarchy feature (`textDocument/prepareTypeHierarchy`,
+//! `typeHierarchy/supertypes`, `typeHierarchy/subtypes`) over contracts,
+//! abstract contracts and interfaces.
+//!
+//! # Architecture
+//!
+//! Supertypes are the direct bases of a contract. When the build has a
+//! `decl_index` entry for the contract they are read from
+//! `ContractDefinition.baseContracts` and ordered by the contract's
+//! `linearizedBaseContracts` (kept in `CompletionCache`), i.e. in C3
+//! resolution order (most derived first). Warm-loaded builds have an empty
+//! `decl_index`, so the same edges are also recovered from the `nodes`
+//! index: every `InheritanceSpecifier` shares its `src` with the
+//! `IdentifierPath` (or `UserDefinedTypeName` on older compilers) naming
+//! the base, which carries `referencedDeclaration`, and the derived
+//! contract is the `ContractDefinition` whose span contains the specifier.
+//!
+//! Subtypes are found by scanning those edges in reverse, which works on
+//! fresh and warm-loaded builds alike. As with call hierarchy, node IDs are
+//! per-compilation, so the caller resolves the target in each build with
+//! [`resolve_contract_in_build`] before querying it.
+
+use std::collections::HashMap;
+use tower_lsp::lsp_types::{Range, SymbolKind, TypeHierarchyItem, Url};
+
+use crate::call_hierarchy::{find_file_path, resolve_target_in_build};
+use crate::goto::{CachedBuild, NodeInfo, bytes_to_pos, pos_to_bytes};
+use crate::references::byte_to_id;
+use crate::solc_ast::{ContractKind, DeclNode};
+use crate::types::{AbsPath, NodeId, SourceLoc};
+
+/// An inheritance edge `derived is base`, with the byte offset of the
+/// inheritance specifier (used to keep declaration order).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InheritanceEdge {
+    derived: NodeId,
+    base: NodeId,
+    offset: usize,
+}
+
+// ── Resolution ─────────────────────────────────────────────────────────────
+
+/// Resolve the contract a type hierarchy should be prepared for.
+///
+/// Uses `byte_to_id()` to find the innermost node at the cursor, then:
+/// 1. If the node is a `ContractDefinition`, return its ID.
+/// 2. If the node references a `ContractDefinition` (a base in an `is`
+///    list, a type name, a `new C()` expression), return that.
+/// 3. Otherwise return the contract enclosing the cursor.
+pub fn contract_at_position(
+    build: &CachedBuild,
+    abs_path: &str,
+    byte_position: usize,
+) -> Option<NodeId> {
+    let file_nodes = build.nodes.get(abs_path)?;
+
+    if let Some(id) = byte_to_id(&build.nodes, abs_path, byte_position)
+        && let Some(info) = file_nodes.get(&id)
+    {
+        if is_contract(info) {
+            return Some(id);
+        }
+        if let Some(ref_id) = info.referenced_declaration
+            && is_contract_id(build, ref_id)
+        {
+            return Some(ref_id);
+        }
+    }
+
+    file_nodes
+        .iter()
+        .filter(|(_, info)| is_contract(info))
+        .filter_map(|(id, info)| Some((*id, SourceLoc::parse(info.src.as_str())?)))
+        .find(|(_, loc)| loc.offset <= byte_position && byte_position < loc.end())
+        .map(|(id, _)| id)
+}
+
+/// Resolve a `TypeHierarchyItem` target within one build's node-ID space.
+///
+/// Returns `None` if the position does not hold a contract in this build.
+pub fn resolve_contract_in_build(
+    build: &CachedBuild,
+    node_id: NodeId,
+    target_abs: &str,
+    target_name: &str,
+    target_name_offset: usize,
+) -> Option<NodeId> {
+    resolve_target_in_build(build, node_id, target_abs, target_name, target_name_offset)
+        .into_iter()
+        .find(|id| is_contract_id(build, *id))
+}
+
+/// The cross-build anchor of a `TypeHierarchyItem`: the node ID stored in
+/// its `data`, the absolute file path, and the byte offset of its name.
+pub fn item_target(item: &TypeHierarchyItem) -> Option<(NodeId, String, usize)> {
+    let node_id = item.data.as_ref()?.get("nodeId")?.as_i64()?;
+    let target_abs = item.uri.to_file_path().ok()?.to_str()?.to_string();
+    let source_bytes = std::fs::read(&target_abs).ok()?;
+    let offset = pos_to_bytes(&source_bytes, item.selection_range.start);
+    Some((NodeId(node_id), target_abs, offset))
+}
+
+// ── Query functions ────────────────────────────────────────────────────────
+
+/// Direct bases of `contract_id`.
+///
+/// Uses `decl_index` when available (ordered by `linearizedBaseContracts`),
+/// falling back to the `nodes` index (ordered as written in the `is` list).
+pub fn supertypes(build: &CachedBuild, contract_id: NodeId) -> Vec<NodeId> {
+    if let Some(DeclNode::ContractDefinition(contract)) = build.decl_index.get(&contract_id) {
+        let mut bases: Vec<NodeId> = contract
+            .base_contracts
+            .iter()
+            .filter_map(|spec| spec.base_name.referenced_declaration)
+            .map(NodeId)
+            .collect();
+        if let Some(linearized) = build
+            .completion_cache
+            .linearized_base_contracts
+            .get(&contract_id)
+        {
+            bases.sort_by_key(|id| {
+                linearized
+                    .iter()
+                    .position(|l| l == id)
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        return bases;
+    }
+
+    let mut edges: Vec<InheritanceEdge> = build
+        .nodes
+        .values()
+        .flat_map(inheritance_edges)
+        .filter(|edge| edge.derived == contract_id)
+        .collect();
+    edges.sort_by_key(|edge| edge.offset);
+    edges.into_iter().map(|edge| edge.base).collect()
+}
+
+/// Contracts that list `contract_id` directly in their `is` list.
+pub fn subtypes(build: &CachedBuild, contract_id: NodeId) -> Vec<NodeId> {
+    let mut derived: Vec<NodeId> = build
+        .nodes
+        .values()
+        .flat_map(inheritance_edges)
+        .filter(|edge| edge.base == contract_id)
+        .map(|edge| edge.derived)
+        .collect();
+    derived.sort_unstable();
+    derived.dedup();
+    derived
+}
+
+// ── LSP conversion ─────────────────────────────────────────────────────────
+
+/// Convert a contract in `build` into a `TypeHierarchyItem`.
+///
+/// The item's `detail` is the contract kind (`interface`,
+/// `abstract contract`, `contract` or `library`) and `data` carries the
+/// node ID, like `CallHierarchyItem`s do.
+pub fn type_hierarchy_item(build: &CachedBuild, contract_id: NodeId) -> Option<TypeHierarchyItem> {
+    let (abs_path, info) = find_contract(build, contract_id)?;
+    let file_path = find_file_path(abs_path.as_str(), &build.id_to_path_map)?;
+    let source_bytes = std::fs::read(&file_path).ok()?;
+    let uri = Url::from_file_path(&file_path).ok()?;
+
+    let src_loc = SourceLoc::parse(info.src.as_str())?;
+    let range = Range {
+        start: bytes_to_pos(&source_bytes, src_loc.offset)?,
+        end: bytes_to_pos(&source_bytes, src_loc.end())?,
+    };
+
+    let name_loc = info.name_location.as_deref().and_then(SourceLoc::parse)?;
+    let selection_range = Range {
+        start: bytes_to_pos(&source_bytes, name_loc.offset)?,
+        end: bytes_to_pos(&source_bytes, name_loc.end())?,
+    };
+    let name =
+        String::from_utf8_lossy(source_bytes.get(name_loc.offset..name_loc.end())?).into_owned();
+
+    let (detail, kind) = contract_kind(build, contract_id, &source_bytes[src_loc.offset..]);
+
+    Some(TypeHierarchyItem {
+        name,
+        kind,
+        tags: None,
+        detail: Some(detail.to_string()),
+        uri,
+        range,
+        selection_range,
+        data: Some(serde_json::json!({ "nodeId": contract_id.0 })),
+    })
+}
+
+// ── Internal helpers ───────────────────────────────────────────────────────
+
+fn is_contract(info: &NodeInfo) -> bool {
+    info.node_type.as_deref() == Some("ContractDefinition")
+}
+
+fn is_contract_id(build: &CachedBuild, id: NodeId) -> bool {
+    find_contract(build, id).is_some()
+}
+
+/// Find a `ContractDefinition` and the file it is declared in.
+fn find_contract(build: &CachedBuild, id: NodeId) -> Option<(&AbsPath, &NodeInfo)> {
+    build.nodes.iter().find_map(|(abs_path, file_nodes)| {
+        file_nodes
+            .get(&id)
+            .filter(|info| is_contract(info))
+            .map(|info| (abs_path, info))
+    })
+}
+
+/// All inheritance edges declared in one file.
+fn inheritance_edges(file_nodes: &HashMap<NodeId, NodeInfo>) -> Vec<InheritanceEdge> {
+    let mut contracts: Vec<(NodeId, SourceLoc)> = Vec::new();
+    let mut specifiers: Vec<(&str, SourceLoc)> = Vec::new();
+    let mut base_names: HashMap<&str, NodeId> = HashMap::new();
+
+    for (id, info) in file_nodes {
+        match info.node_type.as_deref() {
+            Some("ContractDefinition") => {
+                if let Some(loc) = SourceLoc::parse(info.src.as_str()) {
+                    contracts.push((*id, loc));
+                }
+            }
+            Some("InheritanceSpecifier") => {
+                if let Some(loc) = SourceLoc::parse(info.src.as_str()) {
+                    specifiers.push((info.src.as_str(), loc));
+                }
+            }
+            Some("IdentifierPath" | "UserDefinedTypeName") => {
+                if let Some(ref_id) = info.referenced_declaration {
+                    base_names.insert(info.src.as_str(), ref_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    specifiers
+        .into_iter()
+        .filter_map(|(src, spec)| {
+            let base = *base_names.get(src)?;
+            let derived = contracts
+                .iter()
+                .find(|(_, loc)| loc.offset <= spec.offset && spec.end() <= loc.end())?
+                .0;
+            Some(InheritanceEdge {
+                derived,
+                base,
+                offset: spec.offset,
+            })
+        })
+        .collect()
+}
+
+/// Detail label and symbol kind for a contract.
+///
+/// Reads the kind from `decl_index` when available, otherwise from the
+/// leading keyword of the declaration (`source` starts at its `src` offset).
+/// `abstract` is stripped from `decl_index`, so it always comes from source.
+fn contract_kind(build: &CachedBuild, id: NodeId, source: &[u8]) -> (&'static str, SymbolKind) {
+    let label = match build.decl_index.get(&id) {
+        Some(DeclNode::ContractDefinition(c)) => match c.contract_kind {
+            ContractKind::Interface => "interface",
+            ContractKind::Library => "library",
+            ContractKind::Contract if source.starts_with(b"abstract") => "abstract contract",
+            ContractKind::Contract => "contract",
+        },
+        _ if source.starts_with(b"interface") => "interface",
+        _ if source.starts_with(b"library") => "library",
+        _ if source.starts_with(b"abstract") => "abstract contract",
+        _ => "contract",
+    };
+    let kind = if label == "interface" {
+        SymbolKind::INTERFACE
+    } else {
+        SymbolKind::CLASS
+    };
+    (label, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SOURCE: &str =
+        "interface I {}\nabstract contract A is I {}\ncontract B is A {}\ncontract C is A, I {}\n";
+
+    fn contract(
+        id: i64,
+        src: &str,
+        name: &str,
+        name_location: &str,
+        kind: &str,
+        bases: serde_json::Value,
+        linearized: serde_json::Value,
+    ) -> serde_json::Value {
+        json!({
+            "id": id,
+            "nodeType": "ContractDefinition",
+            "src": src,
+            "name": name,
+            "nameLocation": name_location,
+            "contractKind": kind,
+            "abstract": name == "A",
+            "baseContracts": bases,
+            "linearizedBaseContracts": linearized,
+            "nodes": []
+        })
+    }
+
+    fn base(id: i64, src: &str, name: &str, referenced: i64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "nodeType": "InheritanceSpecifier",
+            "src": src,
+            "baseName": {
+                "id": id + 1,
+                "nodeType": "IdentifierPath",
+                "name": name,
+                "nameLocations": [src],
+                "referencedDeclaration": referenced,
+                "src": src
+            }
+        })
+    }
+
+    /// ```solidity
+    /// interface I {}
+    /// abstract contract A is I {}
+    /// contract B is A {}
+    /// contract C is A, I {}
+    /// ```
+    fn build(path: &str) -> CachedBuild {
+        let nodes = json!([
+            contract(
+                2,
+                "0:14:0",
+                "I",
+                "10:1:0",
+                "interface",
+                json!([]),
+                json!([2])
+            ),
+            contract(
+                3,
+                "15:27:0",
+                "A",
+                "33:1:0",
+                "contract",
+                json!([base(4, "38:1:0", "I", 2)]),
+                json!([3, 2])
+            ),
+            contract(
+                6,
+                "43:18:0",
+                "B",
+                "52:1:0",
+                "contract",
+                json!([base(7, "57:1:0", "A", 3)]),
+                json!([6, 3, 2])
+            ),
+            contract(
+                9,
+                "62:21:0",
+                "C",
+                "71:1:0",
+                "contract",
+                json!([base(10, "76:1:0", "A", 3), base(12, "79:1:0", "I", 2)]),
+                json!([9, 3, 2])
+            )
+        ]);
+        CachedBuild::new(crate::test_support::output(path, 84, nodes), 0, None)
+    }
+
+    #[test]
+    fn test_contract_at_position() {
+        let build = build("/tmp/Types.sol");
+        // On a contract name.
+        assert_eq!(
+            contract_at_position(&build, "/tmp/Types.sol", 52),
+            Some(NodeId(6))
+        );
+        // On a base in an `is` list.
+        assert_eq!(
+            contract_at_position(&build, "/tmp/Types.sol", 79),
+            Some(NodeId(2))
+        );
+        // Inside a contract body.
+        assert_eq!(
+            contract_at_position(&build, "/tmp/Types.sol", 60),
+            Some(NodeId(6))
+        );
+    }
+
+    #[test]
+    fn test_supertypes_and_subtypes() {
+        let build = build("/tmp/Types.sol");
+        assert_eq!(supertypes(&build, NodeId(9)), vec![NodeId(3), NodeId(2)]);
+        assert_eq!(supertypes(&build, NodeId(2)), vec![]);
+        assert_eq!(subtypes(&build, NodeId(2)), vec![NodeId(3), NodeId(9)]);
+        assert_eq!(subtypes(&build, NodeId(3)), vec![NodeId(6), NodeId(9)]);
+        assert_eq!(subtypes(&build, NodeId(9)), vec![]);
+    }
+
+    #[test]
+    fn test_supertypes_without_decl_index() {
+        let mut build = build("/tmp/Types.sol");
+        build.decl_index.clear();
+        assert_eq!(supertypes(&build, NodeId(9)), vec![NodeId(3), NodeId(2)]);
+        assert_eq!(supertypes(&build, NodeId(3)), vec![NodeId(2)]);
+    }
+
+    #[test]
+    fn test_type_hierarchy_item() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Types.sol");
+        std::fs::write(&path, SOURCE).unwrap();
+        let mut build = build(path.to_str().unwrap());
+
+        let item = type_hierarchy_item(&build, NodeId(2)).unwrap();
+        assert_eq!(item.name, "I");
+        assert_eq!(item.kind, SymbolKind::INTERFACE);
+        assert_eq!(item.detail.as_deref(), Some("interface"));
+        assert_eq!(item.selection_range.start.line, 0);
+        assert_eq!(item.data, Some(json!({ "nodeId": 2 })));
+
+        let item = type_hierarchy_item(&build, NodeId(3)).unwrap();
+        assert_eq!(item.kind, SymbolKind::CLASS);
+        assert_eq!(item.detail.as_deref(), Some("abstract contract"));
+        assert_eq!(item.selection_range.start.character, 18);
+
+        // Warm-loaded builds fall back to the declaration keyword.
+        build.decl_index.clear();
+        let item = type_hierarchy_item(&build, NodeId(3)).unwrap();
+        assert_eq!(item.detail.as_deref(), Some("abstract contract"));
+        let item = type_hierarchy_item(&build, NodeId(6)).unwrap();
+        assert_eq!(item.detail.as_deref(), Some("contract"));
+    }
+}
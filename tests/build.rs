@@ -1,4 +1,6 @@
-use solidity_language_server::build::{build_output_to_diagnostics, ignored_error_code_warning};
+use solidity_language_server::build::{
+    build_output_to_diagnostics, ignored_error_code_warning, project_diagnostics,
+};
 use solidity_language_server::runner::{ForgeRunner, Runner};
 use solidity_language_server::utils::byte_offset_to_position;
 use std::fs;
@@ -323,3 +325,42 @@ fn test_source_location_matches_subdirectory_project() {
     );
     assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
 }
+
+#[test]
+fn test_project_diagnostics_groups_errors_by_file() {
+    let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let src_dir = temp_dir.path().join("src");
+    fs::create_dir(&src_dir).expect("failed to create src dir");
+    fs::write(src_dir.join("A.sol"), "contract A {}\n").unwrap();
+    fs::write(src_dir.join("B.sol"), "contract B {\n    uint x;\n}\n").unwrap();
+
+    let error = |file: &str, start: u64, end: u64, severity: &str, code: &str| {
+        serde_json::json!({
+            "sourceLocation": { "file": file, "start": start, "end": end },
+            "severity": severity,
+            "errorCode": code,
+            "message": format!("{severity} {code}")
+        })
+    };
+    let solc_output = serde_json::json!({
+        "errors": [
+            error("src/A.sol", 0, 8, "warning", "1878"),
+            error("src/B.sol", 17, 23, "error", "7576"),
+            error("src/B.sol", 0, 8, "warning", "5574"),
+            error("src/Missing.sol", 0, 1, "error", "6275")
+        ]
+    });
+
+    let diags = project_diagnostics(&solc_output, temp_dir.path(), &[]);
+    assert_eq!(diags.len(), 2, "unreadable files are skipped");
+
+    let a = &diags[&src_dir.join("A.sol")];
+    assert_eq!(a.len(), 1);
+    assert_eq!(a[0].severity, Some(DiagnosticSeverity::WARNING));
+
+    // 5574 is suppressed by default, so only the error remains.
+    let b = &diags[&src_dir.join("B.sol")];
+    assert_eq!(b.len(), 1);
+    assert_eq!(b[0].code, Some(NumberOrString::String("7576".into())));
+    assert_eq!(b[0].range.start.line, 1);
+}
@@ -1003,6 +1003,76 @@ fn test_expand_folder_deletes_from_paths_uses_component_prefix() {
     assert!(expanded[0].ends_with("/tmp/project/src/A.sol"));
 }
 
+// =============================================================================
+// rename_file_edit tests
+// =============================================================================
+
+#[test]
+fn test_rename_file_edit_merges_edits_and_renames_last() {
+    use std::collections::HashMap;
+    use tower_lsp::lsp_types::{
+        DocumentChangeOperation, DocumentChanges, OneOf, Position, Range, ResourceOp, TextEdit,
+    };
+
+    let edit = |line, start, end, text: &str| TextEdit {
+        range: Range {
+            start: Position::new(line, start),
+            end: Position::new(line, end),
+        },
+        new_text: text.to_string(),
+    };
+    let vault = Url::from_file_path("/tmp/project/src/Vault.sol").unwrap();
+    let vault_v2 = Url::from_file_path("/tmp/project/src/VaultV2.sol").unwrap();
+    let user = Url::from_file_path("/tmp/project/src/User.sol").unwrap();
+
+    let changes = HashMap::from([
+        (vault.clone(), vec![edit(0, 9, 14, "VaultV2")]),
+        (
+            user.clone(),
+            vec![edit(0, 8, 13, "VaultV2"), edit(2, 4, 9, "VaultV2")],
+        ),
+    ]);
+    let imports = HashMap::from([(user.clone(), vec![edit(0, 20, 33, "\"./VaultV2.sol\"")])]);
+
+    let result =
+        file_operations::rename_file_edit(changes, imports, vault.clone(), vault_v2.clone());
+    assert!(result.changes.is_none());
+    let Some(DocumentChanges::Operations(ops)) = result.document_changes else {
+        panic!("expected document change operations");
+    };
+    assert_eq!(ops.len(), 3);
+
+    let edits_for = |uri: &Url| {
+        ops.iter()
+            .find_map(|op| match op {
+                DocumentChangeOperation::Edit(e) if &e.text_document.uri == uri => Some(
+                    e.edits
+                        .iter()
+                        .map(|e| match e {
+                            OneOf::Left(e) => e.new_text.clone(),
+                            OneOf::Right(e) => e.text_edit.new_text.clone(),
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                _ => None,
+            })
+            .unwrap()
+    };
+    assert_eq!(edits_for(&vault), vec!["VaultV2"]);
+    assert_eq!(
+        edits_for(&user),
+        vec!["VaultV2", "VaultV2", "\"./VaultV2.sol\""]
+    );
+
+    match ops.last() {
+        Some(DocumentChangeOperation::Op(ResourceOp::Rename(rename))) => {
+            assert_eq!(rename.old_uri, vault);
+            assert_eq!(rename.new_uri, vault_v2);
+        }
+        other => panic!("expected trailing RenameFile, got {other:?}"),
+    }
+}
+
 // =============================================================================
 // Scaffold generation tests
 // =============================================================================